@@ -1,67 +1,125 @@
-use std::{cmp::Ordering, hint};
-
-// Copied from std, passes an index to the provided predicate function
-#[inline]
-#[expect(clippy::cast_lossless)]
-pub fn binary_search_by_with_index<'a, T, F>(data: &'a [T], mut f: F) -> Result<usize, usize>
-where
-    F: FnMut(usize, &'a T) -> Ordering,
-{
-    let mut size = data.len();
-    if size == 0 {
-        return Err(0);
-    }
-    let mut base = 0usize;
-
-    // This loop intentionally doesn't have an early exit if the comparison
-    // returns Equal. We want the number of loop iterations to depend *only*
-    // on the size of the input slice so that the CPU can reliably predict
-    // the loop count.
-    while size > 1 {
-        let half = size / 2;
-        let mid = base + half;
-
-        // SAFETY: the call is made safe by the following inconstants:
-        // - `mid >= 0`: by definition
-        // - `mid < size`: `mid = size / 2 + size / 4 + size / 8 ...`
-        let cmp = f(mid, unsafe { data.get_unchecked(mid) });
-
-        // Binary search interacts poorly with branch prediction, so force
-        // the compiler to use conditional moves if supported by the target
-        // architecture.
-        base = select_unpredictable(cmp == Ordering::Greater, base, mid);
-
-        // This is imprecise in the case where `size` is odd and the
-        // comparison returns Greater: the mid element still gets included
-        // by `size` even though it's known to be larger than the element
-        // being searched for.
-        //
-        // This is fine though: we gain more performance by keeping the
-        // loop iteration count invariant (and thus predictable) than we
-        // lose from considering one additional element.
-        size -= half;
+use std::ops::Range;
+
+/// A sample of a time series, the samples being sorted by time.
+pub trait Timestamped {
+    /// In nanoseconds
+    fn time(&self) -> i64;
+}
+
+impl<V> Timestamped for (i64, V) {
+    fn time(&self) -> i64 {
+        self.0
     }
+}
+
+/// The index of the first sample at or after `time`, the number of samples if there are none.
+pub fn lower_bound<T: Timestamped>(samples: &[T], time: i64) -> usize {
+    samples.partition_point(|sample| sample.time() < time)
+}
 
-    // SAFETY: base is always in [0, size) because base <= mid.
-    let cmp = f(base, unsafe { data.get_unchecked(base) });
-    if cmp == Ordering::Equal {
-        // SAFETY: same as the `get_unchecked` above.
-        unsafe { hint::assert_unchecked(base < data.len()) };
-        Ok(base)
-    } else {
-        let result = base + (cmp == Ordering::Less) as usize;
-        // SAFETY: same as the `get_unchecked` above.
-        // Note that this is `<=`, unlike the assume in the `Ok` path.
-        unsafe { hint::assert_unchecked(result <= data.len()) };
-        Err(result)
+/// The index of the first sample after `time`, the number of samples if there are none.
+pub fn upper_bound<T: Timestamped>(samples: &[T], time: i64) -> usize {
+    samples.partition_point(|sample| sample.time() <= time)
+}
+
+/// The indices of the samples between `begin` and `end` (inclusive), padded by one sample on
+/// each side so that the lines going out of the range are drawn too.
+///
+/// The range is empty when all the samples are before or after the view, since no line crosses it.
+pub fn visible_range<T: Timestamped>(samples: &[T], begin: i64, end: i64) -> Range<usize> {
+    let mut first = lower_bound(samples, begin);
+    let mut last = upper_bound(samples, end).max(first);
+
+    // A line only enters the view if it reaches a sample at or after `begin`
+    if first > 0 && first < samples.len() {
+        first -= 1;
     }
+    // and only leaves it if it starts from a sample at or before `end`
+    if last > 0 && last < samples.len() {
+        last += 1;
+    }
+
+    first..last
 }
 
-#[inline]
-fn select_unpredictable<T>(b: bool, true_val: T, false_val: T) -> T {
-    if b {
-        true_val
-    } else {
-        false_val
+/// The samples between `begin` and `end`, padded like `visible_range`.
+pub fn visible_slice<T: Timestamped>(samples: &[T], begin: i64, end: i64) -> &[T] {
+    &samples[visible_range(samples, begin, end)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(times: &[i64]) -> Vec<(i64, f64)> {
+        times.iter().map(|time| (*time, 0.0)).collect()
+    }
+
+    #[test]
+    fn bounds() {
+        let empty = samples(&[]);
+        assert_eq!(lower_bound(&empty, 0), 0);
+        assert_eq!(upper_bound(&empty, 0), 0);
+
+        let samples = samples(&[10, 20, 20, 20, 30]);
+        assert_eq!(lower_bound(&samples, 5), 0);
+        assert_eq!(upper_bound(&samples, 5), 0);
+        assert_eq!(lower_bound(&samples, 10), 0);
+        assert_eq!(upper_bound(&samples, 10), 1);
+        assert_eq!(lower_bound(&samples, 20), 1);
+        assert_eq!(upper_bound(&samples, 20), 4);
+        assert_eq!(lower_bound(&samples, 25), 4);
+        assert_eq!(upper_bound(&samples, 25), 4);
+        assert_eq!(lower_bound(&samples, 30), 4);
+        assert_eq!(upper_bound(&samples, 30), 5);
+        assert_eq!(lower_bound(&samples, 35), 5);
+        assert_eq!(upper_bound(&samples, 35), 5);
+    }
+
+    #[test]
+    fn visible_range_of_no_samples() {
+        assert_eq!(visible_range(&samples(&[]), 0, 10), 0..0);
+    }
+
+    #[test]
+    fn series_outside_the_view_are_not_visible() {
+        let samples = samples(&[10, 20, 30]);
+        assert!(visible_range(&samples, 40, 50).is_empty());
+        assert!(visible_range(&samples, 0, 5).is_empty());
+        assert!(visible_slice(&samples, 40, 50).is_empty());
+    }
+
+    #[test]
+    fn visible_range_is_padded_by_one_sample() {
+        let samples = samples(&[10, 20, 30, 40, 50]);
+        assert_eq!(visible_range(&samples, 25, 35), 1..4);
+        assert_eq!(visible_range(&samples, 0, 100), 0..5);
+        assert_eq!(visible_range(&samples, 0, 15), 0..2);
+        assert_eq!(visible_range(&samples, 45, 100), 3..5);
+    }
+
+    #[test]
+    fn lines_crossing_the_whole_view_are_visible() {
+        let samples = samples(&[10, 20, 30]);
+        assert_eq!(visible_range(&samples, 12, 18), 0..2);
+    }
+
+    #[test]
+    fn samples_at_the_boundaries_are_visible() {
+        let samples = samples(&[10, 20, 30, 40]);
+        assert_eq!(visible_range(&samples, 20, 30), 0..4);
+        assert_eq!(visible_range(&samples, 40, 50), 2..4);
+        assert_eq!(visible_range(&samples, 0, 10), 0..2);
+        assert_eq!(visible_range(&samples, 20, 20), 0..3);
+    }
+
+    #[test]
+    fn duplicate_timestamps_are_all_visible() {
+        let samples = samples(&[10, 20, 20, 20, 30]);
+        assert_eq!(visible_range(&samples, 20, 20), 0..5);
+        assert_eq!(visible_range(&samples, 15, 25), 0..5);
+        assert_eq!(visible_range(&samples, 0, 20), 0..5);
+        assert_eq!(visible_range(&samples, 20, 100), 0..5);
+        assert_eq!(visible_range(&samples, 21, 100), 3..5);
     }
 }
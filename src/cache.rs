@@ -0,0 +1,297 @@
+use std::cell::RefCell;
+
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use js_sys::{Array, Promise, Uint8Array};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    IdbDatabase, IdbObjectStore, IdbRequest, IdbTransaction, IdbTransactionMode, Window,
+    WorkerGlobalScope,
+};
+
+const DATABASE_NAME: &str = "sirius";
+const DATABASE_VERSION: u32 = 1;
+
+/// Raw Parquet payloads, by cache key
+const PAYLOADS_STORE: &str = "payloads";
+/// Size and last access time of the payloads, by cache key
+const ENTRIES_STORE: &str = "entries";
+
+/// The least recently used payloads are evicted above this size.
+const MAX_CACHE_SIZE: f64 = 256.0 * 1_024.0 * 1_024.0;
+
+thread_local! {
+    static DATABASE: RefCell<Option<IdbDatabase>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    /// Size of the payload, in bytes
+    size: f64,
+    /// Milliseconds since the Unix epoch
+    last_access: f64,
+}
+
+/// The number of payloads in the cache and their total size in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheUsage {
+    pub count: usize,
+    pub size: f64,
+}
+
+/// Returns the cached payload for `key`, if any.
+pub async fn get(key: &str) -> Result<Option<Bytes>> {
+    let database = open_database().await?;
+    let (_transaction, payloads, _entries) = stores(&database, IdbTransactionMode::Readonly)?;
+
+    let payload = await_request(&payloads.get(&key.into()).map_err(js_error)?).await?;
+    if payload.is_undefined() {
+        return Ok(None);
+    }
+
+    let payload = Uint8Array::new(&payload).to_vec();
+    let entry = CacheEntry {
+        size: to_f64(payload.len()),
+        last_access: js_sys::Date::now(),
+    };
+    let (transaction, _payloads, entries) = stores(&database, IdbTransactionMode::Readwrite)?;
+    put_entry(&entries, key, &entry)?;
+    await_transaction(&transaction).await?;
+
+    debug!(key, "cache hit");
+
+    Ok(Some(Bytes::from(payload)))
+}
+
+/// Stores `payload` for `key`, and evicts the least recently used payloads above the budget.
+pub async fn put(key: &str, payload: &[u8]) -> Result<()> {
+    if to_f64(payload.len()) > MAX_CACHE_SIZE {
+        return Ok(());
+    }
+
+    let database = open_database().await?;
+    let (transaction, payloads, entries) = stores(&database, IdbTransactionMode::Readwrite)?;
+
+    let entry = CacheEntry {
+        size: to_f64(payload.len()),
+        last_access: js_sys::Date::now(),
+    };
+    payloads
+        .put_with_key(&Uint8Array::from(payload), &key.into())
+        .map_err(js_error)?;
+    put_entry(&entries, key, &entry)?;
+    await_transaction(&transaction).await?;
+
+    evict(&database).await
+}
+
+/// Removes all the cached payloads.
+pub async fn clear() -> Result<()> {
+    let database = open_database().await?;
+    let (transaction, payloads, entries) = stores(&database, IdbTransactionMode::Readwrite)?;
+
+    payloads.clear().map_err(js_error)?;
+    entries.clear().map_err(js_error)?;
+
+    await_transaction(&transaction).await
+}
+
+/// The number and total size of the cached payloads.
+pub async fn usage() -> Result<CacheUsage> {
+    let database = open_database().await?;
+    let entries = all_entries(&database).await?;
+
+    Ok(CacheUsage {
+        count: entries.len(),
+        size: entries.iter().map(|(_key, entry)| entry.size).sum(),
+    })
+}
+
+async fn evict(database: &IdbDatabase) -> Result<()> {
+    let mut all_entries = all_entries(database).await?;
+    let mut size = all_entries
+        .iter()
+        .map(|(_key, entry)| entry.size)
+        .sum::<f64>();
+    if size <= MAX_CACHE_SIZE {
+        return Ok(());
+    }
+
+    all_entries.sort_by(|(_, a), (_, b)| a.last_access.total_cmp(&b.last_access));
+
+    let (transaction, payloads, entries) = stores(database, IdbTransactionMode::Readwrite)?;
+    for (key, entry) in all_entries {
+        if size <= MAX_CACHE_SIZE {
+            break;
+        }
+
+        debug!(key = key.as_string(), "cache eviction");
+        payloads.delete(&key).map_err(js_error)?;
+        entries.delete(&key).map_err(js_error)?;
+        size -= entry.size;
+    }
+
+    await_transaction(&transaction).await
+}
+
+async fn all_entries(database: &IdbDatabase) -> Result<Vec<(JsValue, CacheEntry)>> {
+    let (_transaction, _payloads, entries) = stores(database, IdbTransactionMode::Readonly)?;
+
+    // Both requests are issued before waiting, the transaction being still active
+    let keys = entries.get_all_keys().map_err(js_error)?;
+    let values = entries.get_all().map_err(js_error)?;
+    let keys = await_request(&keys).await?;
+    let values = await_request(&values).await?;
+
+    Array::from(&keys)
+        .iter()
+        .zip(Array::from(&values).iter())
+        .map(|(key, value)| {
+            let entry = serde_wasm_bindgen::from_value(value)
+                .map_err(|err| anyhow!("cache entry deserialization error: {err}"))?;
+            Ok((key, entry))
+        })
+        .collect()
+}
+
+fn put_entry(entries: &IdbObjectStore, key: &str, entry: &CacheEntry) -> Result<()> {
+    let entry = serde_wasm_bindgen::to_value(entry)
+        .map_err(|err| anyhow!("cache entry serialization error: {err}"))?;
+    entries
+        .put_with_key(&entry, &key.into())
+        .map_err(js_error)?;
+
+    Ok(())
+}
+
+async fn open_database() -> Result<IdbDatabase> {
+    if let Some(database) = DATABASE.with_borrow(Clone::clone) {
+        return Ok(database);
+    }
+
+    // The cache is also used from the data pipeline worker, which has no window
+    let global = js_sys::global();
+    let factory = if let Some(window) = global.dyn_ref::<Window>() {
+        window.indexed_db()
+    } else if let Some(worker) = global.dyn_ref::<WorkerGlobalScope>() {
+        worker.indexed_db()
+    } else {
+        Ok(None)
+    }
+    .map_err(js_error)?
+    .ok_or_else(|| anyhow!("IndexedDB is not available"))?;
+    let open_request = factory
+        .open_with_u32(DATABASE_NAME, DATABASE_VERSION)
+        .map_err(js_error)?;
+
+    let upgrade_request = open_request.clone();
+    let on_upgrade_needed = Closure::once_into_js(move || {
+        let Ok(database) = upgrade_request.result() else {
+            return;
+        };
+        let database = database.unchecked_into::<IdbDatabase>();
+        for store in [PAYLOADS_STORE, ENTRIES_STORE] {
+            if let Err(err) = database.create_object_store(store) {
+                error!(store, "object store creation error: {err:?}");
+            }
+        }
+    });
+    open_request.set_onupgradeneeded(Some(on_upgrade_needed.unchecked_ref()));
+
+    let database = await_request(&open_request)
+        .await?
+        .unchecked_into::<IdbDatabase>();
+    DATABASE.set(Some(database.clone()));
+
+    Ok(database)
+}
+
+/// A transaction over both stores, the requests of which must all be issued before waiting for
+/// anything else than them, since the transaction commits as soon as none is pending.
+fn stores(
+    database: &IdbDatabase,
+    mode: IdbTransactionMode,
+) -> Result<(IdbTransaction, IdbObjectStore, IdbObjectStore)> {
+    let names = Array::of2(&PAYLOADS_STORE.into(), &ENTRIES_STORE.into());
+    let transaction = database
+        .transaction_with_str_sequence_and_mode(&names, mode)
+        .map_err(js_error)?;
+
+    let payloads = transaction.object_store(PAYLOADS_STORE).map_err(js_error)?;
+    let entries = transaction.object_store(ENTRIES_STORE).map_err(js_error)?;
+
+    Ok((transaction, payloads, entries))
+}
+
+/// Waits for all the requests of a transaction to be committed.
+async fn await_transaction(transaction: &IdbTransaction) -> Result<()> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let on_complete = Closure::once_into_js(move || {
+            if let Err(err) = resolve.call0(&JsValue::UNDEFINED) {
+                error!("promise resolution error: {err:?}");
+            }
+        });
+
+        let error_transaction = transaction.clone();
+        let on_error = Closure::<dyn Fn()>::new(move || {
+            let error = error_transaction
+                .error()
+                .map_or(JsValue::UNDEFINED, Into::into);
+            if let Err(err) = reject.call1(&JsValue::UNDEFINED, &error) {
+                error!("promise rejection error: {err:?}");
+            }
+        })
+        .into_js_value();
+
+        transaction.set_oncomplete(Some(on_complete.unchecked_ref()));
+        // A failed request aborts the transaction, which is reported by both events
+        transaction.set_onerror(Some(on_error.unchecked_ref()));
+        transaction.set_onabort(Some(on_error.unchecked_ref()));
+    });
+
+    JsFuture::from(promise).await.map_err(js_error)?;
+    Ok(())
+}
+
+/// Waits for the completion of an `IndexedDB` request, and returns its result.
+async fn await_request(request: &IdbRequest) -> Result<JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let success_request = request.clone();
+        let on_success = Closure::once_into_js(move || {
+            let result = success_request.result().unwrap_or(JsValue::UNDEFINED);
+            if let Err(err) = resolve.call1(&JsValue::UNDEFINED, &result) {
+                error!("promise resolution error: {err:?}");
+            }
+        });
+
+        let error_request = request.clone();
+        let on_error = Closure::once_into_js(move || {
+            let error = error_request
+                .error()
+                .ok()
+                .flatten()
+                .map_or(JsValue::UNDEFINED, Into::into);
+            if let Err(err) = reject.call1(&JsValue::UNDEFINED, &error) {
+                error!("promise rejection error: {err:?}");
+            }
+        });
+
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+
+    JsFuture::from(promise).await.map_err(js_error)
+}
+
+#[expect(clippy::needless_pass_by_value)]
+fn js_error(err: JsValue) -> anyhow::Error {
+    anyhow!("IndexedDB error: {err:?}")
+}
+
+#[expect(clippy::cast_precision_loss)]
+fn to_f64(size: usize) -> f64 {
+    size as f64
+}
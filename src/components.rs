@@ -1,4 +1,8 @@
-use leptos::{component, view, IntoView};
+use chrono::{DateTime, Utc};
+use leptos::{component, view, Callable, Callback, IntoView, Signal, SignalGet};
+use leptos_router::A;
+
+use crate::measures::canvas::time_to_x;
 
 #[component]
 pub fn Spinner() -> impl IntoView {
@@ -25,3 +29,50 @@ pub fn Spinner() -> impl IntoView {
         </svg>
     }
 }
+
+#[component]
+pub fn TimeSelectionOverlay(
+    process_id: Signal<String>,
+    selection: Signal<Option<(i64, i64)>>,
+    begin: Signal<DateTime<Utc>>,
+    end: Signal<DateTime<Utc>>,
+    canvas_width: Signal<f64>,
+    #[prop(into)] on_clear: Callback<()>,
+) -> impl IntoView {
+    let position = move || {
+        let (selection_begin, selection_end) = selection.get()?;
+        let begin_ns = begin.get().timestamp_nanos_opt()?;
+        let end_ns = end.get().timestamp_nanos_opt()?;
+        let width = canvas_width.get();
+
+        let left = time_to_x(selection_begin, begin_ns, end_ns, width).clamp(0.0, width);
+        let right = time_to_x(selection_end, begin_ns, end_ns, width).clamp(0.0, width);
+
+        Some((left, right - left))
+    };
+
+    let flamegraph_href = move || {
+        let (selection_begin, selection_end) = selection.get().unwrap_or_default();
+        format!(
+            "/flamegraph/{}?begin={selection_begin}&end={selection_end}",
+            process_id.get()
+        )
+    };
+
+    view! {
+        <div
+            class="absolute top-0 h-full bg-sky-400/20 border-x border-sky-400 pointer-events-none"
+            class:hidden=move || position().is_none()
+            style:left=move || format!("{}px", position().unwrap_or_default().0)
+            style:width=move || format!("{}px", position().unwrap_or_default().1)
+        >
+            <div class="flex gap-2 p-1 text-xs pointer-events-auto">
+                <A href=flamegraph_href>"Flame graph"</A>
+                <a href="#" on:click=move |evt| {
+                    evt.prevent_default();
+                    on_clear.call(());
+                }>"Clear"</a>
+            </div>
+        </div>
+    }
+}
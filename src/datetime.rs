@@ -3,3 +3,15 @@ use chrono::{DateTime, Utc};
 pub fn display_datetime(datetime: DateTime<Utc>) -> String {
     datetime.with_timezone(&chrono::Local).to_rfc3339()
 }
+
+/// Displays a duration expressed in nanoseconds using the most readable unit.
+#[expect(clippy::cast_precision_loss)]
+pub fn display_duration(duration_ns: i64) -> String {
+    let duration = duration_ns as f64;
+    match duration_ns.unsigned_abs() {
+        0..1_000 => format!("{duration_ns} ns"),
+        1_000..1_000_000 => format!("{:.2} µs", duration / 1_000.0),
+        1_000_000..1_000_000_000 => format!("{:.2} ms", duration / 1_000_000.0),
+        _ => format!("{:.2} s", duration / 1_000_000_000.0),
+    }
+}
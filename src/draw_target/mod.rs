@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod recording;
+mod svg;
+
+use anyhow::{anyhow, Result};
+use web_sys::CanvasRenderingContext2d;
+
+#[cfg(test)]
+pub use recording::Recording;
+pub use svg::SvgDocument;
+
+/// The drawing operations used by the charts, following the canvas 2D API.
+///
+/// Coordinates are in pixels, colors and fonts are CSS values.
+pub trait DrawTarget {
+    fn save(&mut self);
+    fn restore(&mut self);
+    fn scale(&mut self, x: f64, y: f64) -> Result<()>;
+
+    fn set_font(&mut self, font: &str);
+    fn set_fill_style(&mut self, color: &str);
+    fn set_stroke_style(&mut self, color: &str);
+
+    fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64);
+    fn clear_rect(&mut self, x: f64, y: f64, width: f64, height: f64);
+
+    fn begin_path(&mut self);
+    fn move_to(&mut self, x: f64, y: f64);
+    fn line_to(&mut self, x: f64, y: f64);
+    /// Angles are in radians, clockwise from the positive x axis.
+    fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) -> Result<()>;
+    fn stroke(&mut self);
+    fn fill(&mut self);
+
+    fn fill_text(&mut self, text: &str, x: f64, y: f64) -> Result<()>;
+}
+
+impl DrawTarget for CanvasRenderingContext2d {
+    fn save(&mut self) {
+        CanvasRenderingContext2d::save(self);
+    }
+
+    fn restore(&mut self) {
+        CanvasRenderingContext2d::restore(self);
+    }
+
+    fn scale(&mut self, x: f64, y: f64) -> Result<()> {
+        CanvasRenderingContext2d::scale(self, x, y).map_err(|err| anyhow!("{err:?}"))
+    }
+
+    fn set_font(&mut self, font: &str) {
+        CanvasRenderingContext2d::set_font(self, font);
+    }
+
+    fn set_fill_style(&mut self, color: &str) {
+        CanvasRenderingContext2d::set_fill_style(self, &color.into());
+    }
+
+    fn set_stroke_style(&mut self, color: &str) {
+        CanvasRenderingContext2d::set_stroke_style(self, &color.into());
+    }
+
+    fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        CanvasRenderingContext2d::fill_rect(self, x, y, width, height);
+    }
+
+    fn clear_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        CanvasRenderingContext2d::clear_rect(self, x, y, width, height);
+    }
+
+    fn begin_path(&mut self) {
+        CanvasRenderingContext2d::begin_path(self);
+    }
+
+    fn move_to(&mut self, x: f64, y: f64) {
+        CanvasRenderingContext2d::move_to(self, x, y);
+    }
+
+    fn line_to(&mut self, x: f64, y: f64) {
+        CanvasRenderingContext2d::line_to(self, x, y);
+    }
+
+    fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) -> Result<()> {
+        CanvasRenderingContext2d::arc(self, x, y, radius, start_angle, end_angle)
+            .map_err(|err| anyhow!("{err:?}"))
+    }
+
+    fn stroke(&mut self) {
+        CanvasRenderingContext2d::stroke(self);
+    }
+
+    fn fill(&mut self) {
+        CanvasRenderingContext2d::fill(self);
+    }
+
+    fn fill_text(&mut self, text: &str, x: f64, y: f64) -> Result<()> {
+        CanvasRenderingContext2d::fill_text(self, text, x, y).map_err(|err| anyhow!("{err:?}"))
+    }
+}
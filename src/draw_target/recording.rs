@@ -0,0 +1,143 @@
+use std::fmt;
+
+use anyhow::Result;
+
+use super::DrawTarget;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    Save,
+    Restore,
+    Scale(f64, f64),
+    SetFont(String),
+    SetFillStyle(String),
+    SetStrokeStyle(String),
+    FillRect(f64, f64, f64, f64),
+    ClearRect(f64, f64, f64, f64),
+    BeginPath,
+    MoveTo(f64, f64),
+    LineTo(f64, f64),
+    Arc(f64, f64, f64, f64, f64),
+    Stroke,
+    Fill,
+    FillText(String, f64, f64),
+}
+
+impl fmt::Display for DrawCommand {
+    /// Coordinates are rounded to the hundredth of a pixel, so that snapshots don't depend on
+    /// floating point noise.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Save => write!(f, "save"),
+            Self::Restore => write!(f, "restore"),
+            Self::Scale(x, y) => write!(f, "scale {x:.2} {y:.2}"),
+            Self::SetFont(font) => write!(f, "font {font:?}"),
+            Self::SetFillStyle(color) => write!(f, "fill_style {color:?}"),
+            Self::SetStrokeStyle(color) => write!(f, "stroke_style {color:?}"),
+            Self::FillRect(x, y, width, height) => {
+                write!(f, "fill_rect {x:.2} {y:.2} {width:.2} {height:.2}")
+            }
+            Self::ClearRect(x, y, width, height) => {
+                write!(f, "clear_rect {x:.2} {y:.2} {width:.2} {height:.2}")
+            }
+            Self::BeginPath => write!(f, "begin_path"),
+            Self::MoveTo(x, y) => write!(f, "move_to {x:.2} {y:.2}"),
+            Self::LineTo(x, y) => write!(f, "line_to {x:.2} {y:.2}"),
+            Self::Arc(x, y, radius, start_angle, end_angle) => write!(
+                f,
+                "arc {x:.2} {y:.2} {radius:.2} {start_angle:.2} {end_angle:.2}"
+            ),
+            Self::Stroke => write!(f, "stroke"),
+            Self::Fill => write!(f, "fill"),
+            Self::FillText(text, x, y) => write!(f, "fill_text {text:?} {x:.2} {y:.2}"),
+        }
+    }
+}
+
+/// Records the draw commands instead of drawing, to test the rendering without a browser.
+///
+/// Displayed with one command per line, e.g. for snapshots.
+#[derive(Debug, Clone, Default)]
+pub struct Recording {
+    pub commands: Vec<DrawCommand>,
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for command in &self.commands {
+            writeln!(f, "{command}")?;
+        }
+        Ok(())
+    }
+}
+
+impl DrawTarget for Recording {
+    fn save(&mut self) {
+        self.commands.push(DrawCommand::Save);
+    }
+
+    fn restore(&mut self) {
+        self.commands.push(DrawCommand::Restore);
+    }
+
+    fn scale(&mut self, x: f64, y: f64) -> Result<()> {
+        self.commands.push(DrawCommand::Scale(x, y));
+        Ok(())
+    }
+
+    fn set_font(&mut self, font: &str) {
+        self.commands.push(DrawCommand::SetFont(font.to_string()));
+    }
+
+    fn set_fill_style(&mut self, color: &str) {
+        self.commands
+            .push(DrawCommand::SetFillStyle(color.to_string()));
+    }
+
+    fn set_stroke_style(&mut self, color: &str) {
+        self.commands
+            .push(DrawCommand::SetStrokeStyle(color.to_string()));
+    }
+
+    fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.commands
+            .push(DrawCommand::FillRect(x, y, width, height));
+    }
+
+    fn clear_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.commands
+            .push(DrawCommand::ClearRect(x, y, width, height));
+    }
+
+    fn begin_path(&mut self) {
+        self.commands.push(DrawCommand::BeginPath);
+    }
+
+    fn move_to(&mut self, x: f64, y: f64) {
+        self.commands.push(DrawCommand::MoveTo(x, y));
+    }
+
+    fn line_to(&mut self, x: f64, y: f64) {
+        self.commands.push(DrawCommand::LineTo(x, y));
+    }
+
+    fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) -> Result<()> {
+        self.commands
+            .push(DrawCommand::Arc(x, y, radius, start_angle, end_angle));
+        Ok(())
+    }
+
+    fn stroke(&mut self) {
+        self.commands.push(DrawCommand::Stroke);
+    }
+
+    fn fill(&mut self) {
+        self.commands.push(DrawCommand::Fill);
+    }
+
+    fn fill_text(&mut self, text: &str, x: f64, y: f64) -> Result<()> {
+        self.commands
+            .push(DrawCommand::FillText(text.to_string(), x, y));
+        Ok(())
+    }
+}
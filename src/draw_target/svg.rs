@@ -0,0 +1,214 @@
+use std::f64::consts::{PI, TAU};
+use std::fmt::Write;
+
+use anyhow::Result;
+
+use super::DrawTarget;
+
+/// The state saved and restored along with the canvas' one.
+#[derive(Debug, Clone)]
+struct SvgState {
+    font: String,
+    fill_style: String,
+    stroke_style: String,
+    /// The `g` elements opened by `scale` since the last save
+    groups: usize,
+}
+
+impl Default for SvgState {
+    /// The defaults of the canvas 2D API.
+    fn default() -> Self {
+        Self {
+            font: "10px sans-serif".to_string(),
+            fill_style: "#000000".to_string(),
+            stroke_style: "#000000".to_string(),
+            groups: 0,
+        }
+    }
+}
+
+/// Draws in an SVG document, with one element per stroke, fill, rectangle or text.
+///
+/// Coordinates are rounded to the hundredth of a pixel.
+#[derive(Debug, Clone)]
+pub struct SvgDocument {
+    width: f64,
+    height: f64,
+    elements: String,
+    /// The `d` attribute of the current path
+    path: String,
+    state: SvgState,
+    saved: Vec<SvgState>,
+}
+
+impl SvgDocument {
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            elements: String::new(),
+            path: String::new(),
+            state: SvgState::default(),
+            saved: Vec::new(),
+        }
+    }
+
+    /// The document, the groups left open being closed.
+    pub fn finish(mut self) -> String {
+        while !self.saved.is_empty() {
+            self.restore();
+        }
+        self.close_groups();
+
+        let Self { width, height, .. } = self;
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.2}\" height=\"{height:.2}\" \
+             viewBox=\"0 0 {width:.2} {height:.2}\">\n{}</svg>\n",
+            self.elements
+        )
+    }
+
+    fn close_groups(&mut self) {
+        for _ in 0..self.state.groups {
+            self.elements.push_str("</g>\n");
+        }
+        self.state.groups = 0;
+    }
+
+    /// Like the canvas, starts a new subpath if there is no current point.
+    fn path_to(&mut self, x: f64, y: f64) {
+        let command = if self.path.is_empty() { 'M' } else { 'L' };
+        let _ = write!(self.path, "{command}{x:.2} {y:.2} ");
+    }
+}
+
+impl DrawTarget for SvgDocument {
+    fn save(&mut self) {
+        self.saved.push(self.state.clone());
+        self.state.groups = 0;
+    }
+
+    fn restore(&mut self) {
+        self.close_groups();
+        if let Some(state) = self.saved.pop() {
+            self.state = state;
+        }
+    }
+
+    fn scale(&mut self, x: f64, y: f64) -> Result<()> {
+        writeln!(self.elements, "<g transform=\"scale({x} {y})\">")?;
+        self.state.groups += 1;
+        Ok(())
+    }
+
+    fn set_font(&mut self, font: &str) {
+        self.state.font = font.to_string();
+    }
+
+    fn set_fill_style(&mut self, color: &str) {
+        self.state.fill_style = color.to_string();
+    }
+
+    fn set_stroke_style(&mut self, color: &str) {
+        self.state.stroke_style = color.to_string();
+    }
+
+    fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        let _ = writeln!(
+            self.elements,
+            "<rect x=\"{x:.2}\" y=\"{y:.2}\" width=\"{width:.2}\" height=\"{height:.2}\" \
+             fill=\"{}\"/>",
+            escape(&self.state.fill_style)
+        );
+    }
+
+    /// Does nothing since the elements below can't be erased, the documents only being drawn on
+    /// from a blank state.
+    fn clear_rect(&mut self, _x: f64, _y: f64, _width: f64, _height: f64) {}
+
+    fn begin_path(&mut self) {
+        self.path.clear();
+    }
+
+    fn move_to(&mut self, x: f64, y: f64) {
+        let _ = write!(self.path, "M{x:.2} {y:.2} ");
+    }
+
+    fn line_to(&mut self, x: f64, y: f64) {
+        self.path_to(x, y);
+    }
+
+    /// Only clockwise arcs are drawn, like with the canvas by default.
+    fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) -> Result<()> {
+        let point = |angle: f64| (x + radius * angle.cos(), y + radius * angle.sin());
+
+        let (start_x, start_y) = point(start_angle);
+        self.path_to(start_x, start_y);
+
+        let sweep = end_angle - start_angle;
+        // An SVG arc can't end where it starts, so full circles are drawn in two halves
+        let angles = if sweep >= TAU {
+            vec![start_angle + PI, start_angle + TAU]
+        } else {
+            vec![start_angle + sweep.rem_euclid(TAU)]
+        };
+
+        let mut previous_angle = start_angle;
+        for angle in angles {
+            let large_arc = u8::from(angle - previous_angle > PI);
+            let (x, y) = point(angle);
+            write!(
+                self.path,
+                "A{radius:.2} {radius:.2} 0 {large_arc} 1 {x:.2} {y:.2} "
+            )?;
+            previous_angle = angle;
+        }
+
+        Ok(())
+    }
+
+    fn stroke(&mut self) {
+        let _ = writeln!(
+            self.elements,
+            "<path d=\"{}\" fill=\"none\" stroke=\"{}\"/>",
+            self.path.trim_end(),
+            escape(&self.state.stroke_style)
+        );
+    }
+
+    fn fill(&mut self) {
+        let _ = writeln!(
+            self.elements,
+            "<path d=\"{}\" fill=\"{}\"/>",
+            self.path.trim_end(),
+            escape(&self.state.fill_style)
+        );
+    }
+
+    fn fill_text(&mut self, text: &str, x: f64, y: f64) -> Result<()> {
+        writeln!(
+            self.elements,
+            "<text x=\"{x:.2}\" y=\"{y:.2}\" fill=\"{}\" style=\"font: {}\">{}</text>",
+            escape(&self.state.fill_style),
+            escape(&self.state.font),
+            escape(text)
+        )?;
+        Ok(())
+    }
+}
+
+/// Escapes the characters which can't appear in text or attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
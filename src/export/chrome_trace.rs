@@ -0,0 +1,185 @@
+use chrono::DateTime;
+use serde::Serialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::log::types::LogEntry;
+use crate::measures::types::Measure;
+use crate::spans::types::ThreadSpans;
+use crate::types::ProcessInfo;
+
+const PID: u32 = 1;
+
+/// Thread id used for the events which don't belong to a thread, such as the log entries.
+const PROCESS_TID: u32 = 0;
+
+/// Events of the [Chrome Trace Event format](https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU).
+#[derive(Debug, Clone, Serialize)]
+struct TraceEvent {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cat: Option<String>,
+    ph: &'static str,
+    /// Timestamp in microseconds
+    ts: f64,
+    /// Duration in microseconds, only used by complete events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dur: Option<f64>,
+    pid: u32,
+    tid: u32,
+    /// Scope, only used by instant events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    s: Option<&'static str>,
+    #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+    args: serde_json::Value,
+}
+
+/// Sirius specific metadata, allowing the trace to be imported back with absolute times.
+#[derive(Debug, Clone, Serialize)]
+struct TraceMetadata {
+    process_id: String,
+    exe: String,
+    /// Time (in nanoseconds since the Unix epoch) all the timestamps are relative to
+    origin: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceDocument {
+    trace_events: Vec<TraceEvent>,
+    display_time_unit: &'static str,
+    other_data: TraceMetadata,
+}
+
+/// The data to export, all the times are expressed in nanoseconds.
+pub struct TraceContent<'a> {
+    pub process: &'a ProcessInfo,
+    pub threads: &'a [ThreadSpans],
+    pub log_entries: &'a [LogEntry],
+    pub measures: &'a [Measure],
+    pub begin: i64,
+    pub end: i64,
+}
+
+/// Serializes the content of a time range as a Chrome Trace Event JSON document.
+///
+/// Spans become complete events (clipped to the range), log entries become process wide instant
+/// events, and measures become counter tracks. Timestamps are relative to `begin` so that
+/// microseconds keep their precision once converted to floating point numbers.
+pub fn to_chrome_trace(content: &TraceContent) -> serde_json::Result<String> {
+    let mut trace_events = Vec::new();
+
+    trace_events.push(metadata_event(
+        "process_name",
+        PROCESS_TID,
+        &content.process.exe,
+    ));
+
+    for (index, thread) in content.threads.iter().enumerate() {
+        let tid = thread_tid(index);
+        trace_events.push(metadata_event("thread_name", tid, &thread.stream_id));
+
+        for span in &thread.spans {
+            if span.end < content.begin || span.begin > content.end {
+                continue;
+            }
+
+            let begin = span.begin.max(content.begin);
+            let end = span.end.min(content.end);
+
+            trace_events.push(TraceEvent {
+                name: span.name.clone(),
+                cat: Some("span".to_string()),
+                ph: "X",
+                ts: to_relative_us(begin, content.begin),
+                dur: Some(to_relative_us(end, begin)),
+                pid: PID,
+                tid,
+                s: None,
+                args: serde_json::Value::Null,
+            });
+        }
+    }
+
+    for log_entry in content.log_entries {
+        let Some(time) = parse_time_ns(&log_entry.time) else {
+            continue;
+        };
+
+        trace_events.push(TraceEvent {
+            name: log_entry.msg.clone(),
+            cat: Some(log_entry.target.clone()),
+            ph: "i",
+            ts: to_relative_us(time, content.begin),
+            dur: None,
+            pid: PID,
+            tid: PROCESS_TID,
+            s: Some("p"),
+            args: serde_json::Value::Null,
+        });
+    }
+
+    for measure in content.measures {
+        let Some(time) = parse_time_ns(&measure.time) else {
+            continue;
+        };
+
+        trace_events.push(TraceEvent {
+            name: format!("{} ({})", measure.target, measure.unit),
+            cat: None,
+            ph: "C",
+            ts: to_relative_us(time, content.begin),
+            dur: None,
+            pid: PID,
+            tid: PROCESS_TID,
+            s: None,
+            args: json!({ "value": measure.value }),
+        });
+    }
+
+    serde_json::to_string(&TraceDocument {
+        trace_events,
+        display_time_unit: "ns",
+        other_data: TraceMetadata {
+            process_id: content.process.process_id.clone(),
+            exe: content.process.exe.clone(),
+            origin: content.begin,
+        },
+    })
+}
+
+fn metadata_event(name: &str, tid: u32, value: &str) -> TraceEvent {
+    TraceEvent {
+        name: name.to_string(),
+        cat: None,
+        ph: "M",
+        ts: 0.0,
+        dur: None,
+        pid: PID,
+        tid,
+        s: None,
+        args: json!({ "name": value }),
+    }
+}
+
+/// Thread ids must be integers, threads are numbered from 1 as 0 is reserved for the process.
+fn thread_tid(index: usize) -> u32 {
+    u32::try_from(index + 1).unwrap_or(u32::MAX)
+}
+
+#[expect(clippy::cast_precision_loss)]
+fn to_relative_us(time: i64, origin: i64) -> f64 {
+    (time - origin) as f64 / 1_000.0
+}
+
+fn parse_time_ns(time: &str) -> Option<i64> {
+    let datetime = match DateTime::parse_from_rfc3339(time) {
+        Ok(datetime) => datetime,
+        Err(err) => {
+            error!(time, "datetime parse error: {err}");
+            return None;
+        }
+    };
+
+    datetime.timestamp_nanos_opt()
+}
@@ -0,0 +1,216 @@
+use anyhow::{anyhow, Result};
+use chrono::DateTime;
+use leptos::{
+    component, create_action, use_context, view, IntoView, Memo, Signal, SignalGet, SignalWith,
+};
+use tracing::error;
+use wasm_bindgen::JsValue;
+
+use crate::import::imported_data::{use_imported_process, ImportedProcess};
+use crate::local_query::{query_table, LocalTables};
+use crate::log::types::LogEntry;
+use crate::measures::measures_table_name;
+use crate::measures::types::Measure;
+use crate::query_builder::{col, Order, Query, Source};
+use crate::request::{QueryRequest, QUERY_URL};
+use crate::spans::types::ThreadSpans;
+use crate::types::ProcessInfo;
+
+use super::chrome_trace::{to_chrome_trace, TraceContent};
+use super::download::download_file;
+
+/// Exports the spans, log entries and measures of a process over `range` (in nanoseconds) as a
+/// Chrome/Perfetto trace file.
+#[component]
+pub fn ExportTraceButton(
+    process: Memo<Option<ProcessInfo>>,
+    threads: Signal<Vec<ThreadSpans>>,
+    range: Signal<(i64, i64)>,
+) -> impl IntoView {
+    let imported_process = use_imported_process(move || {
+        process.with(|process| {
+            process
+                .as_ref()
+                .map(|process| process.process_id.clone())
+                .unwrap_or_default()
+        })
+    });
+
+    let local_tables = use_context::<LocalTables>();
+
+    let export = create_action(move |(): &()| {
+        let process = process.get();
+        let (begin, end) = range.get();
+        let threads = threads.with(|threads| clip_threads(threads, begin, end));
+        let imported_process = imported_process.get();
+
+        async move {
+            let Some(process) = process else {
+                return;
+            };
+
+            let result = match imported_process {
+                Some(imported_process) => {
+                    export_imported_trace(&imported_process, &threads, begin, end)
+                }
+                None => export_trace(local_tables, &process, &threads, begin, end).await,
+            };
+
+            if let Err(err) = result {
+                error!("trace export error: {err}");
+            }
+        }
+    });
+
+    view! {
+        <button
+            class="outline"
+            disabled=move || process.get().is_none() || export.pending().get()
+            on:click=move |_| export.dispatch(())
+        >
+            {move || if export.pending().get() { "Exporting…" } else { "Export trace" }}
+        </button>
+    }
+}
+
+async fn export_trace(
+    local_tables: Option<LocalTables>,
+    process: &ProcessInfo,
+    threads: &[ThreadSpans],
+    begin: i64,
+    end: i64,
+) -> Result<()> {
+    let (url, request) = log_entries_request(process, begin, end);
+    let log_entries = query_table::<Vec<LogEntry>>(
+        local_tables,
+        &format!("log_entries/{}", process.process_id),
+        "time",
+        &url,
+        &request,
+    )
+    .await?;
+
+    let (url, request) = measures_request(process, begin, end);
+    let measures = query_table::<Option<Vec<Measure>>>(
+        local_tables,
+        &measures_table_name(&process.process_id),
+        "time",
+        &url,
+        &request,
+    )
+    .await?
+    .unwrap_or_default();
+
+    download_trace(&TraceContent {
+        process,
+        threads,
+        log_entries: &log_entries,
+        measures: &measures,
+        begin,
+        end,
+    })
+}
+
+fn export_imported_trace(
+    imported_process: &ImportedProcess,
+    threads: &[ThreadSpans],
+    begin: i64,
+    end: i64,
+) -> Result<()> {
+    let in_range = |time: &str| {
+        DateTime::parse_from_rfc3339(time)
+            .ok()
+            .and_then(|time| time.timestamp_nanos_opt())
+            .is_some_and(|time| time >= begin && time <= end)
+    };
+
+    let log_entries = imported_process
+        .log_entries
+        .iter()
+        .filter(|log_entry| in_range(&log_entry.time))
+        .cloned()
+        .collect::<Vec<_>>();
+    let measures = imported_process
+        .measures
+        .iter()
+        .filter(|measure| in_range(&measure.time))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    download_trace(&TraceContent {
+        process: &imported_process.process,
+        threads,
+        log_entries: &log_entries,
+        measures: &measures,
+        begin,
+        end,
+    })
+}
+
+fn download_trace(content: &TraceContent) -> Result<()> {
+    let trace =
+        to_chrome_trace(content).map_err(|err| anyhow!("trace serialization error: {err}"))?;
+
+    download_file(
+        &format!(
+            "{}-{}-{}.json",
+            content.process.process_id, content.begin, content.end
+        ),
+        "application/json",
+        &JsValue::from_str(&trace),
+    )
+}
+
+/// Only keeps the spans overlapping the range, to avoid cloning the whole process.
+fn clip_threads(threads: &[ThreadSpans], begin: i64, end: i64) -> Vec<ThreadSpans> {
+    threads
+        .iter()
+        .map(|thread| ThreadSpans {
+            spans: thread
+                .spans
+                .iter()
+                .filter(|span| span.end >= begin && span.begin <= end)
+                .cloned()
+                .collect(),
+            blocks: Vec::new(),
+            ..thread.clone()
+        })
+        .collect()
+}
+
+fn log_entries_request(process: &ProcessInfo, begin: i64, end: i64) -> (String, QueryRequest) {
+    let begin = DateTime::from_timestamp_nanos(begin);
+    let end = DateTime::from_timestamp_nanos(end);
+    let request = QueryRequest {
+        sql: Query::from(Source::LogEntries)
+            // The process is selected too, for the query to run again over the local table
+            .columns(&["process_id", "time", "target", "msg"])
+            .filter(col("process_id").eq(process.process_id.as_str()))
+            .filter(col("time").ge(begin))
+            .filter(col("time").le(end))
+            .order_by("time", Order::Asc)
+            .to_string(),
+        begin: Some(begin.to_rfc3339()),
+        end: Some(end.to_rfc3339()),
+    };
+
+    (QUERY_URL.to_string(), request)
+}
+
+fn measures_request(process: &ProcessInfo, begin: i64, end: i64) -> (String, QueryRequest) {
+    let begin = DateTime::from_timestamp_nanos(begin);
+    let end = DateTime::from_timestamp_nanos(end);
+    let request = QueryRequest {
+        sql: Query::from(Source::Measures)
+            .columns(&["process_id", "target", "time", "value", "unit"])
+            .filter(col("process_id").eq(process.process_id.as_str()))
+            .filter(col("time").ge(begin))
+            .filter(col("time").le(end))
+            .order_by("time", Order::Asc)
+            .to_string(),
+        begin: Some(begin.to_rfc3339()),
+        end: Some(end.to_rfc3339()),
+    };
+
+    (QUERY_URL.to_string(), request)
+}
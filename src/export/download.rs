@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use tracing::error;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+/// Makes the browser download `content` as a file named `file_name`.
+pub fn download_file(file_name: &str, mime_type: &str, content: &JsValue) -> Result<()> {
+    let parts = js_sys::Array::of1(content);
+    let options = BlobPropertyBag::new();
+    options.set_type(mime_type);
+
+    let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+        .map_err(|err| anyhow!("blob creation error: {err:?}"))?;
+    let url = Url::create_object_url_with_blob(&blob)
+        .map_err(|err| anyhow!("object url creation error: {err:?}"))?;
+
+    download_url(file_name, &url)?;
+
+    // Revoking the url right after the click can cancel the download in some browsers
+    leptos::set_timeout(
+        move || {
+            if let Err(err) = Url::revoke_object_url(&url) {
+                error!("object url revocation error: {err:?}");
+            }
+        },
+        Duration::ZERO,
+    );
+
+    Ok(())
+}
+
+/// Makes the browser download the file at `url`, e.g. an object or data url, as `file_name`.
+pub fn download_url(file_name: &str, url: &str) -> Result<()> {
+    let document = leptos::document();
+    let anchor = document
+        .create_element("a")
+        .map_err(|err| anyhow!("anchor creation error: {err:?}"))?
+        .dyn_into::<HtmlAnchorElement>()
+        .map_err(|err| anyhow!("anchor dyn conversion error: {err:?}"))?;
+    anchor.set_href(url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    Ok(())
+}
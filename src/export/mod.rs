@@ -0,0 +1,5 @@
+pub mod chrome_trace;
+mod components;
+pub mod download;
+
+pub use components::ExportTraceButton;
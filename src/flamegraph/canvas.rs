@@ -0,0 +1,154 @@
+use anyhow::Result;
+use leptos::{html::Canvas, HtmlElement};
+use tracing::{debug, error};
+use web_sys::CanvasRenderingContext2d;
+
+use crate::spans::call_tree::{CallTree, ROOT_INDEX};
+use crate::spans::color::get_span_color;
+use crate::use_canvas::context_2d;
+
+pub const ROW_HEIGHT: f64 = 20.0;
+
+/// Frames narrower than this (in pixels) are not drawn, and neither are their children.
+const MIN_FRAME_WIDTH: f64 = 0.5;
+
+/// Frames narrower than this (in pixels) don't display their name.
+const MIN_LABEL_WIDTH: f64 = 32.0;
+
+/// Rough average width of a character using the 12px font.
+const CHAR_WIDTH: f64 = 7.0;
+
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    node: usize,
+    x: f64,
+    y: f64,
+    width: f64,
+}
+
+pub struct FlameGraphCanvas {
+    ctx: CanvasRenderingContext2d,
+    frames: Vec<Frame>,
+}
+
+impl FlameGraphCanvas {
+    pub fn try_new(node: &HtmlElement<Canvas>) -> Result<Self> {
+        Ok(Self {
+            ctx: context_2d(node)?,
+            frames: Vec::new(),
+        })
+    }
+
+    pub fn render(
+        &mut self,
+        tree: &CallTree,
+        width: f64,
+        height: f64,
+        hovered: Option<usize>,
+        dpr: f64,
+    ) {
+        debug!("rendering flame graph");
+
+        if width < f64::EPSILON || height < f64::EPSILON {
+            return;
+        }
+
+        self.ctx.save();
+        if let Err(err) = self.ctx.scale(dpr, dpr) {
+            error!(dpr, "context scaling failed: {err:?}");
+        }
+        self.ctx.set_font("12px Arial");
+        self.ctx.set_fill_style(&"#13171f".into());
+        self.ctx.fill_rect(0.0, 0.0, width, height);
+
+        self.frames.clear();
+        self.layout(tree, ROOT_INDEX, 0.0, width, 0.0);
+
+        for frame in &self.frames {
+            let node = &tree.nodes[frame.node];
+
+            let color = if hovered == Some(frame.node) {
+                "#ffffff".to_string()
+            } else {
+                get_span_color(&node.name)
+            };
+            self.ctx.set_fill_style(&color.into());
+            self.ctx.fill_rect(
+                frame.x,
+                frame.y,
+                (frame.width - 1.0).max(0.5),
+                ROW_HEIGHT - 1.0,
+            );
+
+            if frame.width < MIN_LABEL_WIDTH {
+                continue;
+            }
+
+            self.ctx.set_fill_style(&"#13171f".into());
+            if let Err(err) = self.ctx.fill_text(
+                &truncate_label(&node.name, frame.width),
+                frame.x + 4.0,
+                frame.y + ROW_HEIGHT - 6.0,
+            ) {
+                error!("fill text error: {err:?}");
+            }
+        }
+
+        self.ctx.restore();
+    }
+
+    /// Returns the node under the provided position, if any.
+    pub fn node_at(&self, x: f64, y: f64) -> Option<usize> {
+        self.frames
+            .iter()
+            .find(|frame| {
+                x >= frame.x
+                    && x < frame.x + frame.width
+                    && y >= frame.y
+                    && y < frame.y + ROW_HEIGHT
+            })
+            .map(|frame| frame.node)
+    }
+
+    #[expect(clippy::cast_precision_loss)]
+    fn layout(&mut self, tree: &CallTree, index: usize, x: f64, width: f64, y: f64) {
+        if width < MIN_FRAME_WIDTH {
+            return;
+        }
+
+        self.frames.push(Frame {
+            node: index,
+            x,
+            y,
+            width,
+        });
+
+        let node = &tree.nodes[index];
+        if node.total <= 0 {
+            return;
+        }
+
+        let rev_factor = width / node.total as f64;
+        let mut child_x = x;
+        for child in &node.children {
+            let child_width = tree.nodes[*child].total as f64 * rev_factor;
+            self.layout(tree, *child, child_x, child_width, y + ROW_HEIGHT);
+            child_x += child_width;
+        }
+    }
+}
+
+#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn truncate_label(name: &str, width: f64) -> String {
+    let max_chars = ((width - 8.0) / CHAR_WIDTH).max(0.0) as usize;
+    if name.chars().count() <= max_chars {
+        return name.to_string();
+    }
+
+    let mut label = name
+        .chars()
+        .take(max_chars.saturating_sub(1))
+        .collect::<String>();
+    label.push('…');
+    label
+}
@@ -0,0 +1,386 @@
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+use chrono::DateTime;
+use ev::MouseEvent;
+use leptos::html::Canvas as CanvasNode;
+use leptos::{
+    component, create_effect, create_memo, create_rw_signal, create_signal, ev, view, For,
+    IntoView, NodeRef, Params, Signal, SignalGet, SignalGetUntracked, SignalSet, SignalUpdate,
+    SignalWith,
+};
+use leptos_router::{use_params, use_query, Params, A};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::components::Spinner;
+use crate::datetime::{display_datetime, display_duration};
+use crate::import::imported_data::use_imported_process;
+use crate::request::create_request_opt;
+use crate::spans::call_tree::{bottom_up, top_down, CallTree};
+use crate::spans::request::{spans_request, thread_streams_request};
+use crate::spans::types::{group_spans_by_thread, Span};
+use crate::types::Stream;
+use crate::use_canvas::{use_canvas, UseCanvasReturn};
+
+use super::canvas::{FlameGraphCanvas, ROW_HEIGHT};
+
+#[derive(Params, PartialEq)]
+struct FlameGraphParams {
+    id: Option<Uuid>,
+}
+
+#[derive(Params, PartialEq)]
+struct FlameGraphQuery {
+    begin: Option<i64>,
+    end: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CallTreeMode {
+    TopDown,
+    BottomUp,
+}
+
+#[component]
+pub fn FlameGraph() -> impl IntoView {
+    let params = use_params::<FlameGraphParams>();
+    let query = use_query::<FlameGraphQuery>();
+
+    let id = move || {
+        params.with(|params| {
+            params
+                .as_ref()
+                .ok()
+                .and_then(|params| params.id)
+                .unwrap_or_default()
+        })
+    };
+
+    let range = create_memo(move |_| {
+        query.with(|query| {
+            let query = query.as_ref().ok()?;
+            Some((query.begin?, query.end?))
+        })
+    });
+
+    let begin = move || DateTime::from_timestamp_nanos(range.get().unwrap_or_default().0);
+    let end = move || DateTime::from_timestamp_nanos(range.get().unwrap_or_default().1);
+
+    let imported_process = use_imported_process(move || id().to_string());
+
+    let streams = create_request_opt::<_, Vec<Stream>>(move || {
+        if imported_process.with(Option::is_some) {
+            return None;
+        }
+
+        Some(thread_streams_request(&id().to_string(), begin(), end()))
+    });
+
+    let spans =
+        create_request_opt::<_, Vec<Span>>(move || spans_request(&streams.get()?, begin(), end()));
+
+    let threads = create_memo(move |_| match imported_process.get() {
+        Some(imported_process) => group_spans_by_thread(imported_process.spans.clone()),
+        None => group_spans_by_thread(spans.get().unwrap_or_default()),
+    });
+
+    let top_down_tree = create_memo(move |_| {
+        let (begin, end) = range.get()?;
+        Some(threads.with(|threads| top_down(threads, begin, end)))
+    });
+
+    let bottom_up_tree = create_memo(move |_| {
+        let (begin, end) = range.get()?;
+        Some(threads.with(|threads| bottom_up(threads, begin, end)))
+    });
+
+    let (mode, set_mode) = create_signal(CallTreeMode::TopDown);
+
+    let is_loading = move || streams.loading().get() || spans.loading().get();
+
+    view! {
+        <div class="w-full p-4 flex flex-col gap-4">
+            <div class="flex gap-4 items-baseline">
+                <A href=move || format!("/timeline/{}", id())>"Timeline"</A>
+                <span>{move || display_datetime(begin())}</span>
+                <span>"→"</span>
+                <span>{move || display_datetime(end())}</span>
+                <span>"(" {move || display_duration((end() - begin()).num_nanoseconds().unwrap_or_default())} ")"</span>
+            </div>
+            {move || {
+                if range.get().is_none() {
+                    view! { <p>"Invalid time range"</p> }.into_view()
+                } else if is_loading() {
+                    view! { <Spinner /> }.into_view()
+                } else {
+                    view! {
+                        <FlameGraphView tree=Signal::derive(move || top_down_tree.get().unwrap_or_else(empty_tree)) />
+                        <div class="flex gap-2">
+                            <button
+                                class:outline=move || mode.get() != CallTreeMode::TopDown
+                                on:click=move |_| set_mode.set(CallTreeMode::TopDown)
+                            >
+                                "Top-down"
+                            </button>
+                            <button
+                                class:outline=move || mode.get() != CallTreeMode::BottomUp
+                                on:click=move |_| set_mode.set(CallTreeMode::BottomUp)
+                            >
+                                "Bottom-up"
+                            </button>
+                        </div>
+                        <CallTreeTable tree=Signal::derive(move || {
+                            match mode.get() {
+                                CallTreeMode::TopDown => top_down_tree.get(),
+                                CallTreeMode::BottomUp => bottom_up_tree.get(),
+                            }
+                            .unwrap_or_else(empty_tree)
+                        }) />
+                    }
+                        .into_view()
+                }
+            }}
+        </div>
+    }
+}
+
+fn empty_tree() -> CallTree {
+    top_down(&[], 0, 0)
+}
+
+#[component]
+fn FlameGraphView(tree: Signal<CallTree>) -> impl IntoView {
+    let UseCanvasReturn {
+        node_ref: canvas_node_ref,
+        dpr,
+        width: canvas_width,
+        height: canvas_height,
+        actual_width: canvas_actual_width,
+        actual_height: canvas_actual_height,
+    } = use_canvas();
+
+    let (canvas, set_canvas) = create_signal::<Option<Rc<RefCell<FlameGraphCanvas>>>>(None);
+    let (hovered, set_hovered) = create_signal::<Option<usize>>(None);
+
+    let max_depth = create_memo(move |_| {
+        tree.with(|tree| {
+            tree.nodes
+                .iter()
+                .map(|node| node.depth)
+                .max()
+                .unwrap_or_default()
+        })
+    });
+
+    create_effect(move |_| {
+        let Some(node) = canvas_node_ref.get() else {
+            return;
+        };
+
+        match FlameGraphCanvas::try_new(&node) {
+            Ok(flame_graph_canvas) => {
+                set_canvas.set(Some(Rc::new(RefCell::new(flame_graph_canvas))));
+            }
+            Err(err) => error!("flame graph canvas failed to initialize: {err}"),
+        }
+    });
+
+    create_effect(move |_| {
+        let Some(canvas) = canvas.get() else {
+            return;
+        };
+
+        tree.with(|tree| {
+            canvas.borrow_mut().render(
+                tree,
+                canvas_width.get(),
+                canvas_height.get(),
+                hovered.get(),
+                dpr.get(),
+            );
+        });
+    });
+
+    let handle_mousemove = move |evt: MouseEvent| {
+        let (Some(canvas_node), Some(canvas)) = (canvas_node_ref.get(), canvas.get()) else {
+            return;
+        };
+
+        let dom_rect = canvas_node.get_bounding_client_rect();
+        let x = f64::from(evt.client_x()) - dom_rect.left();
+        let y = f64::from(evt.client_y()) - dom_rect.top();
+
+        let node = canvas.borrow().node_at(x, y);
+        if node != hovered.get() {
+            set_hovered.set(node);
+        }
+    };
+
+    let hovered_label = move || {
+        let index = hovered.get()?;
+        tree.with(|tree| {
+            let node = tree.nodes.get(index)?;
+            let root_total = tree.root().total.max(1);
+            #[expect(clippy::cast_precision_loss)]
+            let percent = node.total as f64 / root_total as f64 * 100.0;
+            Some(format!(
+                "{} — {} ({percent:.2}%), {} calls",
+                node.name,
+                display_duration(node.total),
+                node.calls
+            ))
+        })
+    };
+
+    view! {
+        <div class="flex flex-col gap-1">
+            <div class="h-6 truncate">{move || hovered_label().unwrap_or_default()}</div>
+            <FlameGraphCanvasNode
+                canvas_node_ref
+                actual_width=canvas_actual_width
+                actual_height=canvas_actual_height
+                height=Signal::derive(move || f64::from(max_depth.get() + 1) * ROW_HEIGHT)
+                on_mousemove=handle_mousemove
+                on_mouseleave=move |_| set_hovered.set(None)
+            />
+        </div>
+    }
+}
+
+#[component]
+fn FlameGraphCanvasNode(
+    canvas_node_ref: NodeRef<CanvasNode>,
+    actual_width: Signal<f64>,
+    actual_height: Signal<f64>,
+    height: Signal<f64>,
+    on_mousemove: impl Fn(MouseEvent) + 'static,
+    on_mouseleave: impl Fn(MouseEvent) + 'static,
+) -> impl IntoView {
+    view! {
+        <canvas
+            class="border border-black w-full"
+            node_ref=canvas_node_ref
+            width=actual_width
+            height=actual_height
+            style:width="100%"
+            style:height=move || format!("{}px", height.get())
+            on:mousemove=on_mousemove
+            on:mouseleave=on_mouseleave
+        />
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CallTreeRow {
+    index: usize,
+    name: String,
+    depth: u32,
+    total: i64,
+    self_time: i64,
+    calls: u64,
+    has_children: bool,
+}
+
+#[component]
+fn CallTreeTable(tree: Signal<CallTree>) -> impl IntoView {
+    let expanded = create_rw_signal(HashSet::<usize>::new());
+
+    // The node indices are only meaningful for a given tree
+    create_effect(move |_| {
+        tree.track();
+        expanded.set(HashSet::new());
+    });
+
+    let rows = create_memo(move |_| {
+        tree.with(|tree| {
+            expanded.with(|expanded| {
+                let mut rows = Vec::new();
+                let mut stack = tree
+                    .root()
+                    .children
+                    .iter()
+                    .rev()
+                    .copied()
+                    .collect::<Vec<_>>();
+
+                while let Some(index) = stack.pop() {
+                    let node = &tree.nodes[index];
+                    rows.push(CallTreeRow {
+                        index,
+                        name: node.name.clone(),
+                        depth: node.depth,
+                        total: node.total,
+                        self_time: node.self_time,
+                        calls: node.calls,
+                        has_children: !node.children.is_empty(),
+                    });
+
+                    if expanded.contains(&index) {
+                        stack.extend(node.children.iter().rev());
+                    }
+                }
+
+                rows
+            })
+        })
+    });
+
+    let root_total = create_memo(move |_| tree.with(|tree| tree.root().total.max(1)));
+
+    view! {
+        <div class="overflow-auto">
+            <table class="striped">
+                <thead>
+                    <tr>
+                        <th scope="col">"Name"</th>
+                        <th scope="col">"Total"</th>
+                        <th scope="col">"Self"</th>
+                        <th scope="col">"Calls"</th>
+                        <th scope="col">"%"</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    <For
+                        each=move || rows.get()
+                        key=|row| (row.index, row.total, row.has_children)
+                        let:row
+                    >
+                        {
+                            let index = row.index;
+                            let is_expanded = move || expanded.with(|expanded| expanded.contains(&index));
+                            #[expect(clippy::cast_precision_loss)]
+                            let percent = row.total as f64 / root_total.get_untracked() as f64 * 100.0;
+                            view! {
+                                <tr>
+                                    <td style:padding-left=format!("{}rem", f64::from(row.depth))>
+                                        <span
+                                            class="cursor-pointer select-none inline-block w-4"
+                                            on:click=move |_| {
+                                                expanded.update(|expanded| {
+                                                    if !expanded.remove(&index) {
+                                                        expanded.insert(index);
+                                                    }
+                                                });
+                                            }
+                                        >
+                                            {move || match (row.has_children, is_expanded()) {
+                                                (false, _) => "",
+                                                (true, true) => "▾",
+                                                (true, false) => "▸",
+                                            }}
+                                        </span>
+                                        {row.name}
+                                    </td>
+                                    <td>{display_duration(row.total)}</td>
+                                    <td>{display_duration(row.self_time)}</td>
+                                    <td>{row.calls}</td>
+                                    <td>{format!("{percent:.2}")}</td>
+                                </tr>
+                            }
+                        }
+                    </For>
+                </tbody>
+            </table>
+        </div>
+    }
+}
@@ -0,0 +1,5 @@
+mod canvas;
+mod components;
+
+pub use canvas::truncate_label;
+pub use components::FlameGraph;
@@ -0,0 +1,81 @@
+use std::{cell::Cell, rc::Rc};
+
+use tracing::error;
+use wasm_bindgen::{closure::Closure, prelude::wasm_bindgen, JsValue};
+
+/// Used when animation frames aren't available, e.g. in the workers of older browsers.
+const FALLBACK_FRAME_MS: i32 = 16;
+
+#[wasm_bindgen]
+extern "C" {
+    /// Available from both the window and the workers, in recent browsers.
+    #[wasm_bindgen(catch, js_name = requestAnimationFrame)]
+    fn request_animation_frame(callback: &JsValue) -> Result<i32, JsValue>;
+
+    #[wasm_bindgen(catch, js_name = cancelAnimationFrame)]
+    fn cancel_animation_frame(handle: i32) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &JsValue, timeout: i32) -> i32;
+
+    #[wasm_bindgen(js_name = clearTimeout)]
+    fn clear_timeout(handle: i32);
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Frame {
+    Animation(i32),
+    Timeout(i32),
+}
+
+/// Calls `on_frame` once on the next animation frame, however many times it was requested until
+/// then.
+pub struct FrameScheduler {
+    requested: Rc<Cell<Option<Frame>>>,
+    on_frame: Closure<dyn FnMut()>,
+}
+
+impl FrameScheduler {
+    pub fn new(mut on_frame: impl FnMut() + 'static) -> Self {
+        let requested = Rc::new(Cell::new(None));
+        let on_frame = Closure::new({
+            let requested = Rc::clone(&requested);
+            move || {
+                requested.set(None);
+                on_frame();
+            }
+        });
+
+        Self {
+            requested,
+            on_frame,
+        }
+    }
+
+    pub fn request(&self) {
+        if self.requested.get().is_some() {
+            return;
+        }
+
+        let frame = match request_animation_frame(self.on_frame.as_ref()) {
+            Ok(handle) => Frame::Animation(handle),
+            Err(_err) => Frame::Timeout(set_timeout(self.on_frame.as_ref(), FALLBACK_FRAME_MS)),
+        };
+        self.requested.set(Some(frame));
+    }
+}
+
+impl Drop for FrameScheduler {
+    /// The callback can't be called once dropped.
+    fn drop(&mut self) {
+        match self.requested.take() {
+            Some(Frame::Animation(handle)) => {
+                if let Err(err) = cancel_animation_frame(handle) {
+                    error!("animation frame cancellation error: {err:?}");
+                }
+            }
+            Some(Frame::Timeout(handle)) => clear_timeout(handle),
+            None => {}
+        }
+    }
+}
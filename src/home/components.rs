@@ -6,12 +6,15 @@ use leptos::{component, view, For, IntoView, SignalGet};
 
 use crate::components::Spinner;
 use crate::datetime::display_datetime;
+use crate::import::imported_data::use_imported_processes;
+use crate::query_builder::{Order, Query, Source};
 use crate::request::{create_request, QueryRequest, QUERY_URL};
 use crate::types::ProcessInfo;
 
 #[component]
 pub fn Home() -> impl IntoView {
     let processes = create_request::<_, Vec<ProcessInfo>>(processes_request);
+    let imported_processes = use_imported_processes();
 
     view! {
         <div class="overflow-auto">
@@ -25,6 +28,13 @@ pub fn Home() -> impl IntoView {
                     </tr>
                 </thead>
                 <tbody>
+                    <For
+                        each=move || imported_processes.get()
+                        key=|process| (process.process_id.clone(), process.start_time)
+                        let:process
+                    >
+                        <Process process=process></Process>
+                    </For>
                     {move || {
                         if processes.loading().get() {
                             view! { <Spinner /> }
@@ -62,6 +72,8 @@ pub fn Process(process: ProcessInfo) -> impl IntoView {
                 <a href=format!("/measures/{}", process.process_id)>"Measures"</a>
                 " / "
                 <a href=format!("/log/{}", process.process_id)>"Log"</a>
+                " / "
+                <a href=format!("/timeline/{}", process.process_id)>"Timeline"</a>
             </th>
             <td>{process.process_id}</td>
             <td>{exe}</td>
@@ -74,7 +86,10 @@ fn processes_request() -> (String, QueryRequest) {
     let end = Utc::now();
     let begin = end - Duration::days(1);
     let request = QueryRequest {
-        sql: "select * from processes order by start_time desc limit 100".to_string(),
+        sql: Query::from(Source::Processes)
+            .order_by("start_time", Order::Desc)
+            .limit(100)
+            .to_string(),
         begin: Some(begin.to_rfc3339()),
         end: Some(end.to_rfc3339()),
     };
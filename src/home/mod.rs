@@ -1,3 +1,3 @@
 mod components;
 
-pub use components::Home;
+pub use components::{Home, Process};
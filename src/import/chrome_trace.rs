@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::log::types::LogEntry;
+use crate::measures::types::Measure;
+use crate::spans::types::Span;
+
+use super::imported_data::ImportedProcess;
+
+#[derive(Debug, Clone, Deserialize)]
+struct TraceEvent {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    cat: Option<String>,
+    ph: String,
+    /// Timestamp in microseconds
+    #[serde(default)]
+    ts: f64,
+    /// Duration in microseconds, only used by complete events
+    #[serde(default)]
+    dur: Option<f64>,
+    #[serde(default)]
+    tid: serde_json::Value,
+    #[serde(default)]
+    args: serde_json::Value,
+}
+
+/// Metadata written by the trace export, allowing to restore the absolute times.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct TraceMetadata {
+    process_id: Option<String>,
+    exe: Option<String>,
+    origin: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum Trace {
+    Object {
+        #[serde(rename = "traceEvents")]
+        trace_events: Vec<TraceEvent>,
+        #[serde(rename = "otherData", default)]
+        other_data: TraceMetadata,
+    },
+    Array(Vec<TraceEvent>),
+}
+
+/// Imports a trace in the [Chrome Trace Event format](https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU).
+///
+/// Complete events become spans, instant events log entries and counter events measures. Traces
+/// exported by Sirius are restored with their process id and absolute times, the timestamps of
+/// other traces are considered relative to the Unix epoch.
+pub fn import_chrome_trace(content: &[u8], file_name: &str) -> Result<ImportedProcess> {
+    let trace: Trace = serde_json::from_slice(content)
+        .map_err(|err| anyhow!("trace deserialization error: {err}"))?;
+    let (trace_events, metadata) = match trace {
+        Trace::Object {
+            trace_events,
+            other_data,
+        } => (trace_events, other_data),
+        Trace::Array(trace_events) => (trace_events, TraceMetadata::default()),
+    };
+
+    let process_id = metadata
+        .process_id
+        .filter(|process_id| Uuid::parse_str(process_id).is_ok())
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let origin = metadata.origin.unwrap_or_default();
+    let to_datetime = |ts: f64| DateTime::from_timestamp_nanos(origin + to_ns(ts));
+
+    let mut exe = metadata.exe;
+    let mut thread_names = HashMap::new();
+    let mut log_entries = Vec::new();
+    let mut measures = Vec::new();
+    let mut complete_events = Vec::new();
+
+    for event in trace_events {
+        let tid = thread_id(&event.tid);
+        match event.ph.as_str() {
+            "M" => {
+                let Some(value) = event.args.get("name").and_then(|name| name.as_str()) else {
+                    continue;
+                };
+                match event.name.as_str() {
+                    "process_name" => {
+                        exe.get_or_insert_with(|| value.to_string());
+                    }
+                    "thread_name" => {
+                        thread_names.insert(tid, value.to_string());
+                    }
+                    _ => {}
+                }
+            }
+            "X" => complete_events.push((tid, event)),
+            "i" | "I" => log_entries.push(LogEntry {
+                time: to_datetime(event.ts).to_rfc3339(),
+                target: event.cat.unwrap_or_default(),
+                msg: event.name,
+            }),
+            "C" => {
+                let (target, unit) = parse_counter_name(&event.name);
+                let Some(values) = event.args.as_object() else {
+                    continue;
+                };
+                for (key, value) in values {
+                    let Some(value) = value.as_f64() else {
+                        continue;
+                    };
+                    // Counters exported by Sirius have a single `value` series
+                    let target = if key == "value" {
+                        target.to_string()
+                    } else {
+                        format!("{target}.{key}")
+                    };
+                    measures.push(Measure {
+                        target,
+                        time: to_datetime(event.ts).to_rfc3339(),
+                        value,
+                        unit: unit.to_string(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let spans = complete_events_to_spans(complete_events, &thread_names, to_datetime);
+
+    let mut imported_process =
+        ImportedProcess::synthesize(process_id, exe.unwrap_or_else(|| file_name.to_string()));
+    imported_process.log_entries = log_entries;
+    imported_process.measures = measures;
+    imported_process.spans = spans;
+    imported_process.update_start_time();
+
+    Ok(imported_process)
+}
+
+/// Rebuilds the call stacks of each thread, as complete events don't record their parent.
+fn complete_events_to_spans(
+    mut events: Vec<(String, TraceEvent)>,
+    thread_names: &HashMap<String, String>,
+    to_datetime: impl Fn(f64) -> DateTime<Utc>,
+) -> Vec<Span> {
+    // Parents start before, or at the same time but last longer than, their children
+    events.sort_by(|(a_tid, a), (b_tid, b)| {
+        a_tid.cmp(b_tid).then(a.ts.total_cmp(&b.ts)).then(
+            b.dur
+                .unwrap_or_default()
+                .total_cmp(&a.dur.unwrap_or_default()),
+        )
+    });
+
+    let mut spans = Vec::with_capacity(events.len());
+    let mut stack: Vec<(i64, f64)> = Vec::new();
+    let mut current_tid = None;
+
+    for (id, (tid, event)) in (1_i64..).zip(events) {
+        if current_tid.as_ref() != Some(&tid) {
+            stack.clear();
+            current_tid = Some(tid.clone());
+        }
+
+        let end = event.ts + event.dur.unwrap_or_default();
+        while stack
+            .last()
+            .is_some_and(|(_parent, parent_end)| *parent_end < end)
+        {
+            stack.pop();
+        }
+
+        let parent = stack.last().map_or(0, |(parent, _parent_end)| *parent);
+        let depth = u32::try_from(stack.len()).unwrap_or(u32::MAX);
+        stack.push((id, end));
+
+        spans.push(Span {
+            stream_id: thread_names.get(&tid).cloned().unwrap_or(tid),
+            id,
+            parent,
+            depth,
+            begin: to_datetime(event.ts),
+            end: to_datetime(end),
+            name: event.name,
+            target: event.cat.unwrap_or_default(),
+        });
+    }
+
+    spans
+}
+
+/// The thread id of an event as a string, thread ids being numbers or strings depending on the
+/// tracer. Events without a thread id belong to the process, like in the trace export.
+fn thread_id(tid: &serde_json::Value) -> String {
+    match tid {
+        serde_json::Value::String(tid) => tid.clone(),
+        serde_json::Value::Number(tid) => tid
+            .as_u64()
+            .map_or_else(|| tid.to_string(), |tid| tid.to_string()),
+        _ => "0".to_string(),
+    }
+}
+
+/// Splits the `target (unit)` counter names of the trace export.
+fn parse_counter_name(name: &str) -> (&str, &str) {
+    name.strip_suffix(')')
+        .and_then(|name| name.rsplit_once(" ("))
+        .unwrap_or((name, ""))
+}
+
+#[expect(clippy::cast_possible_truncation)]
+fn to_ns(us: f64) -> i64 {
+    (us * 1_000.0).round() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export::chrome_trace::{to_chrome_trace, TraceContent};
+    use crate::spans::types::{SpanData, ThreadSpans};
+    use crate::types::ProcessInfo;
+
+    use super::*;
+
+    const ORIGIN: i64 = 1_700_000_000_000_000_000;
+    const PROCESS_ID: &str = "8c5c0b8a-3a8f-4d38-9bd3-4a2a4c1f4a3e";
+
+    fn span(id: i64, parent: i64, depth: u32, (begin, end): (i64, i64), name: &str) -> SpanData {
+        SpanData {
+            id,
+            parent,
+            depth,
+            begin: ORIGIN + begin,
+            end: ORIGIN + end,
+            name: name.to_string(),
+        }
+    }
+
+    fn find<'a>(spans: &'a [Span], name: &str) -> &'a Span {
+        spans.iter().find(|span| span.name == name).unwrap()
+    }
+
+    fn parent_name<'a>(spans: &'a [Span], span: &Span) -> Option<&'a str> {
+        spans
+            .iter()
+            .find(|parent| parent.id == span.parent)
+            .map(|parent| parent.name.as_str())
+    }
+
+    #[test]
+    fn round_trip() {
+        let process = ProcessInfo {
+            exe: "server".to_string(),
+            ..ProcessInfo::for_tests(PROCESS_ID, DateTime::from_timestamp_nanos(ORIGIN))
+        };
+        let threads = [
+            ThreadSpans {
+                stream_id: "main".to_string(),
+                max_depth: 3,
+                spans: vec![
+                    span(1, 0, 1, (0, 10_000), "run"),
+                    span(2, 1, 2, (1_000, 4_000), "load"),
+                    span(3, 1, 2, (5_000, 9_000), "update"),
+                    span(4, 3, 3, (5_000, 7_000), "physics"),
+                ],
+                blocks: Vec::new(),
+            },
+            ThreadSpans {
+                stream_id: "worker".to_string(),
+                max_depth: 1,
+                spans: vec![span(1, 0, 1, (2_000, 3_000), "job")],
+                blocks: Vec::new(),
+            },
+        ];
+        let time = DateTime::from_timestamp_nanos(ORIGIN + 6_000).to_rfc3339();
+        let log_entries = [LogEntry {
+            time: time.clone(),
+            target: "app".to_string(),
+            msg: "started".to_string(),
+        }];
+        let measures = [Measure {
+            target: "cpu".to_string(),
+            time: time.clone(),
+            value: 42.5,
+            unit: "percent".to_string(),
+        }];
+
+        let trace = to_chrome_trace(&TraceContent {
+            process: &process,
+            threads: &threads,
+            log_entries: &log_entries,
+            measures: &measures,
+            begin: ORIGIN,
+            end: ORIGIN + 20_000,
+        })
+        .unwrap();
+        let imported = import_chrome_trace(trace.as_bytes(), "trace.json").unwrap();
+
+        assert_eq!(imported.process.process_id, PROCESS_ID);
+        assert_eq!(imported.process.exe, "server");
+
+        let spans = &imported.spans;
+        assert_eq!(spans.len(), 5);
+        for (name, stream_id, depth, parent) in [
+            ("run", "main", 0, None),
+            ("load", "main", 1, Some("run")),
+            ("update", "main", 1, Some("run")),
+            ("physics", "main", 2, Some("update")),
+            ("job", "worker", 0, None),
+        ] {
+            let span = find(spans, name);
+            assert_eq!(span.stream_id, stream_id, "{name}");
+            assert_eq!(span.depth, depth, "{name}");
+            assert_eq!(parent_name(spans, span), parent, "{name}");
+        }
+
+        let physics = find(spans, "physics");
+        assert_eq!(
+            physics.begin,
+            DateTime::from_timestamp_nanos(ORIGIN + 5_000)
+        );
+        assert_eq!(physics.end, DateTime::from_timestamp_nanos(ORIGIN + 7_000));
+
+        let [measure] = imported.measures.as_slice() else {
+            panic!("one measure expected");
+        };
+        assert_eq!(
+            (
+                measure.target.as_str(),
+                measure.unit.as_str(),
+                measure.value
+            ),
+            ("cpu", "percent", 42.5)
+        );
+        assert_eq!(measure.time, time);
+
+        let [log_entry] = imported.log_entries.as_slice() else {
+            panic!("one log entry expected");
+        };
+        assert_eq!(
+            (log_entry.msg.as_str(), log_entry.time.as_str()),
+            ("started", time.as_str())
+        );
+    }
+
+    #[test]
+    fn third_party_trace() {
+        let trace = serde_json::json!([
+            { "name": "frame", "ph": "X", "ts": 0.0, "dur": 10.0, "tid": "main" },
+            { "name": "draw", "ph": "X", "ts": 0.0, "dur": 4.0, "tid": "main" },
+            { "name": "present", "ph": "X", "ts": 4.0, "dur": 6.0, "tid": "main" },
+            { "name": "io", "ph": "X", "ts": 1.0, "dur": 2.0, "tid": 7 },
+            { "name": "orphan", "ph": "X", "ts": 1.0, "dur": 2.0 },
+            { "name": "memory (bytes)", "ph": "C", "ts": 5.0, "args": { "heap": 10, "stack": 2 } },
+        ]);
+        let imported = import_chrome_trace(trace.to_string().as_bytes(), "profile.json").unwrap();
+
+        assert_eq!(imported.process.exe, "profile.json");
+
+        let spans = &imported.spans;
+        for (name, stream_id, depth, parent) in [
+            ("frame", "main", 0, None),
+            ("draw", "main", 1, Some("frame")),
+            ("present", "main", 1, Some("frame")),
+            ("io", "7", 0, None),
+            ("orphan", "0", 0, None),
+        ] {
+            let span = find(spans, name);
+            assert_eq!(span.stream_id, stream_id, "{name}");
+            assert_eq!(span.depth, depth, "{name}");
+            assert_eq!(parent_name(spans, span), parent, "{name}");
+        }
+
+        let mut measures = imported
+            .measures
+            .iter()
+            .map(|measure| {
+                (
+                    measure.target.as_str(),
+                    measure.unit.as_str(),
+                    measure.value,
+                )
+            })
+            .collect::<Vec<_>>();
+        measures.sort_by(|a, b| a.0.cmp(b.0));
+        assert_eq!(
+            measures,
+            [
+                ("memory.heap", "bytes", 10.0),
+                ("memory.stack", "bytes", 2.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn counter_names() {
+        assert_eq!(parse_counter_name("cpu (percent)"), ("cpu", "percent"));
+        assert_eq!(
+            parse_counter_name("queue (items) (max)"),
+            ("queue (items)", "max")
+        );
+        assert_eq!(parse_counter_name("frames"), ("frames", ""));
+        assert_eq!(parse_counter_name("ratio)"), ("ratio)", ""));
+    }
+}
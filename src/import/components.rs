@@ -0,0 +1,200 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use leptos::{
+    component, create_action, create_signal, ev, expect_context, view, For, IntoView, SignalGet,
+    SignalSet, SignalUpdate,
+};
+use tracing::error;
+use uuid::Uuid;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{File, FileList, HtmlInputElement};
+
+use crate::home::Process;
+
+use super::chrome_trace::import_chrome_trace;
+use super::imported_data::{use_imported_processes, ImportedData, ImportedProcess};
+use super::parquet::import_parquet;
+
+/// Outcome of the import of a single file.
+#[derive(Debug, Clone)]
+struct ImportStatus {
+    file_name: String,
+    result: Result<String, String>,
+}
+
+/// Loads Parquet table dumps and Chrome trace files, the imported processes can then be browsed
+/// without an analytics server.
+#[component]
+pub fn Import() -> impl IntoView {
+    let imported_data = expect_context::<ImportedData>();
+
+    let (statuses, set_statuses) = create_signal(Vec::<ImportStatus>::new());
+    let (is_dragging_over, set_dragging_over) = create_signal(false);
+
+    let import = create_action(move |files: &Vec<File>| {
+        let files = files.clone();
+
+        async move {
+            // Parquet dumps without process id of a single batch of files end up in the same process
+            let fallback_process_id = Uuid::new_v4().to_string();
+
+            for file in files {
+                let file_name = file.name();
+                let result = import_file(&file, &fallback_process_id)
+                    .await
+                    .map(|imported_processes| {
+                        let description = describe(&imported_processes);
+                        for imported_process in imported_processes {
+                            imported_data.insert(imported_process);
+                        }
+                        description
+                    })
+                    .map_err(|err| {
+                        error!(file_name, "import error: {err}");
+                        err.to_string()
+                    });
+
+                set_statuses.update(|statuses| statuses.push(ImportStatus { file_name, result }));
+            }
+        }
+    });
+
+    let handle_files = move |files: Option<FileList>| {
+        let Some(files) = files else {
+            return;
+        };
+
+        let files = (0..files.length())
+            .filter_map(|index| files.get(index))
+            .collect::<Vec<_>>();
+        import.dispatch(files);
+    };
+
+    let handle_change = move |evt: ev::Event| {
+        let Some(input) = evt
+            .target()
+            .and_then(|target| target.dyn_into::<HtmlInputElement>().ok())
+        else {
+            return;
+        };
+
+        handle_files(input.files());
+        // Allows to import the same file again
+        input.set_value("");
+    };
+
+    let handle_dragover = move |evt: ev::DragEvent| {
+        evt.prevent_default();
+        set_dragging_over.set(true);
+    };
+
+    let handle_drop = move |evt: ev::DragEvent| {
+        evt.prevent_default();
+        set_dragging_over.set(false);
+        handle_files(
+            evt.data_transfer()
+                .and_then(|data_transfer| data_transfer.files()),
+        );
+    };
+
+    view! {
+        <div class="w-full p-4 flex flex-col gap-4">
+            <article
+                class="flex flex-col items-center gap-2 border-2 border-dashed"
+                class:border-sky-500=move || is_dragging_over.get()
+                on:dragover=handle_dragover
+                on:dragleave=move |_| set_dragging_over.set(false)
+                on:drop=handle_drop
+            >
+                <p>
+                    "Drop Parquet dumps of the processes, log_entries, measures tables or of a spans view, or Chrome trace files"
+                </p>
+                <input
+                    type="file"
+                    multiple
+                    accept=".parquet,.json"
+                    disabled=move || import.pending().get()
+                    on:change=handle_change
+                />
+            </article>
+            <ul>
+                <For
+                    each=move || statuses.get().into_iter().enumerate()
+                    key=|(index, _status)| *index
+                    let:status
+                >
+                    <li>
+                        <strong>{status.1.file_name}</strong>
+                        ": "
+                        {match status.1.result {
+                            Ok(description) => description,
+                            Err(err) => format!("error: {err}"),
+                        }}
+                    </li>
+                </For>
+            </ul>
+            <ImportedProcesses />
+        </div>
+    }
+}
+
+#[component]
+fn ImportedProcesses() -> impl IntoView {
+    let imported_processes = use_imported_processes();
+
+    view! {
+        <table class="striped">
+            <thead>
+                <tr>
+                    <th scope="col"></th>
+                    <th scope="col">"ID"</th>
+                    <th scope="col">"Exe"</th>
+                    <th scope="col">"Start time"</th>
+                </tr>
+            </thead>
+            <tbody>
+                <For
+                    each=move || imported_processes.get()
+                    key=|process| (process.process_id.clone(), process.start_time)
+                    let:process
+                >
+                    <Process process=process></Process>
+                </For>
+            </tbody>
+        </table>
+    }
+}
+
+async fn import_file(file: &File, fallback_process_id: &str) -> Result<Vec<ImportedProcess>> {
+    let file_name = file.name();
+    let buffer = JsFuture::from(file.array_buffer())
+        .await
+        .map_err(|err| anyhow!("file read error: {err:?}"))?;
+    let content = js_sys::Uint8Array::new(&buffer).to_vec();
+
+    let extension = Path::new(&file_name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase);
+
+    match extension.as_deref() {
+        Some("json") => Ok(vec![import_chrome_trace(&content, &file_name)?]),
+        Some("parquet") => import_parquet(Bytes::from(content), &file_name, fallback_process_id),
+        _ => Err(anyhow!("unsupported file type, expected .parquet or .json")),
+    }
+}
+
+fn describe(imported_processes: &[ImportedProcess]) -> String {
+    let count = |len: fn(&ImportedProcess) -> usize| imported_processes.iter().map(len).sum();
+    let log_entries: usize = count(|process| process.log_entries.len());
+    let measures: usize = count(|process| process.measures.len());
+    let spans: usize = count(|process| process.spans.len());
+
+    format!(
+        "{} process(es), {log_entries} log entries, {measures} measures, {spans} spans",
+        imported_processes.len()
+    )
+}
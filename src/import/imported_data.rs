@@ -0,0 +1,166 @@
+use std::{collections::HashMap, rc::Rc};
+
+use chrono::{DateTime, Utc};
+use leptos::{
+    create_rw_signal, provide_context, use_context, RwSignal, Signal, SignalUpdate, SignalWith,
+};
+
+use crate::log::types::LogEntry;
+use crate::measures::types::Measure;
+use crate::spans::types::Span;
+use crate::types::ProcessInfo;
+
+/// Data loaded from a local file, which is displayed in place of the analytics server's data.
+#[derive(Debug, Clone)]
+pub struct ImportedProcess {
+    pub process: ProcessInfo,
+    pub log_entries: Vec<LogEntry>,
+    pub measures: Vec<Measure>,
+    pub spans: Vec<Span>,
+    /// True when the process information wasn't part of the imported files
+    pub synthesized: bool,
+}
+
+impl ImportedProcess {
+    pub fn new(process: ProcessInfo) -> Self {
+        Self {
+            process,
+            log_entries: Vec::new(),
+            measures: Vec::new(),
+            spans: Vec::new(),
+            synthesized: false,
+        }
+    }
+
+    /// Creates a placeholder for a process whose information is unknown.
+    pub fn synthesize(process_id: String, exe: String) -> Self {
+        Self {
+            process: ProcessInfo {
+                process_id,
+                exe,
+                username: String::new(),
+                realname: String::new(),
+                computer: String::new(),
+                distro: String::new(),
+                cpu_brand: String::new(),
+                tsc_frequency: 0,
+                start_time: DateTime::UNIX_EPOCH,
+                start_ticks: 0,
+                parent_process_id: None,
+            },
+            log_entries: Vec::new(),
+            measures: Vec::new(),
+            spans: Vec::new(),
+            synthesized: true,
+        }
+    }
+
+    /// Merges `other` in this process, the process information is kept unless synthesized.
+    pub fn merge(&mut self, other: ImportedProcess) {
+        if self.synthesized && !other.synthesized {
+            self.process = other.process;
+            self.synthesized = false;
+        }
+
+        self.log_entries.extend(other.log_entries);
+        self.measures.extend(other.measures);
+        self.spans.extend(other.spans);
+        self.update_start_time();
+    }
+
+    /// Synthesized processes start with their first recorded event.
+    pub fn update_start_time(&mut self) {
+        if self.synthesized {
+            if let Some((begin, _end)) = self.data_range() {
+                self.process.start_time = begin;
+            }
+        }
+    }
+
+    /// The time range to display, from the process start to its last recorded event.
+    pub fn time_range(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        let begin = self.process.start_time;
+        let end = self
+            .data_range()
+            .map_or(begin, |(_data_begin, data_end)| data_end.max(begin));
+
+        (begin, end)
+    }
+
+    fn data_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let parse = |time: &str| {
+            DateTime::parse_from_rfc3339(time)
+                .ok()
+                .map(|time| time.with_timezone(&Utc))
+        };
+
+        self.log_entries
+            .iter()
+            .filter_map(|log_entry| parse(&log_entry.time))
+            .chain(
+                self.measures
+                    .iter()
+                    .filter_map(|measure| parse(&measure.time)),
+            )
+            .chain(self.spans.iter().flat_map(|span| [span.begin, span.end]))
+            .fold(None, |range, time| match range {
+                None => Some((time, time)),
+                Some((begin, end)) => Some((time.min(begin), time.max(end))),
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ImportedData(RwSignal<HashMap<String, Rc<ImportedProcess>>>);
+
+impl ImportedData {
+    pub fn insert(&self, imported_process: ImportedProcess) {
+        self.0.update(|processes| {
+            let process_id = imported_process.process.process_id.clone();
+            match processes.get_mut(&process_id) {
+                Some(existing) => Rc::make_mut(existing).merge(imported_process),
+                None => {
+                    processes.insert(process_id, Rc::new(imported_process));
+                }
+            }
+        });
+    }
+}
+
+pub fn provide_imported_data() {
+    provide_context(ImportedData(create_rw_signal(HashMap::new())));
+}
+
+/// The data imported for the provided process, if any.
+///
+/// Imported data takes precedence over the analytics server's.
+pub fn use_imported_process(
+    process_id: impl Fn() -> String + 'static,
+) -> Signal<Option<Rc<ImportedProcess>>> {
+    let imported_data = use_context::<ImportedData>();
+
+    Signal::derive(move || {
+        let ImportedData(processes) = imported_data?;
+        processes.with(|processes| processes.get(&process_id()).cloned())
+    })
+}
+
+/// All the imported processes, most recent first.
+pub fn use_imported_processes() -> Signal<Vec<ProcessInfo>> {
+    let imported_data = use_context::<ImportedData>();
+
+    Signal::derive(move || {
+        let Some(ImportedData(processes)) = imported_data else {
+            return Vec::new();
+        };
+
+        let mut processes = processes.with(|processes| {
+            processes
+                .values()
+                .map(|imported_process| imported_process.process.clone())
+                .collect::<Vec<_>>()
+        });
+        processes.sort_by_key(|process| std::cmp::Reverse(process.start_time));
+        processes
+    })
+}
@@ -0,0 +1,6 @@
+mod chrome_trace;
+mod components;
+pub mod imported_data;
+mod parquet;
+
+pub use components::Import;
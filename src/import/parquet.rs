@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::log::types::LogEntry;
+use crate::measures::types::Measure;
+use crate::request::decode_parquet;
+use crate::spans::types::Span;
+use crate::types::ProcessInfo;
+
+use super::imported_data::ImportedProcess;
+
+/// A row of a table dump which may, or may not, contain the process id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Row<T> {
+    process_id: Option<String>,
+    #[serde(flatten)]
+    row: T,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SpanRow {
+    stream_id: Option<String>,
+    id: i64,
+    parent: i64,
+    depth: u32,
+    begin: DateTime<Utc>,
+    end: DateTime<Utc>,
+    name: String,
+    #[serde(default)]
+    target: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TableKind {
+    Processes,
+    LogEntries,
+    Measures,
+    Spans,
+}
+
+/// Imports a dump of the `processes`, `log_entries`, `measures` tables, or of a spans view.
+///
+/// The table is detected from the columns of the file. Rows without a process id are assigned
+/// to `fallback_process_id`.
+pub fn import_parquet(
+    bytes: Bytes,
+    file_name: &str,
+    fallback_process_id: &str,
+) -> Result<Vec<ImportedProcess>> {
+    let kind = detect_table_kind(&bytes)?;
+
+    let mut processes: HashMap<String, ImportedProcess> = HashMap::new();
+    let synthesized = (fallback_process_id, file_name);
+
+    match kind {
+        TableKind::Processes => {
+            for process_info in decode_parquet::<Vec<ProcessInfo>>(bytes)? {
+                get_or_synthesize(
+                    &mut processes,
+                    Some(process_info.process_id.clone()),
+                    synthesized,
+                )
+                .merge(ImportedProcess::new(process_info));
+            }
+        }
+        TableKind::LogEntries => {
+            for Row { process_id, row } in decode_parquet::<Vec<Row<LogEntry>>>(bytes)? {
+                get_or_synthesize(&mut processes, process_id, synthesized)
+                    .log_entries
+                    .push(row);
+            }
+        }
+        TableKind::Measures => {
+            for Row { process_id, row } in decode_parquet::<Vec<Row<Measure>>>(bytes)? {
+                get_or_synthesize(&mut processes, process_id, synthesized)
+                    .measures
+                    .push(row);
+            }
+        }
+        TableKind::Spans => {
+            for Row { process_id, row } in decode_parquet::<Vec<Row<SpanRow>>>(bytes)? {
+                get_or_synthesize(&mut processes, process_id, synthesized)
+                    .spans
+                    .push(Span {
+                        stream_id: row.stream_id.unwrap_or_else(|| "imported".to_string()),
+                        id: row.id,
+                        parent: row.parent,
+                        depth: row.depth,
+                        begin: row.begin,
+                        end: row.end,
+                        name: row.name,
+                        target: row.target,
+                    });
+            }
+        }
+    }
+
+    Ok(processes
+        .into_values()
+        .map(|mut imported_process| {
+            imported_process.update_start_time();
+            imported_process
+        })
+        .collect())
+}
+
+/// Rows without process id belong to the fallback process, named after the imported file.
+fn get_or_synthesize<'a>(
+    processes: &'a mut HashMap<String, ImportedProcess>,
+    process_id: Option<String>,
+    (fallback_process_id, file_name): (&str, &str),
+) -> &'a mut ImportedProcess {
+    let process_id = process_id.unwrap_or_else(|| fallback_process_id.to_string());
+    processes
+        .entry(process_id.clone())
+        .or_insert_with(|| ImportedProcess::synthesize(process_id, file_name.to_string()))
+}
+
+fn detect_table_kind(bytes: &Bytes) -> Result<TableKind> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(bytes.clone())?;
+    let schema = builder.schema();
+    let has_columns = |columns: &[&str]| {
+        columns
+            .iter()
+            .all(|column| schema.field_with_name(column).is_ok())
+    };
+
+    if has_columns(&["process_id", "exe", "start_time", "tsc_frequency"]) {
+        Ok(TableKind::Processes)
+    } else if has_columns(&["time", "target", "msg"]) {
+        Ok(TableKind::LogEntries)
+    } else if has_columns(&["time", "target", "value", "unit"]) {
+        Ok(TableKind::Measures)
+    } else if has_columns(&["id", "parent", "depth", "begin", "end", "name"]) {
+        Ok(TableKind::Spans)
+    } else {
+        bail!(
+            "unsupported table, columns: {:?}",
+            schema
+                .fields()
+                .iter()
+                .map(|field| field.name())
+                .collect::<Vec<_>>()
+        )
+    }
+}
@@ -0,0 +1,87 @@
+use std::{cell::RefCell, collections::HashMap, future::Future, rc::Rc};
+
+use anyhow::{anyhow, Result};
+use futures::future::{AbortHandle, Abortable, FutureExt, LocalBoxFuture, Shared, WeakShared};
+use leptos::on_cleanup;
+use tracing::debug;
+
+use crate::request::Payload;
+
+type Fetch = LocalBoxFuture<'static, Result<Payload, Rc<anyhow::Error>>>;
+type SharedFetch = Shared<Fetch>;
+type WeakFetch = WeakShared<Fetch>;
+
+thread_local! {
+    /// The requests being performed, only weakly referenced so that a request is dropped, and its
+    /// fetch aborted, once nobody waits for it anymore.
+    static IN_FLIGHT: RefCell<HashMap<String, WeakFetch>> = RefCell::new(HashMap::new());
+}
+
+/// Performs `fetch`, unless an identical request (same `key`) is already in flight, in which case
+/// its response is shared.
+pub async fn fetch_shared<F>(key: String, fetch: F) -> Result<Payload>
+where
+    F: Future<Output = Result<Payload>> + 'static,
+{
+    let existing =
+        IN_FLIGHT.with_borrow(|in_flight| in_flight.get(&key).and_then(WeakShared::upgrade));
+
+    let shared = if let Some(shared) = existing {
+        debug!(key, "sharing in-flight request");
+        shared
+    } else {
+        let shared: SharedFetch = fetch
+            .map(|result| result.map_err(Rc::new))
+            .boxed_local()
+            .shared();
+        if let Some(weak) = shared.downgrade() {
+            IN_FLIGHT.with_borrow_mut(|in_flight| in_flight.insert(key.clone(), weak));
+        }
+        shared
+    };
+
+    let result = shared.clone().await;
+
+    // Identical requests made from now on must be performed again
+    IN_FLIGHT.with_borrow_mut(|in_flight| {
+        if in_flight
+            .get(&key)
+            .and_then(WeakShared::upgrade)
+            .is_some_and(|current| current.ptr_eq(&shared))
+        {
+            in_flight.remove(&key);
+        }
+    });
+
+    result.map_err(|err| anyhow!("{err}"))
+}
+
+/// Only lets the latest request complete: starting a request aborts the previous one, as well as
+/// disposing of the owner (e.g. leaving the page).
+#[derive(Clone, Default)]
+pub struct LatestRequest(Rc<RefCell<Option<AbortHandle>>>);
+
+impl LatestRequest {
+    pub fn new() -> Self {
+        let latest_request = Self::default();
+
+        let current = latest_request.0.clone();
+        on_cleanup(move || {
+            if let Some(abort_handle) = current.borrow_mut().take() {
+                abort_handle.abort();
+            }
+        });
+
+        latest_request
+    }
+
+    /// Runs `future`, or returns `None` if it was superseded before completing.
+    pub async fn run<F: Future>(&self, future: F) -> Option<F::Output> {
+        let (abort_handle, registration) = AbortHandle::new_pair();
+        if let Some(previous) = self.0.borrow_mut().replace(abort_handle) {
+            previous.abort();
+        }
+
+        Abortable::new(future, registration).await.ok()
+    }
+}
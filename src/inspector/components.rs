@@ -0,0 +1,205 @@
+use leptos::{
+    component, create_memo, view, Callable, Callback, CollectView, IntoView, Show, SignalGet,
+    SignalSet, SignalWith,
+};
+use leptos_router::A;
+
+use crate::request::PayloadSource;
+
+use super::request_log::{use_request_log, RequestRecord, RequestStatus};
+
+/// Only the beginning of the SQL is displayed, the full text is in the tooltip.
+const MAX_SQL_LENGTH: usize = 80;
+
+/// Lists the requests made to the analytics server, most recent first.
+#[component]
+pub fn RequestInspector(#[prop(into)] on_close: Callback<()>) -> impl IntoView {
+    let request_log = use_request_log();
+    let records = move || {
+        request_log
+            .map(|request_log| request_log.get().into_iter().rev().collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+
+    view! {
+        <aside class="fixed bottom-0 left-0 h-1/3 w-full p-4 overflow-auto z-10 bg-slate-900 shadow-lg">
+            <div class="flex justify-between items-baseline">
+                <strong>"Requests"</strong>
+                <div class="flex gap-4">
+                    <a
+                        href="#"
+                        on:click=move |evt| {
+                            evt.prevent_default();
+                            if let Some(request_log) = request_log {
+                                request_log.set(Vec::new());
+                            }
+                        }
+                    >
+                        "Clear"
+                    </a>
+                    <a
+                        href="#"
+                        on:click=move |evt| {
+                            evt.prevent_default();
+                            on_close.call(());
+                        }
+                    >
+                        "Close"
+                    </a>
+                </div>
+            </div>
+            <table class="text-sm">
+                <thead>
+                    <tr>
+                        <th>"SQL"</th>
+                        <th>"Range"</th>
+                        <th>"Status"</th>
+                        <th>"Size"</th>
+                        <th>"Rows"</th>
+                        <th>"Network"</th>
+                        <th>"Decode"</th>
+                        <th>"Deserialization"</th>
+                        <th></th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {move || {
+                        records()
+                            .into_iter()
+                            .map(|record| view! { <RequestRow record /> })
+                            .collect_view()
+                    }}
+                </tbody>
+            </table>
+        </aside>
+    }
+}
+
+/// Overall progress of the pending requests, displayed while downloading or decoding responses.
+#[component]
+pub fn RequestProgressBar() -> impl IntoView {
+    let request_log = use_request_log();
+    let progress = create_memo(move |_| {
+        let request_log = request_log?;
+        request_log.with(|records| {
+            let pending = records
+                .iter()
+                .filter(|record| record.status == RequestStatus::Pending)
+                .collect::<Vec<_>>();
+            if pending.is_empty() {
+                return None;
+            }
+
+            // The overall size is only known if announced for all the requests
+            let expected = pending
+                .iter()
+                .map(|record| record.expected)
+                .sum::<Option<usize>>();
+
+            Some(PendingProgress {
+                received: pending.iter().map(|record| record.received).sum(),
+                expected,
+                rows: pending.iter().map(|record| record.rows).sum(),
+            })
+        })
+    });
+
+    let description = move || {
+        progress.get().map(|progress| {
+            let expected = progress
+                .expected
+                .map(|expected| format!(" / {:.1}", to_kilobytes(expected) / 1_024.0))
+                .unwrap_or_default();
+            format!(
+                "{:.1}{expected} MB, {} rows",
+                to_kilobytes(progress.received) / 1_024.0,
+                progress.rows
+            )
+        })
+    };
+
+    view! {
+        <Show when=move || progress.with(Option::is_some)>
+            <div class="px-4 flex gap-4 items-center text-sm">
+                // Without a value, the progress bar is indeterminate
+                <progress
+                    value=move || progress.get().and_then(|progress| progress.expected.map(|_| progress.received))
+                    max=move || progress.get().and_then(|progress| progress.expected)
+                ></progress>
+                <span class="whitespace-nowrap">{description}</span>
+            </div>
+        </Show>
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PendingProgress {
+    received: usize,
+    expected: Option<usize>,
+    rows: usize,
+}
+
+#[component]
+fn RequestRow(record: RequestRecord) -> impl IntoView {
+    let sql = if record.sql.chars().count() > MAX_SQL_LENGTH {
+        format!(
+            "{}…",
+            record.sql.chars().take(MAX_SQL_LENGTH).collect::<String>()
+        )
+    } else {
+        record.sql.clone()
+    };
+
+    let range = match (&record.begin, &record.end) {
+        (Some(begin), Some(end)) => format!("{begin} – {end}"),
+        _ => String::new(),
+    };
+
+    let status = match &record.status {
+        RequestStatus::Pending => "pending".to_string(),
+        RequestStatus::Succeeded => match record.source {
+            Some(PayloadSource::Server { status }) => status.to_string(),
+            Some(PayloadSource::Cache) => "cache".to_string(),
+            Some(PayloadSource::Local) => "local".to_string(),
+            None => "ok".to_string(),
+        },
+        RequestStatus::Failed(err) => format!("error: {err}"),
+        RequestStatus::Cancelled => "cancelled".to_string(),
+    };
+
+    let console_href = format!(
+        "/sql?sql={}{}{}",
+        js_sys::encode_uri_component(&record.sql),
+        record
+            .begin
+            .as_ref()
+            .map(|begin| format!("&begin={}", js_sys::encode_uri_component(begin)))
+            .unwrap_or_default(),
+        record
+            .end
+            .as_ref()
+            .map(|end| format!("&end={}", js_sys::encode_uri_component(end)))
+            .unwrap_or_default(),
+    );
+
+    view! {
+        <tr>
+            <td class="font-mono" title=record.sql>{sql}</td>
+            <td>{range}</td>
+            <td>{status}</td>
+            <td>{format!("{:.1} KB", to_kilobytes(record.bytes))}</td>
+            <td>{format!("{} ({} batches)", record.rows, record.batches)}</td>
+            <td>{format!("{:.0} ms", record.network)}</td>
+            <td>{format!("{:.0} ms", record.decode)}</td>
+            <td>{format!("{:.0} ms", record.deserialization)}</td>
+            <td>
+                <A href=console_href>"Open in console"</A>
+            </td>
+        </tr>
+    }
+}
+
+#[expect(clippy::cast_precision_loss)]
+fn to_kilobytes(bytes: usize) -> f64 {
+    bytes as f64 / 1_024.0
+}
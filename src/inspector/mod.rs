@@ -0,0 +1,5 @@
+mod components;
+mod request_log;
+
+pub use components::{RequestInspector, RequestProgressBar};
+pub use request_log::{provide_request_log, DownloadProgress, InspectedRequest};
@@ -0,0 +1,181 @@
+use std::cell::Cell;
+
+use anyhow::Result;
+use leptos::{create_rw_signal, RwSignal, SignalUpdate};
+use serde::Serialize;
+
+use crate::request::PayloadSource;
+
+/// Only the most recent requests are kept.
+const MAX_RECORDS: usize = 200;
+
+thread_local! {
+    static REQUEST_LOG: Cell<Option<RwSignal<Vec<RequestRecord>>>> = const { Cell::new(None) };
+    static NEXT_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RequestStatus {
+    Pending,
+    Succeeded,
+    Failed(String),
+    /// Superseded by another request, or the page was left
+    Cancelled,
+}
+
+/// A request made to the analytics server, all the durations are in milliseconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestRecord {
+    pub id: usize,
+    pub url: String,
+    /// The SQL of queries, or the serialized request for the other endpoints
+    pub sql: String,
+    pub begin: Option<String>,
+    pub end: Option<String>,
+    pub status: RequestStatus,
+    pub source: Option<PayloadSource>,
+    /// Bytes of the response body received so far
+    pub received: usize,
+    /// Size of the response body, when announced by the server
+    pub expected: Option<usize>,
+    pub bytes: usize,
+    pub rows: usize,
+    pub batches: usize,
+    pub network: f64,
+    pub decode: f64,
+    pub deserialization: f64,
+}
+
+/// Must be called once, from the root of the application, before any request is made.
+pub fn provide_request_log() {
+    REQUEST_LOG.set(Some(create_rw_signal(Vec::new())));
+}
+
+/// The requests made so far, oldest first.
+pub fn use_request_log() -> Option<RwSignal<Vec<RequestRecord>>> {
+    REQUEST_LOG.get()
+}
+
+/// Handle updating the record of a request as it progresses, the request is considered cancelled
+/// if dropped before finishing.
+#[derive(Debug)]
+pub struct InspectedRequest {
+    id: usize,
+    finished: Cell<bool>,
+}
+
+impl InspectedRequest {
+    pub fn start(url: &str, request: &impl Serialize) -> Self {
+        let id = NEXT_ID.get();
+        NEXT_ID.set(id + 1);
+
+        let request = serde_json::to_value(request).unwrap_or_default();
+        let field = |name: &str| request.get(name)?.as_str().map(ToString::to_string);
+
+        let record = RequestRecord {
+            id,
+            url: url.to_string(),
+            sql: field("sql").unwrap_or_else(|| request.to_string()),
+            begin: field("begin"),
+            end: field("end"),
+            status: RequestStatus::Pending,
+            source: None,
+            received: 0,
+            expected: None,
+            bytes: 0,
+            rows: 0,
+            batches: 0,
+            network: 0.0,
+            decode: 0.0,
+            deserialization: 0.0,
+        };
+
+        if let Some(request_log) = REQUEST_LOG.get() {
+            request_log.update(|records| {
+                records.push(record);
+                if records.len() > MAX_RECORDS {
+                    records.remove(0);
+                }
+            });
+        }
+
+        Self {
+            id,
+            finished: Cell::new(false),
+        }
+    }
+
+    fn update(&self, update: impl FnOnce(&mut RequestRecord)) {
+        update_record(self.id, update);
+    }
+
+    /// Handle to report the download progress, which can outlive the request.
+    pub fn progress(&self) -> DownloadProgress {
+        DownloadProgress { id: self.id }
+    }
+
+    pub fn set_payload(&self, source: PayloadSource, bytes: usize, network: f64) {
+        self.update(|record| {
+            record.source = Some(source);
+            record.bytes = bytes;
+            record.network = network;
+        });
+    }
+
+    pub fn set_decoded(&self, rows: usize, batches: usize, decode: f64) {
+        self.update(|record| {
+            record.rows = rows;
+            record.batches = batches;
+            record.decode = decode;
+        });
+    }
+
+    pub fn set_deserialized(&self, deserialization: f64) {
+        self.update(|record| record.deserialization = deserialization);
+    }
+
+    pub fn finish<T>(&self, result: &Result<T>) {
+        self.finished.set(true);
+        self.update(|record| {
+            record.status = match result {
+                Ok(_) => RequestStatus::Succeeded,
+                Err(err) => RequestStatus::Failed(err.to_string()),
+            };
+        });
+    }
+}
+
+impl Drop for InspectedRequest {
+    fn drop(&mut self) {
+        if !self.finished.get() {
+            self.update(|record| record.status = RequestStatus::Cancelled);
+        }
+    }
+}
+
+/// Reports the progress of the download of a response body.
+#[derive(Debug, Clone, Copy)]
+pub struct DownloadProgress {
+    id: usize,
+}
+
+impl DownloadProgress {
+    pub fn set_received(self, received: usize, expected: Option<usize>) {
+        update_record(self.id, |record| {
+            record.received = received;
+            record.expected = expected;
+        });
+    }
+}
+
+fn update_record(id: usize, update: impl FnOnce(&mut RequestRecord)) {
+    let Some(request_log) = REQUEST_LOG.get() else {
+        return;
+    };
+
+    request_log.update(|records| {
+        if let Some(record) = records.iter_mut().rev().find(|record| record.id == id) {
+            update(record);
+        }
+    });
+}
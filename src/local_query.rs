@@ -0,0 +1,561 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use arrow::array::{ArrayRef, RecordBatch};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, SchemaRef, TimeUnit};
+use chrono::DateTime;
+use datafusion::datasource::{MemTable, TableProvider};
+use datafusion::prelude::{ident, lit, SessionConfig, SessionContext};
+use datafusion::scalar::ScalarValue;
+use datafusion::sql::TableReference;
+use leptos::{
+    create_resource, create_rw_signal, provide_context, store_value, use_context, RwSignal,
+    Serializable, Signal, SignalGet, SignalSet, SignalUpdate, StoredValue,
+};
+use tracing::{debug, error};
+
+use crate::in_flight::LatestRequest;
+use crate::inspector::InspectedRequest;
+use crate::network::{refetch_on_reconnect, FailureFlag};
+use crate::pipeline::query_local_table;
+use crate::request::{deserialize_batches, fetch_record_batches_with, PayloadSource, QueryRequest};
+
+/// The rows decoded so far are rendered at most this often, in milliseconds.
+const PARTIAL_UPDATE_INTERVAL_MS: f64 = 250.0;
+
+/// The record batches downloaded for a table, along with their time range and the name of their
+/// time column.
+#[derive(Clone)]
+struct LocalTable {
+    rows: Arc<MemTable>,
+    begin: i64,
+    end: i64,
+    time_column: String,
+}
+
+impl LocalTable {
+    /// Runs `sql` over the rows between `begin` and `end`, which stand for the view it selects
+    /// from, restricted to the range like on the server.
+    async fn query(&self, sql: &str, begin: i64, end: i64) -> Result<Vec<RecordBatch>> {
+        // Tasks can't be spawned on other threads in the browser, so the plans must not be
+        // repartitioned
+        let context =
+            SessionContext::new_with_config(SessionConfig::new().with_target_partitions(1));
+
+        let state = context.state();
+        let views = state.resolve_table_references(&state.sql_to_statement(sql, "generic")?)?;
+        let [view] = <[TableReference; 1]>::try_from(views)
+            .map_err(|_views| anyhow!("local queries must select from a single view"))?;
+
+        let time_zone = match self
+            .rows
+            .schema()
+            .field_with_name(&self.time_column)?
+            .data_type()
+        {
+            DataType::Timestamp(_, time_zone) => time_zone.clone(),
+            data_type => return Err(anyhow!("unsupported time column type {data_type}")),
+        };
+        let time = |time| {
+            lit(ScalarValue::TimestampNanosecond(
+                Some(time),
+                time_zone.clone(),
+            ))
+        };
+        let rows = context.read_table(self.rows.clone())?.filter(
+            ident(&self.time_column)
+                .gt_eq(time(begin))
+                .and(ident(&self.time_column).lt_eq(time(end))),
+        )?;
+        context.register_table(view, rows.into_view())?;
+
+        Ok(context.sql(sql).await?.collect().await?)
+    }
+}
+
+/// The tables already downloaded from the analytics server, queried with `DataFusion`.
+#[derive(Clone, Default)]
+pub struct LocalCatalog {
+    tables: HashMap<String, LocalTable>,
+}
+
+impl LocalCatalog {
+    /// Registers the batches downloaded for the table `name` between `begin` and `end`, replacing
+    /// the previous ones.
+    pub fn register(
+        &mut self,
+        name: &str,
+        begin: i64,
+        end: i64,
+        time_column: &str,
+        schema: SchemaRef,
+        batches: Vec<RecordBatch>,
+    ) -> Result<()> {
+        self.tables.insert(
+            name.to_string(),
+            LocalTable {
+                rows: Arc::new(MemTable::try_new(schema, vec![batches])?),
+                begin,
+                end,
+                time_column: time_column.to_string(),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// The result of `sql` over the rows of the table `name` between `begin` and `end`
+    /// (inclusive), or `None` when the range isn't covered by the downloaded batches.
+    ///
+    /// The query is the one sent to the server, the rows of the table standing for the view it
+    /// selects from, so they must hold all the columns it refers to.
+    pub async fn query(
+        &self,
+        name: &str,
+        sql: &str,
+        begin: i64,
+        end: i64,
+    ) -> Option<Result<Vec<RecordBatch>>> {
+        let table = self.tables.get(name)?;
+        if begin < table.begin || end > table.end {
+            return None;
+        }
+
+        Some(table.query(sql, begin, end).await)
+    }
+}
+
+/// The local catalog of the page.
+///
+/// Queries over a time range covered by a table are answered in the browser, without a
+/// round-trip to the server.
+#[derive(Clone, Copy)]
+pub struct LocalTables(StoredValue<LocalCatalog>);
+
+impl LocalTables {
+    fn register(
+        self,
+        name: &str,
+        begin: i64,
+        end: i64,
+        time_column: &str,
+        schema: SchemaRef,
+        batches: Vec<RecordBatch>,
+    ) {
+        let result = self.0.try_update_value(|catalog| {
+            catalog.register(name, begin, end, time_column, schema, batches)
+        });
+        if let Some(Err(err)) = result {
+            error!(name, "local table registration error: {err}");
+        }
+    }
+
+    async fn query(
+        self,
+        name: &str,
+        sql: &str,
+        begin: i64,
+        end: i64,
+    ) -> Option<Result<Vec<RecordBatch>>> {
+        // The batches are shared by the clones, only the ranges are copied
+        let catalog = self.0.with_value(Clone::clone);
+        if let Some(batches) = catalog.query(name, sql, begin, end).await {
+            return Some(batches);
+        }
+
+        // The measures fetched through the pipeline are registered in the worker
+        query_local_table(name, sql, begin, end).await.transpose()
+    }
+}
+
+pub fn provide_local_tables() {
+    provide_context(LocalTables(store_value(LocalCatalog::default())));
+}
+
+/// Performs a query over the table `name`, locally if its range was already downloaded.
+///
+/// The `name` must identify the rows selected by the query regardless of the time range (e.g.
+/// the measures of a given process), and the query must not be limited. The queries over a
+/// downloaded range run again on its rows, which must select all the columns they refer to.
+pub async fn query_table<R: Serializable>(
+    local_tables: Option<LocalTables>,
+    name: &str,
+    time_column: &str,
+    url: &str,
+    request: &QueryRequest,
+) -> Result<R> {
+    let inspected = InspectedRequest::start(url, request);
+
+    let result = async {
+        let table = TableQuery {
+            local_tables,
+            name,
+            time_column,
+            url,
+            request,
+        };
+        let batches = table.batches(&inspected, |_batch| Ok(())).await?;
+
+        let start = js_sys::Date::now();
+        let value = deserialize_batches(&batches);
+        inspected.set_deserialized(js_sys::Date::now() - start);
+
+        value
+    }
+    .await;
+
+    inspected.finish(&result);
+    result
+}
+
+/// Same as `query_table`, but the rows are appended to `rows` as the batches are decoded.
+///
+/// The previous rows are kept until the first batch is decoded, to avoid flickering.
+async fn stream_table<R>(table: TableQuery<'_>, rows: RwSignal<Option<Vec<R>>>) -> Result<()>
+where
+    R: 'static,
+    Vec<R>: Serializable,
+{
+    let inspected = InspectedRequest::start(table.url, table.request);
+
+    let mut pending = Vec::new();
+    let mut is_first_update = true;
+    let mut deserialization = 0.0;
+    let mut last_update = js_sys::Date::now();
+    let mut flush = |pending: &mut Vec<RecordBatch>| -> Result<()> {
+        let start = js_sys::Date::now();
+        let new_rows = deserialize_batches::<Vec<R>>(pending)?;
+        pending.clear();
+        deserialization += js_sys::Date::now() - start;
+        inspected.set_deserialized(deserialization);
+
+        if is_first_update {
+            is_first_update = false;
+            rows.set(Some(new_rows));
+        } else {
+            rows.update(|rows| rows.get_or_insert_with(Vec::new).extend(new_rows));
+        }
+
+        Ok(())
+    };
+
+    let result = async {
+        table
+            .batches(&inspected, |batch| {
+                pending.push(batch.clone());
+                if js_sys::Date::now() - last_update > PARTIAL_UPDATE_INTERVAL_MS {
+                    last_update = js_sys::Date::now();
+                    flush(&mut pending)?;
+                }
+                Ok(())
+            })
+            .await?;
+
+        flush(&mut pending)
+    }
+    .await;
+
+    if result.is_err() {
+        rows.set(None);
+    }
+
+    inspected.finish(&result);
+    result
+}
+
+/// A query over the table `name`, see `query_table`.
+#[derive(Clone, Copy)]
+struct TableQuery<'a> {
+    local_tables: Option<LocalTables>,
+    name: &'a str,
+    time_column: &'a str,
+    url: &'a str,
+    request: &'a QueryRequest,
+}
+
+impl TableQuery<'_> {
+    /// The record batches of the query, `on_batch` is called as soon as each batch is available.
+    async fn batches(
+        self,
+        inspected: &InspectedRequest,
+        mut on_batch: impl FnMut(&RecordBatch) -> Result<()>,
+    ) -> Result<Vec<RecordBatch>> {
+        let (begin, end) = request_range(self.request)?;
+
+        let start = js_sys::Date::now();
+        let local_batches = match self.local_tables {
+            Some(local_tables) => {
+                local_tables
+                    .query(self.name, &self.request.sql, begin, end)
+                    .await
+            }
+            None => None,
+        };
+        if let Some(batches) = local_batches {
+            debug!(name = self.name, "local query");
+            let batches = batches?;
+            inspected.set_payload(PayloadSource::Local, 0, 0.0);
+            inspected.set_decoded(
+                batches.iter().map(RecordBatch::num_rows).sum(),
+                batches.len(),
+                js_sys::Date::now() - start,
+            );
+            for batch in &batches {
+                on_batch(batch)?;
+            }
+            return Ok(batches);
+        }
+
+        let (schema, batches, _bytes) =
+            fetch_record_batches_with(self.url, self.request, inspected, on_batch).await?;
+        if let Some(local_tables) = self.local_tables {
+            local_tables.register(
+                self.name,
+                begin,
+                end,
+                self.time_column,
+                schema,
+                batches.clone(),
+            );
+        }
+
+        Ok(batches)
+    }
+}
+
+pub struct CreateTableStreamReturn<R: 'static> {
+    /// The rows decoded so far, `None` before the first request completes or if it failed
+    pub rows: Signal<Option<Vec<R>>>,
+    pub is_loading: Signal<bool>,
+}
+
+/// Same as `create_request_opt`, but the queries are performed through the local tables, and the
+/// rows are available as soon as the first batches are decoded.
+pub fn create_table_stream_opt<R>(
+    name: impl Fn() -> String + 'static,
+    time_column: &'static str,
+    request: impl Fn() -> Option<(String, QueryRequest)> + 'static,
+) -> CreateTableStreamReturn<R>
+where
+    R: 'static,
+    Vec<R>: Serializable,
+{
+    let local_tables = use_context::<LocalTables>();
+    let latest_request = LatestRequest::new();
+    let failure = FailureFlag::default();
+    let rows = create_rw_signal(None);
+
+    let resource = create_resource(
+        move || request().map(|(url, request)| (name(), url, request)),
+        {
+            let failure = failure.clone();
+            move |request: Option<(String, String, QueryRequest)>| {
+                let latest_request = latest_request.clone();
+                let failure = failure.clone();
+                async move {
+                    let Some((name, url, request)) = request else {
+                        failure.set(false);
+                        rows.set(None);
+                        return;
+                    };
+
+                    let table = TableQuery {
+                        local_tables,
+                        name: &name,
+                        time_column,
+                        url: &url,
+                        request: &request,
+                    };
+                    // TODO: Return an error instead of this
+                    match latest_request.run(stream_table(table, rows)).await {
+                        Some(Ok(())) => failure.set(false),
+                        Some(Err(err)) => {
+                            error!("request error: {err}");
+                            failure.set(true);
+                        }
+                        None => {}
+                    }
+                }
+            }
+        },
+    );
+
+    refetch_on_reconnect(resource, failure);
+
+    CreateTableStreamReturn {
+        rows: rows.into(),
+        is_loading: Signal::derive(move || resource.loading().get()),
+    }
+}
+
+/// The time range of `request`, in nanoseconds since the Unix epoch.
+pub fn request_range(request: &QueryRequest) -> Result<(i64, i64)> {
+    let parse = |time: Option<&String>| {
+        let time = time.ok_or_else(|| anyhow!("query without time range"))?;
+        DateTime::parse_from_rfc3339(time)?
+            .timestamp_nanos_opt()
+            .ok_or_else(|| anyhow!("conversion to nanoseconds overflow"))
+    };
+
+    Ok((parse(request.begin.as_ref())?, parse(request.end.as_ref())?))
+}
+
+/// The timestamps of the column `time_column`, in nanoseconds since the Unix epoch.
+pub fn time_column_nanos(batch: &RecordBatch, time_column: &str) -> Result<ArrayRef> {
+    let times = batch
+        .column_by_name(time_column)
+        .ok_or_else(|| anyhow!("missing time column {time_column}"))?;
+
+    match times.data_type() {
+        DataType::Timestamp(_, _) => Ok(cast(
+            &cast(times, &DataType::Timestamp(TimeUnit::Nanosecond, None))?,
+            &DataType::Int64,
+        )?),
+        data_type => Err(anyhow!("unsupported time column type {data_type}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{AsArray, Float64Array, TimestampNanosecondArray};
+    use arrow::datatypes::{Field, Schema, TimestampNanosecondType};
+    use futures::executor::block_on;
+
+    use super::*;
+
+    fn batch(times: &[i64]) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, Some("+00:00".into())),
+                false,
+            ),
+            Field::new("value", DataType::Float64, false),
+        ]));
+        #[expect(clippy::cast_precision_loss)]
+        let values = times.iter().map(|time| *time as f64).collect::<Vec<_>>();
+
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(TimestampNanosecondArray::from(times.to_vec()).with_timezone("+00:00")),
+                Arc::new(Float64Array::from(values)),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn catalog() -> LocalCatalog {
+        let batches = vec![batch(&[10, 20, 30]), batch(&[40, 50])];
+        let mut catalog = LocalCatalog::default();
+        catalog
+            .register("measures/p", 0, 100, "time", batches[0].schema(), batches)
+            .unwrap();
+        catalog
+    }
+
+    /// The rows of the table, the range being applied like on the server.
+    const ALL_ROWS: &str = "SELECT * FROM measures ORDER BY \"time\" ASC";
+
+    fn times(batches: &[RecordBatch]) -> Vec<i64> {
+        batches
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .column_by_name("time")
+                    .unwrap()
+                    .as_primitive::<TimestampNanosecondType>()
+                    .values()
+                    .to_vec()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn covered_ranges_are_queried_locally() {
+        let catalog = catalog();
+
+        let batches = block_on(catalog.query("measures/p", ALL_ROWS, 20, 40))
+            .unwrap()
+            .unwrap();
+        assert_eq!(times(&batches), [20, 30, 40]);
+
+        let batches = block_on(catalog.query("measures/p", ALL_ROWS, 0, 100))
+            .unwrap()
+            .unwrap();
+        assert_eq!(times(&batches), [10, 20, 30, 40, 50]);
+
+        let batches = block_on(catalog.query("measures/p", ALL_ROWS, 60, 100))
+            .unwrap()
+            .unwrap();
+        assert_eq!(times(&batches), [] as [i64; 0]);
+    }
+
+    #[test]
+    fn the_whole_query_runs_locally() {
+        let catalog = catalog();
+
+        let sql =
+            "SELECT \"time\"\n  FROM measures\n WHERE \"value\" >= 20\n ORDER BY \"time\" DESC";
+        let batches = block_on(catalog.query("measures/p", sql, 0, 40))
+            .unwrap()
+            .unwrap();
+        assert_eq!(times(&batches), [40, 30, 20]);
+
+        // The time literals of the query builder
+        let sql = "SELECT * FROM measures WHERE \"time\" <= '1970-01-01T00:00:00.000000030+00:00'";
+        let batches = block_on(catalog.query("measures/p", sql, 0, 100))
+            .unwrap()
+            .unwrap();
+        assert_eq!(times(&batches), [10, 20, 30]);
+
+        let sql = "SELECT count(*) AS \"count\", sum(\"value\") AS \"sum\" FROM measures";
+        let batches = block_on(catalog.query("measures/p", sql, 20, 100))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            arrow::util::pretty::pretty_format_batches(&batches)
+                .unwrap()
+                .to_string(),
+            "+-------+-------+\n\
+             | count | sum   |\n\
+             +-------+-------+\n\
+             | 4     | 140.0 |\n\
+             +-------+-------+"
+        );
+    }
+
+    #[test]
+    fn local_queries_select_from_a_single_view() {
+        let catalog = catalog();
+        let sql = "SELECT * FROM measures UNION ALL SELECT * FROM log_entries";
+        assert!(block_on(catalog.query("measures/p", sql, 0, 100))
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn other_ranges_and_tables_are_not_covered() {
+        let catalog = catalog();
+        assert!(block_on(catalog.query("measures/p", ALL_ROWS, -1, 50)).is_none());
+        assert!(block_on(catalog.query("measures/p", ALL_ROWS, 0, 101)).is_none());
+        assert!(block_on(catalog.query("measures/q", ALL_ROWS, 0, 100)).is_none());
+    }
+
+    #[test]
+    fn registering_replaces_the_table() {
+        let mut catalog = catalog();
+        let batches = vec![batch(&[150])];
+        catalog
+            .register("measures/p", 100, 200, "time", batches[0].schema(), batches)
+            .unwrap();
+
+        assert!(block_on(catalog.query("measures/p", ALL_ROWS, 0, 100)).is_none());
+        let batches = block_on(catalog.query("measures/p", ALL_ROWS, 100, 200))
+            .unwrap()
+            .unwrap();
+        assert_eq!(times(&batches), [150]);
+    }
+}
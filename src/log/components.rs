@@ -3,22 +3,18 @@ use leptos::{
     component, create_effect, create_memo, view, For, IntoView, Params, SignalGet, SignalWith,
 };
 use leptos_router::{use_params, Params};
-use serde::{Deserialize, Serialize};
 use tracing::debug;
 use uuid::Uuid;
 
 use crate::{
     components::Spinner,
     datetime::display_datetime,
-    request::{create_request, QueryRequest, QUERY_URL},
+    import::imported_data::use_imported_process,
+    query_builder::{col, Order, Query, Source},
+    request::{create_request_opt, QueryRequest, QUERY_URL},
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LogEntry {
-    pub time: String,
-    pub target: String,
-    pub msg: String,
-}
+use super::types::LogEntry;
 
 #[derive(Params, PartialEq)]
 struct LogParams {
@@ -38,14 +34,28 @@ pub fn Log() -> impl IntoView {
         })
     };
 
-    let request = create_memo(move |_| log_request(id()));
-    let log = create_request::<_, Vec<LogEntry>>(move || request.get());
+    let imported_process = use_imported_process(move || id().to_string());
+
+    let request = create_memo(move |_| {
+        if imported_process.with(Option::is_some) {
+            return None;
+        }
+
+        Some(log_request(id()))
+    });
+    let log = create_request_opt::<_, Vec<LogEntry>>(move || request.get());
+
+    let log_entries = move || match imported_process.get() {
+        Some(imported_process) => {
+            let mut log_entries = imported_process.log_entries.clone();
+            log_entries.sort_by(|a, b| b.time.cmp(&a.time));
+            log_entries
+        }
+        None => log.get().unwrap_or_default(),
+    };
 
     create_effect(move |_| {
-        let count = log
-            .get()
-            .map(|log_entries| log_entries.len())
-            .unwrap_or_default();
+        let count = log_entries().len();
 
         debug!("count={count}");
     });
@@ -56,7 +66,7 @@ pub fn Log() -> impl IntoView {
                 if log.loading().get() {
                     view! { <Spinner /> }
                 } else {
-                    let log_entries = log.get().unwrap_or_default();
+                    let log_entries = log_entries();
                     view! { <LogEntries log_entries></LogEntries> }
                 }
             }}
@@ -120,9 +130,11 @@ fn log_request(id: Uuid) -> (String, QueryRequest) {
     let end = Utc::now();
     let begin = end - Duration::days(1);
     let request = QueryRequest {
-        sql: format!(
-            "select * from log_entries where process_id = '{id}' order by time desc limit 5000"
-        ),
+        sql: Query::from(Source::LogEntries)
+            .filter(col("process_id").eq(id))
+            .order_by("time", Order::Desc)
+            .limit(5000)
+            .to_string(),
         begin: Some(begin.to_rfc3339()),
         end: Some(end.to_rfc3339()),
     };
@@ -1,3 +1,4 @@
 mod components;
+pub mod types;
 
 pub use components::Log;
@@ -1,24 +1,58 @@
 #![deny(clippy::all, clippy::pedantic)]
 #![allow(non_snake_case, clippy::module_name_repetitions)]
 
-use leptos::{component, view, IntoView};
+use leptos::{component, create_signal, view, IntoView, Show, SignalGet, SignalSet, SignalUpdate};
 use leptos_router::{Route, Router, Routes, A};
 use tracing::Level;
 use wasm_tracing::WASMLayerConfigBuilder;
 
+use crate::flamegraph::FlameGraph;
 use crate::home::Home;
+use crate::import::imported_data::provide_imported_data;
+use crate::import::Import;
+use crate::inspector::{provide_request_log, RequestInspector, RequestProgressBar};
+use crate::local_query::provide_local_tables;
 use crate::log::Log;
 use crate::measures::Measures;
+use crate::network::{provide_network_status, OfflineBanner};
+use crate::schema::SchemaBrowser;
+use crate::settings::{provide_auth_state, AuthRequiredBanner, Settings};
+use crate::span_statistics::SpanStatistics;
+use crate::sql::SqlConsole;
+use crate::timeline::Timeline;
+use crate::web_worker::WorkerRole;
 
 mod binary_search;
+mod cache;
 mod components;
 mod datetime;
+mod draw_target;
+mod export;
+mod flamegraph;
+mod frame_scheduler;
 mod home;
+mod import;
+mod in_flight;
+mod inspector;
+mod local_query;
 mod log;
 mod measures;
+mod network;
+mod offscreen;
+mod pipeline;
+mod query_builder;
 mod request;
+mod schema;
+mod settings;
+mod span_statistics;
+mod spans;
+mod sql;
+mod ticks;
+mod timeline;
 mod types;
 mod use_canvas;
+mod use_time_selection;
+mod web_worker;
 
 fn main() {
     console_error_panic_hook::set_once();
@@ -27,11 +61,26 @@ fn main() {
             .set_max_level(Level::INFO)
             .build(),
     );
-    leptos::mount_to_body(|| view! { <App /> });
+
+    // The same module is loaded by the workers
+    match web_worker::current_role() {
+        Some(WorkerRole::Pipeline) => pipeline::run_worker(),
+        Some(WorkerRole::Renderer) => offscreen::run_worker(),
+        None => leptos::mount_to_body(|| view! { <App /> }),
+    }
 }
 
 #[component]
 fn App() -> impl IntoView {
+    provide_imported_data();
+    provide_local_tables();
+    provide_request_log();
+    provide_auth_state();
+    provide_network_status();
+
+    let (show_schema, set_show_schema) = create_signal(false);
+    let (show_requests, set_show_requests) = create_signal(false);
+
     view! {
         <main class="container-fluid">
             <Router>
@@ -43,12 +92,60 @@ fn App() -> impl IntoView {
                             </strong>
                         </li>
                     </ul>
+                    <ul>
+                        <li>
+                            <A href="/sql">"SQL"</A>
+                        </li>
+                        <li>
+                            <A href="/import">"Import"</A>
+                        </li>
+                        <li>
+                            <a
+                                href="#"
+                                on:click=move |evt| {
+                                    evt.prevent_default();
+                                    set_show_schema.update(|show_schema| *show_schema = !*show_schema);
+                                }
+                            >
+                                "Schema"
+                            </a>
+                        </li>
+                        <li>
+                            <a
+                                href="#"
+                                on:click=move |evt| {
+                                    evt.prevent_default();
+                                    set_show_requests.update(|show_requests| *show_requests = !*show_requests);
+                                }
+                            >
+                                "Requests"
+                            </a>
+                        </li>
+                        <li>
+                            <A href="/settings">"Settings"</A>
+                        </li>
+                    </ul>
                 </nav>
+                <Show when=move || show_schema.get()>
+                    <SchemaBrowser on_close=move |()| set_show_schema.set(false) />
+                </Show>
+                <Show when=move || show_requests.get()>
+                    <RequestInspector on_close=move |()| set_show_requests.set(false) />
+                </Show>
+                <RequestProgressBar />
+                <OfflineBanner />
+                <AuthRequiredBanner />
                 <div class="h-full w-full overflow-auto">
                     <Routes>
                         <Route path="/" view=Home />
+                        <Route path="/sql" view=SqlConsole />
+                        <Route path="/import" view=Import />
+                        <Route path="/settings" view=Settings />
                         <Route path="/measures/:id" view=Measures />
                         <Route path="/log/:id" view=Log />
+                        <Route path="/timeline/:id" view=Timeline />
+                        <Route path="/flamegraph/:id" view=FlameGraph />
+                        <Route path="/spans/:id" view=SpanStatistics />
                         <Route path="/*any" view=|| view! { <h1>"Not Found"</h1> } />
                     </Routes>
                 </div>
@@ -1,73 +1,153 @@
-use std::{cmp::Ordering, f64::consts::PI, ops::Range};
+use std::{f64::consts::PI, ops::Range};
 
-use anyhow::{anyhow, bail, Result};
-use chrono::{DateTime, Duration, DurationRound, SecondsFormat, Utc};
+use anyhow::Result;
+use chrono::{DateTime, Duration, DurationRound, Local, SecondsFormat, Utc};
 use humantime::format_duration;
-use leptos::{html::Canvas, HtmlElement};
+use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info};
-use wasm_bindgen::JsCast;
 use web_sys::CanvasRenderingContext2d;
 
-use crate::binary_search::binary_search_by_with_index;
+use crate::binary_search::{lower_bound, visible_range, visible_slice};
+use crate::draw_target::DrawTarget;
+use crate::use_canvas::BitmapCanvas;
 
 use super::types::{MeasureSet, MeasuresData};
 
 const SCALE_PADDING: f64 = 16.0;
 
+const SCATTER_RADIUS: f64 = 2.5;
+
+/// Maximum distance (in pixels) between the cursor and a measure for the latter to be picked.
+const PICK_DISTANCE: f64 = 8.0;
+
+const CROSSHAIR_COLOR: &str = "rgba(255, 255, 255, 0.3)";
+
+/// The whole value axis, from zero to the maximum of each series.
+pub const FULL_VALUE_RANGE: (f64, f64) = (0.0, 1.0);
+
 const COLORS: [&str; 5] = ["#ff00c1", "#9600ff", "#4900ff", "#00b8ff", "#00fff9"];
 
-pub struct MeasuresCanvas {
-    ctx: CanvasRenderingContext2d,
-    force_debug: bool,
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MeasuresStyle {
+    /// Consecutive measures are joined by a line
+    #[default]
+    Lines,
+    /// Every measure is drawn as an isolated dot
+    Scatter,
 }
 
-impl MeasuresCanvas {
-    pub fn try_new(node: &HtmlElement<Canvas>) -> Result<Self> {
-        #[derive(serde::Serialize)]
-        struct ContextOptions {
-            alpha: bool,
+/// The time zone of the time axis labels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LabelTimeZone {
+    /// The time zone of the browser
+    #[default]
+    Local,
+    Utc,
+}
+
+impl LabelTimeZone {
+    fn format(self, time: DateTime<Utc>) -> String {
+        match self {
+            Self::Local => time
+                .with_timezone(&Local)
+                .to_rfc3339_opts(SecondsFormat::Millis, true),
+            Self::Utc => time.to_rfc3339_opts(SecondsFormat::Millis, true),
         }
+    }
+}
 
-        let ctx = node
-            .get_context_with_context_options(
-                "2d",
-                &serde_wasm_bindgen::to_value(&ContextOptions { alpha: false })
-                    .map_err(|err| anyhow!("context options serialization error: {err}"))?,
-            )
-            .map_err(|err| anyhow!("{err:?}"))?;
-        let Some(ctx) = ctx else {
-            bail!("canvas' 2d context not found");
-        };
+/// The part of the timeline displayed by a chart, its size and the cursor position.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChartView {
+    pub begin: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// In CSS pixels, like `cursor`
+    pub width: f64,
+    pub height: f64,
+    pub dpr: f64,
+    /// The part of the value axis displayed, as fractions of the maximum of each series
+    pub value_range: (f64, f64),
+    /// `None` when the cursor isn't over the chart
+    pub cursor: Option<(f64, f64)>,
+    pub time_zone: LabelTimeZone,
+}
+
+impl ChartView {
+    /// Whether only the cursor moved since `previous`, in which case the measures don't need to
+    /// be redrawn.
+    pub fn moves_only_cursor(&self, previous: &Self) -> bool {
+        Self {
+            cursor: None,
+            ..*self
+        } == Self {
+            cursor: None,
+            ..*previous
+        }
+    }
 
-        let ctx = ctx
-            .dyn_into()
-            .map_err(|err| anyhow!("context dyn conversion error: {err:?}"))?;
+    /// The size of the canvas' bitmap, in device pixels.
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn bitmap_size(&self) -> (u32, u32) {
+        (
+            (self.width * self.dpr) as u32,
+            (self.height * self.dpr) as u32,
+        )
+    }
+}
 
-        Ok(Self {
+pub struct MeasuresCanvas<T = CanvasRenderingContext2d> {
+    ctx: T,
+    /// Whether the rendering statistics are drawn over the measures
+    debug: bool,
+    style: MeasuresStyle,
+}
+
+impl<T: DrawTarget> MeasuresCanvas<T> {
+    pub fn new(ctx: T) -> Self {
+        Self {
             ctx,
-            force_debug: false,
-        })
+            debug: cfg!(debug_assertions),
+            style: MeasuresStyle::default(),
+        }
     }
 
     #[must_use]
     pub fn with_force_debug(mut self) -> Self {
-        self.force_debug = true;
+        self.debug = true;
         self
     }
 
-    #[expect(clippy::too_many_arguments)]
-    pub fn render(
-        &mut self,
-        measures: &MeasuresData,
-        begin: DateTime<Utc>,
-        end: DateTime<Utc>,
-        width: f64,
-        height: f64,
-        mouse_x: f64,
-        dpr: f64,
-    ) {
+    /// Never draws the rendering statistics, even in debug builds.
+    #[must_use]
+    pub fn without_debug(mut self) -> Self {
+        self.debug = false;
+        self
+    }
+
+    #[must_use]
+    pub fn with_style(mut self, style: MeasuresStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn into_target(self) -> T {
+        self.ctx
+    }
+
+    pub fn render(&mut self, measures: &MeasuresData, view: &ChartView) {
         debug!("rendering");
 
+        let ChartView {
+            begin,
+            end,
+            width,
+            height,
+            dpr,
+            value_range,
+            cursor: _,
+            time_zone,
+        } = *view;
+
         if width < f64::EPSILON || height < f64::EPSILON {
             return;
         }
@@ -75,17 +155,16 @@ impl MeasuresCanvas {
         self.ctx.save();
         if let Err(err) = self.ctx.scale(dpr, dpr) {
             error!(dpr, "context scaling failed: {err:?}");
-        };
+        }
         self.ctx.set_font("14px Arial");
-        self.ctx.set_fill_style(&"#13171f".into());
+        self.ctx.set_fill_style("#13171f");
         self.ctx.fill_rect(0.0, 0.0, width, height);
-        self.ctx.set_fill_style(&"white".into());
+        self.ctx.set_fill_style("white");
 
-        self.render_scales(measures, width, height, begin, end, mouse_x);
-        self.render_measures(measures, width, height, begin, end, mouse_x);
-        self.render_dots(measures, width, height, begin, end, mouse_x);
-        if self.force_debug || cfg!(debug_assertions) {
-            self.render_stats(measures, width, height, begin, end, mouse_x);
+        self.render_scales(measures, width, height, begin, end, time_zone);
+        self.render_measures(measures, width, height, begin, end, value_range);
+        if self.debug {
+            self.render_stats(measures, width, height, begin, end);
         }
 
         self.ctx.restore();
@@ -98,75 +177,12 @@ impl MeasuresCanvas {
         height: f64,
         begin: DateTime<Utc>,
         end: DateTime<Utc>,
-        _mouse_x: f64,
+        time_zone: LabelTimeZone,
     ) {
         debug!("rendering scales");
 
-        let Some(begin_ns) = begin.timestamp_nanos_opt() else {
-            error!(
-                begin = begin.to_rfc3339(),
-                "conversion nanoseconds overflow"
-            );
-            return;
-        };
-        let Some(end_ns) = end.timestamp_nanos_opt() else {
-            error!(end = end.to_rfc3339(), "conversion nanoseconds overflow");
-            return;
-        };
         let y = height / 100.0 * 90.0;
-
-        self.ctx.begin_path();
-        self.ctx.move_to(0.0, y);
-        self.ctx.line_to(width, y);
-        self.ctx.set_stroke_style(&"white".into());
-        self.ctx.stroke();
-
-        let duration = end - begin;
-
-        #[expect(clippy::cast_possible_truncation)]
-        let scales = ((width / 204.0) as i32).max(1);
-
-        let interval = duration / scales;
-
-        let truncated_begin_time = match begin.duration_trunc(duration / scales) {
-            Ok(truncated_begin_time) => truncated_begin_time,
-            Err(err) => {
-                error!(
-                    begin = begin.to_rfc3339(),
-                    %duration,
-                    scales,
-                    "date time duration trunc error: {err}"
-                );
-                return;
-            }
-        };
-
-        let mut time = truncated_begin_time + duration / scales;
-        for _ in 0..scales {
-            let Some(time_ns) = time.timestamp_nanos_opt() else {
-                error!(time = time.to_rfc3339(), "conversion nanoseconds overflow");
-                continue;
-            };
-            let x = time_to_x(time_ns, begin_ns, end_ns, width);
-
-            self.ctx.begin_path();
-            self.ctx.move_to(x, y);
-            self.ctx.line_to(x, y + SCALE_PADDING);
-            self.ctx.set_stroke_style(&"white".into());
-            self.ctx.stroke();
-
-            if let Err(err) = self.ctx.fill_text(
-                &time
-                    .with_timezone(&chrono::Local)
-                    .to_rfc3339_opts(SecondsFormat::Millis, true),
-                x - 90.0,
-                y + SCALE_PADDING * 2.0,
-            ) {
-                error!("fill text error: {err:?}");
-            }
-
-            time += interval;
-        }
+        render_time_axis(&mut self.ctx, width, y, begin, end, time_zone);
     }
 
     fn render_measures(
@@ -176,7 +192,7 @@ impl MeasuresCanvas {
         height: f64,
         begin: DateTime<Utc>,
         end: DateTime<Utc>,
-        _mouse_x: f64,
+        value_range: (f64, f64),
     ) {
         debug!("rendering measures");
 
@@ -189,85 +205,46 @@ impl MeasuresCanvas {
             return;
         };
 
-        for (index, (target, measure_set)) in measures.iter().enumerate() {
+        for (index, (target, measure_set)) in sorted_by_target(measures).into_iter().enumerate() {
             info!("target={target}");
 
             self.ctx.begin_path();
 
             let color = get_color(index);
-            self.ctx.set_stroke_style(&color.into());
+            self.ctx.set_stroke_style(color);
 
             let max_measure = get_max_measure_value(measure_set, begin_ns, end_ns);
 
-            for (index, (time, value)) in measure_set.measures.iter().enumerate() {
-                let mut drawn = *time > begin_ns && *time < end_ns;
-
-                if let Some((time, _value)) = measure_set.measures.get(index - 1) {
-                    drawn |= *time > begin_ns && *time < end_ns;
-                }
-
-                if let Some((time, _value)) = measure_set.measures.get(index + 1) {
-                    drawn |= *time > begin_ns && *time < end_ns;
+            for (time, value) in visible_slice(&measure_set.measures, begin_ns, end_ns) {
+                let x = time_to_x(*time, begin_ns, end_ns, width);
+                let y = value_to_y(*value, max_measure, height, value_range);
+
+                match self.style {
+                    MeasuresStyle::Lines => self.ctx.line_to(x, y),
+                    MeasuresStyle::Scatter => {
+                        self.ctx.move_to(x + SCATTER_RADIUS, y);
+                        if let Err(err) = self.ctx.arc(x, y, SCATTER_RADIUS, 0.0, 2.0 * PI) {
+                            error!("arc drawing error: {err:?}");
+                        }
+                    }
                 }
+            }
 
-                if !drawn {
-                    continue;
+            match self.style {
+                MeasuresStyle::Lines => self.ctx.stroke(),
+                MeasuresStyle::Scatter => {
+                    self.ctx.set_fill_style(color);
+                    self.ctx.fill();
+                    self.ctx.set_fill_style("white");
                 }
-
-                let x = time_to_x(*time, begin_ns, end_ns, width);
-                let y = value_to_y(*value, max_measure, height);
-
-                self.ctx.line_to(x, y);
             }
-
-            self.ctx.stroke();
         }
 
         self.ctx.set_font("14px Arial");
-        self.ctx.set_stroke_style(&"white".into());
-    }
-
-    fn render_dots(
-        &mut self,
-        measures: &MeasuresData,
-        width: f64,
-        height: f64,
-        begin: DateTime<Utc>,
-        end: DateTime<Utc>,
-        mouse_x: f64,
-    ) {
-        debug!("rendering dots");
-
-        let Some(begin_ns) = begin.timestamp_nanos_opt() else {
-            error!(begin = begin.to_rfc3339(), "nanoseconds conversion error");
-            return;
-        };
-        let Some(end_ns) = end.timestamp_nanos_opt() else {
-            error!(end = end.to_rfc3339(), "nanoseconds conversion error");
-            return;
-        };
-        let mouse_x_time = x_to_time(mouse_x, begin_ns, end_ns, width);
-
-        for (index, measure_set) in measures.values().enumerate() {
-            if let Some((time, value)) = find_closest_measure(&measure_set.measures, mouse_x_time) {
-                let x = time_to_x(time, begin_ns, end_ns, width);
-                let y = value_to_y(value, measure_set.max, height);
-
-                let color = get_color(index);
-                self.ctx.set_fill_style(&color.into());
-
-                self.ctx.begin_path();
-                if let Err(err) = self.ctx.arc(x, y, 2.0, 0.0, 2.0 * PI) {
-                    error!("arc drawing error: {err:?}");
-                }
-                self.ctx.fill();
-
-                self.ctx.set_fill_style(&"white".into());
-            }
-        }
+        self.ctx.set_stroke_style("white");
     }
 
-    #[expect(clippy::too_many_lines, clippy::cast_precision_loss)]
+    #[expect(clippy::cast_precision_loss)]
     fn render_stats(
         &mut self,
         measures: &MeasuresData,
@@ -275,7 +252,6 @@ impl MeasuresCanvas {
         _height: f64,
         begin: DateTime<Utc>,
         end: DateTime<Utc>,
-        _mouse_x: f64,
     ) {
         debug!("rendering stats");
 
@@ -289,27 +265,10 @@ impl MeasuresCanvas {
         };
         let duration = end - begin;
 
-        let mut num_points = 0;
-
-        for measure_set in measures.values() {
-            for (index, (time, _value)) in measure_set.measures.iter().enumerate() {
-                let mut displayed = *time > begin_ns && *time < end_ns;
-
-                if let Some((time, _value)) = measure_set.measures.get(index - 1) {
-                    displayed |= *time > begin_ns && *time < end_ns;
-                }
-
-                if let Some((time, _value)) = measure_set.measures.get(index + 1) {
-                    displayed |= *time > begin_ns && *time < end_ns;
-                }
-
-                if !displayed {
-                    continue;
-                }
-
-                num_points += 1;
-            }
-        }
+        let num_points = measures
+            .values()
+            .map(|measure_set| visible_range(&measure_set.measures, begin_ns, end_ns).len())
+            .sum::<usize>();
 
         let num_points = num_points.to_string();
         let num_points = num_points
@@ -390,9 +349,9 @@ impl MeasuresCanvas {
             error!("fill text error: {err:?}");
         }
 
-        for (index, (target, measure_set)) in measures.iter().enumerate() {
+        for (index, (target, measure_set)) in sorted_by_target(measures).into_iter().enumerate() {
             let color = get_color(index);
-            self.ctx.set_fill_style(&color.into());
+            self.ctx.set_fill_style(color);
             if let Err(err) = self.ctx.fill_text(
                 &format!("{target} ({})", measure_set.unit),
                 16.0,
@@ -400,8 +359,187 @@ impl MeasuresCanvas {
             ) {
                 error!("fill text error: {err:?}");
             }
-            self.ctx.set_fill_style(&"white".into());
+            self.ctx.set_fill_style("white");
+        }
+    }
+}
+
+/// The measures of a chart, drawn the same way on the page and in the renderer worker.
+pub fn chart_canvas<T: DrawTarget>(ctx: T, style: MeasuresStyle) -> MeasuresCanvas<T> {
+    // TODO: Remove force debug when more stable
+    MeasuresCanvas::new(ctx)
+        .with_force_debug()
+        .with_style(style)
+}
+
+/// The measures of an exported image, without the rendering statistics.
+pub fn export_canvas<T: DrawTarget>(ctx: T, style: MeasuresStyle) -> MeasuresCanvas<T> {
+    MeasuresCanvas::new(ctx).without_debug().with_style(style)
+}
+
+/// Draws the cursor over a `MeasuresCanvas`, on a transparent canvas so that moving the cursor
+/// doesn't redraw the measures.
+pub struct MeasuresOverlay<T = CanvasRenderingContext2d> {
+    ctx: T,
+}
+
+impl MeasuresOverlay {
+    pub fn try_new(canvas: &impl BitmapCanvas) -> Result<Self> {
+        Ok(Self::new(canvas.context_2d(true)?))
+    }
+}
+
+impl<T: DrawTarget> MeasuresOverlay<T> {
+    pub fn new(ctx: T) -> Self {
+        Self { ctx }
+    }
+
+    pub fn render(&mut self, measures: &MeasuresData, view: &ChartView) {
+        debug!("rendering overlay");
+
+        let ChartView {
+            width,
+            height,
+            dpr,
+            cursor,
+            ..
+        } = *view;
+
+        self.ctx.save();
+        if let Err(err) = self.ctx.scale(dpr, dpr) {
+            error!(dpr, "context scaling failed: {err:?}");
+        }
+        self.ctx.clear_rect(0.0, 0.0, width, height);
+
+        if let Some((mouse_x, mouse_y)) = cursor {
+            self.render_crosshair(width, height, mouse_x, mouse_y);
+            self.render_dots(measures, view, mouse_x);
+        }
+
+        self.ctx.restore();
+    }
+
+    fn render_crosshair(&mut self, width: f64, height: f64, mouse_x: f64, mouse_y: f64) {
+        self.ctx.begin_path();
+        self.ctx.move_to(mouse_x, 0.0);
+        self.ctx.line_to(mouse_x, height);
+        self.ctx.move_to(0.0, mouse_y);
+        self.ctx.line_to(width, mouse_y);
+        self.ctx.set_stroke_style(CROSSHAIR_COLOR);
+        self.ctx.stroke();
+    }
+
+    /// Draws the measures closest to the cursor time, on the lines of their series.
+    fn render_dots(&mut self, measures: &MeasuresData, view: &ChartView, mouse_x: f64) {
+        debug!("rendering dots");
+
+        let ChartView {
+            begin,
+            end,
+            width,
+            height,
+            value_range,
+            ..
+        } = *view;
+
+        let Some(begin_ns) = begin.timestamp_nanos_opt() else {
+            error!(begin = begin.to_rfc3339(), "nanoseconds conversion error");
+            return;
+        };
+        let Some(end_ns) = end.timestamp_nanos_opt() else {
+            error!(end = end.to_rfc3339(), "nanoseconds conversion error");
+            return;
+        };
+        let mouse_x_time = x_to_time(mouse_x, begin_ns, end_ns, width);
+
+        for (index, (_target, measure_set)) in sorted_by_target(measures).into_iter().enumerate() {
+            if let Some((time, value)) = find_closest_measure(&measure_set.measures, mouse_x_time) {
+                let max_measure = get_max_measure_value(measure_set, begin_ns, end_ns);
+                let x = time_to_x(time, begin_ns, end_ns, width);
+                let y = value_to_y(value, max_measure, height, value_range);
+
+                let color = get_color(index);
+                self.ctx.set_fill_style(color);
+
+                self.ctx.begin_path();
+                if let Err(err) = self.ctx.arc(x, y, 2.0, 0.0, 2.0 * PI) {
+                    error!("arc drawing error: {err:?}");
+                }
+                self.ctx.fill();
+
+                self.ctx.set_fill_style("white");
+            }
+        }
+    }
+}
+
+/// Draws an horizontal time axis at `y`, with regularly spaced and labelled ticks below it.
+pub fn render_time_axis(
+    ctx: &mut impl DrawTarget,
+    width: f64,
+    y: f64,
+    begin: DateTime<Utc>,
+    end: DateTime<Utc>,
+    time_zone: LabelTimeZone,
+) {
+    let Some(begin_ns) = begin.timestamp_nanos_opt() else {
+        error!(
+            begin = begin.to_rfc3339(),
+            "conversion nanoseconds overflow"
+        );
+        return;
+    };
+    let Some(end_ns) = end.timestamp_nanos_opt() else {
+        error!(end = end.to_rfc3339(), "conversion nanoseconds overflow");
+        return;
+    };
+
+    ctx.begin_path();
+    ctx.move_to(0.0, y);
+    ctx.line_to(width, y);
+    ctx.set_stroke_style("white");
+    ctx.stroke();
+
+    let duration = end - begin;
+
+    #[expect(clippy::cast_possible_truncation)]
+    let scales = ((width / 204.0) as i32).max(1);
+
+    let interval = duration / scales;
+
+    let truncated_begin_time = match begin.duration_trunc(duration / scales) {
+        Ok(truncated_begin_time) => truncated_begin_time,
+        Err(err) => {
+            error!(
+                begin = begin.to_rfc3339(),
+                %duration,
+                scales,
+                "date time duration trunc error: {err}"
+            );
+            return;
         }
+    };
+
+    let mut time = truncated_begin_time + duration / scales;
+    for _ in 0..scales {
+        let Some(time_ns) = time.timestamp_nanos_opt() else {
+            error!(time = time.to_rfc3339(), "conversion nanoseconds overflow");
+            continue;
+        };
+        let x = time_to_x(time_ns, begin_ns, end_ns, width);
+
+        ctx.begin_path();
+        ctx.move_to(x, y);
+        ctx.line_to(x, y + SCALE_PADDING);
+        ctx.set_stroke_style("white");
+        ctx.stroke();
+
+        if let Err(err) = ctx.fill_text(&time_zone.format(time), x - 90.0, y + SCALE_PADDING * 2.0)
+        {
+            error!("fill text error: {err:?}");
+        }
+
+        time += interval;
     }
 }
 
@@ -430,7 +568,7 @@ fn compute_segment_index(begin: DateTime<Utc>, end: DateTime<Utc>, lod: u32) ->
 }
 
 #[expect(clippy::cast_precision_loss)]
-fn time_to_x(time: i64, begin_ns: i64, end_ns: i64, width: f64) -> f64 {
+pub fn time_to_x(time: i64, begin_ns: i64, end_ns: i64, width: f64) -> f64 {
     let rev_factor = 1.0 / (end_ns - begin_ns) as f64;
     let delta = (time - begin_ns) as f64;
     rev_factor * delta * width
@@ -442,69 +580,201 @@ pub fn x_to_time(x: f64, begin_ns: i64, end_ns: i64, width: f64) -> i64 {
     begin_ns + (rev_factor * x * (end_ns - begin_ns) as f64) as i64
 }
 
-fn value_to_y(value: f64, max_value: f64, height: f64) -> f64 {
-    let rev_factor = 1.0 / max_value;
+fn value_to_y(value: f64, max_value: f64, height: f64, (low, high): (f64, f64)) -> f64 {
+    let fraction = (value / max_value - low) / (high - low);
     let y_margin = y_margin(height);
-    height - rev_factor * value * (height - y_margin) - y_margin
+    height - fraction * (height - y_margin) - y_margin
+}
+
+/// The fraction of the maximum of each series displayed at `y`, the inverse of `value_to_y`.
+pub fn y_to_value_fraction(y: f64, height: f64, (low, high): (f64, f64)) -> f64 {
+    let y_margin = y_margin(height);
+    low + (height - y_margin - y) / (height - y_margin) * (high - low)
 }
 
 fn y_margin(height: f64) -> f64 {
     height / 10.0
 }
 
+/// The color of the series at `index`, in the order of `sorted_by_target`.
 pub fn get_color(index: usize) -> &'static str {
     COLORS[index % COLORS.len()]
 }
 
+/// The measure sets ordered by target, which gives each series a stable color.
+pub fn sorted_by_target(measures: &MeasuresData) -> Vec<(&String, &MeasureSet)> {
+    let mut measures = measures.iter().collect::<Vec<_>>();
+    measures.sort_by_key(|(target, _measure_set)| *target);
+    measures
+}
+
+/// The maximum of the values displayed between `begin_ns` and `end_ns`, or of all the values if
+/// none are displayed.
 pub fn get_max_measure_value(measure_set: &MeasureSet, begin_ns: i64, end_ns: i64) -> f64 {
-    let mut displayed_values = Vec::with_capacity(4 * 1024);
+    visible_slice(&measure_set.measures, begin_ns, end_ns)
+        .iter()
+        .map(|(_time, value)| *value)
+        .max_by(f64::total_cmp)
+        .unwrap_or(measure_set.max)
+}
 
-    for (index, (time, value)) in measure_set.measures.iter().enumerate() {
-        let mut displayed = *time > begin_ns && *time < end_ns;
+/// The first measure at or after `time`.
+pub fn find_closest_measure(measures: &[(i64, f64)], time: i64) -> Option<(i64, f64)> {
+    measures.get(lower_bound(measures, time)).copied()
+}
 
-        if let Some((time, _value)) = measure_set.measures.get(index - 1) {
-            displayed |= *time > begin_ns && *time < end_ns;
-        }
+/// Returns the target, time and value of the measure drawn closest to the provided position.
+///
+/// Only measures closer than `PICK_DISTANCE` pixels are considered.
+pub fn find_measure_at(
+    measures: &MeasuresData,
+    x: f64,
+    y: f64,
+    view: &ChartView,
+) -> Option<(String, i64, f64)> {
+    let begin_ns = view.begin.timestamp_nanos_opt()?;
+    let end_ns = view.end.timestamp_nanos_opt()?;
+    let time = x_to_time(x, begin_ns, end_ns, view.width);
 
-        if let Some((time, _value)) = measure_set.measures.get(index + 1) {
-            displayed |= *time > begin_ns && *time < end_ns;
-        }
+    measures
+        .iter()
+        .filter_map(|(target, measure_set)| {
+            let max_measure = get_max_measure_value(measure_set, begin_ns, end_ns);
+            let (measure_time, value) = find_closest_measure(&measure_set.measures, time)?;
+            let distance = (time_to_x(measure_time, begin_ns, end_ns, view.width) - x)
+                .hypot(value_to_y(value, max_measure, view.height, view.value_range) - y);
 
-        if !displayed {
-            continue;
+            (distance < PICK_DISTANCE).then(|| (distance, (target.clone(), measure_time, value)))
+        })
+        .min_by(|(distance1, _), (distance2, _)| distance1.total_cmp(distance2))
+        .map(|(_distance, measure)| measure)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use crate::draw_target::Recording;
+    use crate::measures::export::chart_svg;
+
+    use super::*;
+
+    fn view(cursor: Option<(f64, f64)>) -> ChartView {
+        let begin = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        ChartView {
+            begin,
+            end: begin + Duration::minutes(10),
+            width: 800.0,
+            height: 400.0,
+            dpr: 2.0,
+            value_range: FULL_VALUE_RANGE,
+            cursor,
+            time_zone: LabelTimeZone::Utc,
         }
+    }
 
-        displayed_values.push(*value);
+    /// Two series with a sample on each side of the view.
+    fn measures() -> MeasuresData {
+        let begin_ns = view(None).begin.timestamp_nanos_opt().unwrap();
+        let minute_ns = Duration::minutes(1).num_nanoseconds().unwrap();
+
+        let measure_set = |unit: &str, values: &[f64]| {
+            // Every 3 minutes, from 2 minutes before the view
+            let measures = (-2..)
+                .step_by(3)
+                .zip(values)
+                .map(|(minutes, value)| (begin_ns + minutes * minute_ns, *value))
+                .collect::<Vec<_>>();
+            MeasureSet {
+                unit: unit.to_string(),
+                min: values.iter().copied().fold(f64::INFINITY, f64::min),
+                max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                start: measures[0].0,
+                end: measures[measures.len() - 1].0,
+                measures,
+            }
+        };
+
+        MeasuresData::from([
+            (
+                "memory".to_string(),
+                measure_set("bytes", &[10.0, 40.0, 20.0, 30.0, 60.0]),
+            ),
+            (
+                "cpu".to_string(),
+                measure_set("percent", &[5.0, 50.0, 25.0, 100.0, 75.0]),
+            ),
+        ])
     }
 
-    find_max_measure_value(&displayed_values).unwrap_or(measure_set.max)
-}
+    fn render_measures(style: MeasuresStyle) -> Recording {
+        let mut canvas = MeasuresCanvas::new(Recording::default())
+            .with_force_debug()
+            .with_style(style);
+        canvas.render(&measures(), &view(None));
+        canvas.ctx
+    }
 
-pub fn find_max_measure_value(measures: &[f64]) -> Option<f64> {
-    measures
-        .iter()
-        .max_by(|value1, value2| value1.total_cmp(value2))
-        .copied()
-}
+    fn render_overlay(cursor: Option<(f64, f64)>) -> Recording {
+        let mut overlay = MeasuresOverlay::new(Recording::default());
+        overlay.render(&measures(), &view(cursor));
+        overlay.ctx
+    }
 
-pub fn find_closest_measure(measures: &[(i64, f64)], mouse_x_time: i64) -> Option<(i64, f64)> {
-    let res = binary_search_by_with_index(measures, |index, (time, _value)| {
-        if let Some((prev_time, _)) = measures.get(index - 1) {
-            if *time > mouse_x_time && *prev_time < mouse_x_time {
-                return Ordering::Equal;
-            }
-        }
+    #[test]
+    fn time_axis() {
+        let view = view(None);
+        let mut recording = Recording::default();
+        render_time_axis(
+            &mut recording,
+            view.width,
+            360.0,
+            view.begin,
+            view.end,
+            LabelTimeZone::Utc,
+        );
+        insta::assert_snapshot!(recording);
+    }
 
-        if *time < mouse_x_time {
-            Ordering::Less
-        } else {
-            Ordering::Greater
-        }
-    });
+    #[test]
+    fn lines() {
+        insta::assert_snapshot!(render_measures(MeasuresStyle::Lines));
+    }
 
-    let index = match res {
-        Ok(x) | Err(x) => x,
-    };
+    #[test]
+    fn scatter() {
+        insta::assert_snapshot!(render_measures(MeasuresStyle::Scatter));
+    }
 
-    measures.get(index).copied()
+    #[test]
+    fn svg() {
+        insta::assert_snapshot!(chart_svg(
+            &measures(),
+            &view(Some((400.0, 100.0))),
+            MeasuresStyle::Scatter
+        ));
+    }
+
+    #[test]
+    fn overlay_with_cursor() {
+        insta::assert_snapshot!(render_overlay(Some((400.0, 100.0))));
+    }
+
+    #[test]
+    fn overlay_without_cursor() {
+        insta::assert_snapshot!(render_overlay(None));
+    }
+
+    #[test]
+    fn empty_view() {
+        let mut canvas = MeasuresCanvas::new(Recording::default());
+        canvas.render(
+            &measures(),
+            &ChartView {
+                width: 0.0,
+                ..view(None)
+            },
+        );
+        assert!(canvas.ctx.commands.is_empty());
+    }
 }
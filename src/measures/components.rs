@@ -1,52 +1,200 @@
 use core::f64;
-use std::collections::HashMap;
 
 use chrono::{DateTime, Utc};
-use ev::{MouseEvent, WheelEvent};
+use ev::{KeyboardEvent, MouseEvent, PointerEvent, WheelEvent};
 use leptos::html::Article;
 use leptos::html::Canvas as CanvasNode;
 use leptos::{
-    component, create_memo, create_node_ref, ev, view, For, IntoView, NodeRef, Signal, SignalGet,
-    SignalSet, SignalUpdate,
+    component, create_memo, create_node_ref, create_signal, ev, event_target_value, store_value,
+    view, Callable, Callback, For, IntoView, NodeRef, Signal, SignalGet, SignalSet, SignalWith,
 };
 use tracing::error;
 
-use crate::components::Spinner;
+use crate::components::{Spinner, TimeSelectionOverlay};
 use crate::datetime::display_datetime;
+use crate::use_time_selection::{use_time_selection, UseTimeSelectionReturn};
 
-use super::canvas::{find_closest_measure, get_color, x_to_time};
-use super::create_measures::{create_measures, CreateMeasuresReturn};
-use super::types::MeasureSet;
+use super::canvas::{
+    find_measure_at, get_color, x_to_time, y_to_value_fraction, ChartView, MeasuresStyle,
+};
+use super::create_measures::{
+    create_measures, create_process_measures, CreateMeasuresReturn, CreateNavigationReturn,
+    CreateProcessMeasuresReturn, CreateZoomReturn,
+};
+use super::export::{export_png, export_svg};
+use super::touch_gesture::TouchGesture;
+use super::types::ChartMeasures;
+use super::viewport::Viewport;
+use super::zoom_history::Zoom;
+
+/// Maximum distance (in pixels) the cursor can travel between a press and a release to be
+/// considered as a click.
+const CLICK_DISTANCE: f64 = 3.0;
+
+/// The zoom factor per pixel scrolled, about 10% per notch of a mouse wheel.
+const WHEEL_ZOOM_BASE: f64 = 1.001;
+
+/// The pixels scrolled per line, for the wheel events counted in lines.
+const WHEEL_LINE_HEIGHT: f64 = 16.0;
+
+/// The part of the chart's width panned by the arrow keys.
+const KEYBOARD_PAN_STEP: f64 = 0.1;
+
+/// The zoom factor of the +/- keys.
+const KEYBOARD_ZOOM_FACTOR: f64 = 1.25;
+
+const DEFAULT_PNG_WIDTH: u32 = 3840;
+
+/// Larger canvases can't be drawn by most browsers.
+const MAX_PNG_WIDTH: u32 = 16_384;
+
+/// A rectangle selected by shift-dragging, from `anchor` to the cursor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ZoomBox {
+    anchor: (f64, f64),
+    /// Whether the value range is zoomed on too, otherwise the box spans the whole height
+    with_values: bool,
+}
 
-// TODO: Group by topic instead of a big struct
 #[component]
 pub fn Measures() -> impl IntoView {
+    let CreateProcessMeasuresReturn {
+        process_id,
+        is_loading,
+        measures,
+        range,
+    } = create_process_measures();
+
+    view! { <MeasuresChart measures range is_loading process_id /> }
+}
+
+/// A pannable and zoomable chart of the provided measures.
+///
+/// Time selection is only enabled when a `process_id` is provided, and clicking a measure
+/// calls `on_measure_click` with its target and time.
+// TODO: Group by topic instead of a big struct
+#[expect(clippy::too_many_lines)]
+#[component]
+pub fn MeasuresChart(
+    measures: Signal<Option<ChartMeasures>>,
+    range: Signal<Option<(DateTime<Utc>, DateTime<Utc>)>>,
+    is_loading: Signal<bool>,
+    #[prop(optional, into)] process_id: Option<Signal<String>>,
+    #[prop(optional)] style: MeasuresStyle,
+    #[prop(optional, into)] on_measure_click: Option<Callback<(String, i64)>>,
+) -> impl IntoView {
     let CreateMeasuresReturn {
         canvas_node_ref,
+        overlay_node_ref,
 
         set_dragging,
         set_mouse_x,
         set_mouse_y,
-        set_begin,
-        set_end,
         set_canvas_has_focus,
 
-        is_loading,
         is_dragging,
         canvas_width,
         mouse_x,
         mouse_y,
         window_width,
-        duration,
         begin,
         end,
         measures,
         measures_targets,
         canvas_has_focus,
         canvas_height,
-        canvas_actual_width,
-        canvas_actual_height,
-    } = create_measures();
+        view,
+        navigation,
+        zoom,
+    } = create_measures(measures, range, style);
+    let CreateNavigationReturn {
+        pan_by,
+        zoom_by,
+        jump_to_start,
+        jump_to_now,
+    } = navigation;
+    let CreateZoomReturn { zoom_to, reset, .. } = zoom;
+
+    let (mousedown_position, set_mousedown_position) = create_signal::<Option<(f64, f64)>>(None);
+    let (zoom_box, set_zoom_box) = create_signal::<Option<ZoomBox>>(None);
+    let touch_gesture = store_value(TouchGesture::default());
+
+    let UseTimeSelectionReturn {
+        selection,
+        is_selecting,
+        start_selection,
+        update_selection,
+        finish_selection,
+        set_raw_selection,
+    } = use_time_selection();
+
+    let mouse_time = move || {
+        Some(x_to_time(
+            mouse_x.get(),
+            begin.get().timestamp_nanos_opt()?,
+            end.get().timestamp_nanos_opt()?,
+            canvas_width.get(),
+        ))
+    };
+
+    let handle_mousedown = move |evt: MouseEvent| {
+        set_mousedown_position.set(Some((mouse_x.get(), mouse_y.get())));
+
+        if evt.shift_key() {
+            set_zoom_box.set(Some(ZoomBox {
+                anchor: (mouse_x.get(), mouse_y.get()),
+                with_values: evt.ctrl_key(),
+            }));
+        } else if evt.alt_key() && process_id.is_some() {
+            if let Some(time) = mouse_time() {
+                start_selection.call(time);
+            }
+        } else {
+            set_dragging.set(true);
+        }
+    };
+
+    let handle_mouseup = move |_evt: MouseEvent| {
+        set_dragging.set(false);
+        finish_selection.call(());
+
+        if let Some(zoom_box) = zoom_box.get() {
+            set_zoom_box.set(None);
+            set_mousedown_position.set(None);
+
+            if let Some(zoom) = box_zoom(zoom_box, (mouse_x.get(), mouse_y.get()), &view.get()) {
+                zoom_to.call(zoom);
+            }
+            return;
+        }
+
+        let Some((mousedown_x, mousedown_y)) = mousedown_position.get() else {
+            return;
+        };
+        set_mousedown_position.set(None);
+
+        let Some(on_measure_click) = on_measure_click else {
+            return;
+        };
+
+        // Only a click if the cursor barely moved since the button was pressed
+        if (mouse_x.get() - mousedown_x).hypot(mouse_y.get() - mousedown_y) > CLICK_DISTANCE {
+            return;
+        }
+
+        let measure = measures.with(|measures| {
+            find_measure_at(
+                &measures.as_ref()?.decoded(),
+                mouse_x.get(),
+                mouse_y.get(),
+                &view.get(),
+            )
+        });
+
+        if let Some((target, time, _value)) = measure {
+            on_measure_click.call((target, time));
+        }
+    };
 
     let handle_mousemove = move |evt: MouseEvent| {
         let Some(canvas_node) = canvas_node_ref.get() else {
@@ -58,17 +206,22 @@ pub fn Measures() -> impl IntoView {
         set_mouse_x.set(f64::from(evt.client_x()) - dom_rect.left());
         set_mouse_y.set(f64::from(evt.client_y()) - dom_rect.top());
 
-        if !is_dragging.get() {
+        if is_selecting.get() {
+            if let Some(time) = mouse_time() {
+                update_selection.call(time);
+            }
             return;
         }
 
-        let factor = canvas_width.get() / f64::from(evt.movement_x());
-        // TODO: Keep an eye on this cast
-        #[expect(clippy::cast_possible_truncation)]
-        let delta = duration.get() / factor as i32;
+        if zoom_box.with(Option::is_some) {
+            return;
+        }
 
-        set_begin.update(|begin| *begin -= delta);
-        set_end.update(|end| *end -= delta);
+        if !is_dragging.get() {
+            return;
+        }
+
+        pan_by.call(f64::from(evt.movement_x()));
     };
 
     let handle_mouseenter = move |_evt: MouseEvent| {
@@ -79,65 +232,362 @@ pub fn Measures() -> impl IntoView {
         set_canvas_has_focus.set(false);
     };
 
+    let canvas_position = move |client_x: i32, client_y: i32| {
+        let dom_rect = canvas_node_ref.get()?.get_bounding_client_rect();
+        Some((
+            f64::from(client_x) - dom_rect.left(),
+            f64::from(client_y) - dom_rect.top(),
+        ))
+    };
+
+    // Touchpads scroll horizontally when panning with two fingers, and pinching scrolls with the
+    // control key pressed
     let handle_wheel = move |evt: WheelEvent| {
-        let mut duration = duration.get() / 1000;
-        if evt.delta_y() < 0.0 {
-            duration = -duration;
+        // The cursor may not have moved over the canvas yet
+        let Some((x, _y)) = canvas_position(evt.client_x(), evt.client_y()) else {
+            return;
+        };
+
+        if evt.ctrl_key() {
+            // Otherwise the whole page is zoomed
+            evt.prevent_default();
+        }
+
+        let (delta_x, delta_y) = wheel_delta(&evt, canvas_height.get());
+        if delta_x.abs() > delta_y.abs() {
+            pan_by.call(-delta_x);
+        } else {
+            zoom_by.call((WHEEL_ZOOM_BASE.powf(delta_y), x));
+        }
+    };
+
+    let handle_keydown = move |evt: KeyboardEvent| {
+        // Left to the browser, e.g. to zoom the page
+        if evt.ctrl_key() || evt.meta_key() || evt.alt_key() {
+            return;
+        }
+
+        let width = canvas_width.get();
+        match evt.key().as_str() {
+            "ArrowLeft" => pan_by.call(width * KEYBOARD_PAN_STEP),
+            "ArrowRight" => pan_by.call(-width * KEYBOARD_PAN_STEP),
+            "+" | "=" => zoom_by.call((1.0 / KEYBOARD_ZOOM_FACTOR, width / 2.0)),
+            "-" | "_" => zoom_by.call((KEYBOARD_ZOOM_FACTOR, width / 2.0)),
+            "Home" => jump_to_start.call(()),
+            "End" => jump_to_now.call(()),
+            "Escape" => {
+                set_zoom_box.set(None);
+                reset.call(());
+            }
+            _ => return,
         }
 
-        let rev_factor = 1.0 / canvas_width.get();
-        // TODO: Keep an eye on this cast
-        #[expect(clippy::cast_possible_truncation)]
-        let x_delta = ((rev_factor * f64::from(evt.x())) * 100.0) as i32;
+        // Otherwise the page scrolls
+        evt.prevent_default();
+    };
 
-        set_begin.update(|begin| *begin -= duration * x_delta);
-        set_end.update(|end| *end += duration * (100 - x_delta));
+    // Only the touch pointers, the mouse being handled by the mouse events
+    let handle_pointerdown = move |evt: PointerEvent| {
+        if evt.pointer_type() != "touch" {
+            return;
+        }
+        let Some(position) = canvas_position(evt.client_x(), evt.client_y()) else {
+            return;
+        };
+
+        touch_gesture.update_value(|touch_gesture| touch_gesture.press(evt.pointer_id(), position));
+        if touch_gesture.with_value(TouchGesture::is_active) {
+            set_dragging.set(false);
+            set_zoom_box.set(None);
+        }
+    };
+
+    let handle_pointermove = move |evt: PointerEvent| {
+        if evt.pointer_type() != "touch" {
+            return;
+        }
+        let Some(position) = canvas_position(evt.client_x(), evt.client_y()) else {
+            return;
+        };
+
+        let change = touch_gesture
+            .try_update_value(|touch_gesture| {
+                touch_gesture.move_pointer(evt.pointer_id(), position)
+            })
+            .flatten();
+        if let Some(change) = change {
+            pan_by.call(change.pan_dx);
+            zoom_by.call((change.zoom_factor, change.anchor_x));
+        }
+    };
+
+    let handle_pointerup = move |evt: PointerEvent| {
+        touch_gesture.update_value(|touch_gesture| touch_gesture.release(evt.pointer_id()));
     };
 
     view! {
-        <div
-            class="w-full h-full relative"
-            on:mousedown=move |_| set_dragging.set(true)
-            on:mouseup=move |_| set_dragging.set(false)
-            on:mousemove=handle_mousemove
-            on:mouseenter=handle_mouseenter
-            on:mouseleave=handle_mouseleave
-            on:wheel=handle_wheel
-        >
+        <div class="w-full h-full flex flex-col gap-2">
+            <div class="flex gap-2 items-center justify-between">
+                <ZoomButtons zoom />
+                <span class="text-sm">
+                    "Drag to pan, scroll to zoom, shift + drag to zoom in, ctrl + shift + drag to zoom in on values too, once focused arrows to pan, +/- to zoom, Home/End to go to the start/now and Esc to reset"
+                </span>
+                <ExportChartButtons measures view style />
+            </div>
             <div
-                class="w-full h-full flex justify-center items-center"
-                class:hidden=move || !is_loading.get()
+                class="w-full h-full relative"
+                // Focusable for the keyboard shortcuts, and the touch gestures aren't handled by
+                // the browser
+                tabindex="0"
+                style:touch-action="none"
+                on:keydown=handle_keydown
+                on:pointerdown=handle_pointerdown
+                on:pointermove=handle_pointermove
+                on:pointerup=handle_pointerup
+                on:pointercancel=handle_pointerup
+                on:mousedown=handle_mousedown
+                on:mouseup=handle_mouseup
+                on:mousemove=handle_mousemove
+                on:mouseenter=handle_mouseenter
+                on:mouseleave=handle_mouseleave
+                on:wheel=handle_wheel
             >
+                <div
+                    class="w-full h-full flex justify-center items-center"
+                    class:hidden=move || !is_loading.get()
+                >
+
+                    // canvas.get().is_some() && measures.get().is_some()
+                    <Spinner />
+                </div>
+
+                // <MetricsDrowpdown measures_targets=measures_targets></MetricsDrowpdown>
+
+                <Tooltip
+                    mouse_x
+                    mouse_y
+                    window_width
+                    canvas_height
+                    canvas_width
+                    begin
+                    end
+                    measures
+                    canvas_has_focus
+                    measures_targets
+                ></Tooltip>
+
+                {process_id
+                    .map(|process_id| {
+                        view! {
+                            <TimeSelectionOverlay
+                                process_id
+                                selection
+                                begin
+                                end
+                                canvas_width
+                                on_clear=move |()| set_raw_selection.set(None)
+                            />
+                        }
+                    })}
+
+                {move || {
+                zoom_box
+                    .get()
+                    .map(|zoom_box| {
+                        let (left, top, width, height) = zoom_box_rect(
+                            zoom_box,
+                            (mouse_x.get(), mouse_y.get()),
+                            canvas_height.get(),
+                        );
+                        view! {
+                            <div
+                                class="absolute bg-sky-400/20 border border-sky-400 pointer-events-none"
+                                style:left=format!("{left}px")
+                                style:top=format!("{top}px")
+                                style:width=format!("{width}px")
+                                style:height=format!("{height}px")
+                            />
+                        }
+                    })
+            }}
 
-                // canvas.get().is_some() && measures.get().is_some()
-                <Spinner />
+            <Canvas canvas_node_ref overlay_node_ref is_loading></Canvas>
             </div>
+        </div>
+    }
+}
+
+/// Exports the chart as displayed, without the cursor, as an SVG document or as a PNG image of
+/// the chosen width.
+#[component]
+fn ExportChartButtons(
+    measures: Signal<Option<ChartMeasures>>,
+    view: Signal<ChartView>,
+    style: MeasuresStyle,
+) -> impl IntoView {
+    let (png_width, set_png_width) = create_signal(DEFAULT_PNG_WIDTH);
+
+    let handle_svg_click = move |_evt: MouseEvent| {
+        measures.with(|measures| {
+            let Some(measures) = measures else {
+                return;
+            };
+
+            if let Err(err) = export_svg(&measures.decoded(), &view.get(), style) {
+                error!("svg export error: {err}");
+            }
+        });
+    };
+
+    let handle_png_click = move |_evt: MouseEvent| {
+        measures.with(|measures| {
+            let Some(measures) = measures else {
+                return;
+            };
 
-            // <MetricsDrowpdown measures_targets=measures_targets></MetricsDrowpdown>
-
-            <Tooltip
-                mouse_x
-                mouse_y
-                window_width
-                canvas_height
-                canvas_width
-                begin
-                end
-                measures
-                canvas_has_focus
-                measures_targets
-            ></Tooltip>
-
-            <Canvas
-                canvas_node_ref
-                actual_width=canvas_actual_width
-                actual_height=canvas_actual_height
-                is_loading
-            ></Canvas>
+            if let Err(err) = export_png(&measures.decoded(), &view.get(), style, png_width.get()) {
+                error!("png export error: {err}");
+            }
+        });
+    };
+
+    let handle_png_width_change = move |evt: ev::Event| {
+        if let Ok(width) = event_target_value(&evt).parse::<u32>() {
+            set_png_width.set(width.clamp(1, MAX_PNG_WIDTH));
+        }
+    };
+
+    view! {
+        <div class="flex gap-2 items-center justify-end">
+            <button
+                class="outline"
+                disabled=move || measures.with(Option::is_none)
+                on:click=handle_svg_click
+            >
+                "Export SVG"
+            </button>
+            <label class="flex gap-2 items-center">
+                "PNG width"
+                <input
+                    type="number"
+                    class="w-32"
+                    min="1"
+                    max=MAX_PNG_WIDTH
+                    prop:value=move || png_width.get()
+                    on:change=handle_png_width_change
+                />
+            </label>
+            <button
+                class="outline"
+                disabled=move || measures.with(Option::is_none)
+                on:click=handle_png_click
+            >
+                "Export PNG"
+            </button>
+        </div>
+    }
+}
+
+/// Goes back and forward in the zoom history, or zooms on the whole measures.
+#[component]
+fn ZoomButtons(zoom: CreateZoomReturn) -> impl IntoView {
+    let CreateZoomReturn {
+        zoom_back,
+        zoom_forward,
+        fit_to_data,
+        can_zoom_back,
+        can_zoom_forward,
+        ..
+    } = zoom;
+
+    view! {
+        <div class="flex gap-2">
+            <button
+                class="outline"
+                title="Previous zoom"
+                disabled=move || !can_zoom_back.get()
+                on:click=move |_| zoom_back.call(())
+            >
+                "←"
+            </button>
+            <button
+                class="outline"
+                title="Next zoom"
+                disabled=move || !can_zoom_forward.get()
+                on:click=move |_| zoom_forward.call(())
+            >
+                "→"
+            </button>
+            <button class="outline" on:click=move |_| fit_to_data.call(())>
+                "Fit to data"
+            </button>
         </div>
     }
 }
 
+/// The part of the chart selected by `zoom_box`, `None` if too small to be deliberate.
+fn box_zoom(zoom_box: ZoomBox, (cursor_x, cursor_y): (f64, f64), view: &ChartView) -> Option<Zoom> {
+    let (anchor_x, anchor_y) = zoom_box.anchor;
+    let (left, right) = (anchor_x.min(cursor_x), anchor_x.max(cursor_x));
+    if right - left < CLICK_DISTANCE {
+        return None;
+    }
+
+    let viewport = Viewport::from_range((view.begin, view.end))?;
+    let viewport = Viewport::new(
+        viewport.time_at(left, view.width),
+        viewport.time_at(right, view.width),
+    );
+
+    let (top, bottom) = (anchor_y.min(cursor_y), anchor_y.max(cursor_y));
+    let value_range = if zoom_box.with_values && bottom - top >= CLICK_DISTANCE {
+        (
+            y_to_value_fraction(bottom, view.height, view.value_range),
+            y_to_value_fraction(top, view.height, view.value_range),
+        )
+    } else {
+        view.value_range
+    };
+
+    Some(Zoom {
+        viewport,
+        value_range,
+    })
+}
+
+/// The horizontal and vertical distances scrolled by a wheel event, in pixels.
+fn wheel_delta(evt: &WheelEvent, page_height: f64) -> (f64, f64) {
+    let factor = match evt.delta_mode() {
+        WheelEvent::DOM_DELTA_LINE => WHEEL_LINE_HEIGHT,
+        WheelEvent::DOM_DELTA_PAGE => page_height,
+        _ => 1.0,
+    };
+
+    (evt.delta_x() * factor, evt.delta_y() * factor)
+}
+
+/// The left, top, width and height of the rectangle drawn while selecting `zoom_box`.
+fn zoom_box_rect(
+    zoom_box: ZoomBox,
+    (cursor_x, cursor_y): (f64, f64),
+    height: f64,
+) -> (f64, f64, f64, f64) {
+    let (anchor_x, anchor_y) = zoom_box.anchor;
+    let left = anchor_x.min(cursor_x);
+    let width = (anchor_x - cursor_x).abs();
+
+    if zoom_box.with_values {
+        (
+            left,
+            anchor_y.min(cursor_y),
+            width,
+            (anchor_y - cursor_y).abs(),
+        )
+    } else {
+        (left, 0.0, width, height)
+    }
+}
+
 // TODO: Finish dropdown
 #[component]
 fn MetricsDrowpdown(measures_targets: Signal<Option<Vec<(String, String)>>>) -> impl IntoView {
@@ -163,23 +613,28 @@ fn MetricsDrowpdown(measures_targets: Signal<Option<Vec<(String, String)>>>) ->
     }
 }
 
+/// The size of the canvases' bitmaps is set when rendering, since they can be controlled by a
+/// worker.
 #[component]
 fn Canvas(
     canvas_node_ref: NodeRef<CanvasNode>,
-    actual_width: Signal<f64>,
-    actual_height: Signal<f64>,
+    overlay_node_ref: NodeRef<CanvasNode>,
     is_loading: Signal<bool>,
 ) -> impl IntoView {
     view! {
-        <canvas
-            class="border border-black w-full h-full"
-            class:hidden=move || is_loading.get()
-            node_ref=canvas_node_ref
-            width=actual_width
-            height=actual_height
-            style:width="100%"
-            style:height="600px"
-        />
+        <div class="relative" class:hidden=move || is_loading.get()>
+            <canvas
+                class="border border-black w-full h-full"
+                node_ref=canvas_node_ref
+                style:width="100%"
+                style:height="600px"
+            />
+            // Same border as the chart, so that both are aligned
+            <canvas
+                class="absolute inset-0 w-full h-full border border-transparent pointer-events-none"
+                node_ref=overlay_node_ref
+            />
+        </div>
     }
 }
 
@@ -194,7 +649,7 @@ fn Tooltip(
     begin: Signal<DateTime<Utc>>,
     end: Signal<DateTime<Utc>>,
     canvas_has_focus: Signal<bool>,
-    measures: Signal<Option<HashMap<String, MeasureSet>>>,
+    measures: Signal<Option<ChartMeasures>>,
     measures_targets: Signal<Option<Vec<(String, String)>>>,
 ) -> impl IntoView {
     let tooltip_node_ref = create_node_ref::<Article>();
@@ -270,15 +725,16 @@ fn Tooltip(
                     let target_ = target.clone();
                     let value = create_memo(move |_| {
                         let current_time = current_time.get()?;
-                        let measures = measures.get()?;
-                        let measures_set = measures.get(&target_)?;
                         let Some(current_time) = current_time.timestamp_nanos_opt() else {
                             error!(
                                 current_time=current_time.to_rfc3339(), "conversion to nanoseconds overflow"
                             );
                             return None;
                         };
-                        find_closest_measure(&measures_set.measures, current_time)
+                        measures
+                            .with(|measures| {
+                                measures.as_ref()?.closest_measure(&target_, current_time)
+                            })
                             .map(|(_time, value)| value)
                     });
                     view! {
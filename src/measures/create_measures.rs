@@ -2,17 +2,24 @@ use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use chrono::{DateTime, Duration, Utc};
 use leptos::{
-    create_effect, create_memo, create_signal, html::Canvas, NodeRef, Params, Resource, Signal,
-    SignalGet, SignalSet, SignalWith, WriteSignal,
+    create_effect, create_memo, create_node_ref, create_signal, html::Canvas, store_value,
+    Callback, NodeRef, Params, Signal, SignalGet, SignalSet, SignalUpdate, SignalWith, WriteSignal,
 };
 use leptos_router::{use_params, Params};
 use leptos_use::{use_window_size, UseWindowSizeReturn};
 use tracing::error;
 use uuid::Uuid;
+use web_sys::HtmlCanvasElement;
 
 use crate::{
+    frame_scheduler::FrameScheduler,
+    import::imported_data::use_imported_process,
+    local_query::{create_table_stream_opt, CreateTableStreamReturn},
+    offscreen::OffscreenChart,
+    pipeline::{create_measures_stream, is_pipeline_available, CreateMeasuresStreamReturn},
+    query_builder::{col, Order, Query, Source},
     request::{
-        create_request, create_request_opt, FindProcessRequest, QueryRequest, FIND_PROCESS_URL,
+        create_process_end, create_request_opt, FindProcessRequest, QueryRequest, FIND_PROCESS_URL,
         QUERY_URL,
     },
     types::ProcessInfo,
@@ -20,40 +27,169 @@ use crate::{
 };
 
 use super::{
-    canvas::MeasuresCanvas,
-    types::{Measure, MeasureSet, MeasuresData},
+    canvas::{ChartView, LabelTimeZone, MeasuresStyle, FULL_VALUE_RANGE},
+    layered_chart::LayeredChart,
+    types::{ChartMeasures, Measure, MeasureSet, MeasuresData},
+    viewport::Viewport,
+    zoom_history::{Zoom, ZoomHistory},
 };
 
+/// Draws a measures chart, on the main thread or in the renderer worker.
+enum ChartRenderer {
+    /// The updates made during a frame are rendered at the end of it
+    Page {
+        chart: Rc<RefCell<LayeredChart<HtmlCanvasElement>>>,
+        scheduler: FrameScheduler,
+    },
+    Offscreen(OffscreenChart),
+}
+
 pub struct CreateMeasuresReturn {
     pub canvas_node_ref: NodeRef<Canvas>,
+    /// The canvas stacked over the chart, on which the cursor is drawn
+    pub overlay_node_ref: NodeRef<Canvas>,
 
     pub set_dragging: WriteSignal<bool>,
     pub set_mouse_x: WriteSignal<f64>,
     pub set_mouse_y: WriteSignal<f64>,
-    pub set_begin: WriteSignal<DateTime<Utc>>,
-    pub set_end: WriteSignal<DateTime<Utc>>,
     pub set_canvas_has_focus: WriteSignal<bool>,
 
-    pub is_loading: Signal<bool>,
     pub is_dragging: Signal<bool>,
     pub canvas_width: Signal<f64>,
     pub mouse_x: Signal<f64>,
     pub mouse_y: Signal<f64>,
     pub window_width: Signal<f64>,
-    pub duration: Signal<Duration>,
     pub begin: Signal<DateTime<Utc>>,
     pub end: Signal<DateTime<Utc>>,
-    pub measures: Signal<Option<HashMap<String, MeasureSet>>>,
+    pub measures: Signal<Option<ChartMeasures>>,
     pub measures_targets: Signal<Option<Vec<(String, String)>>>,
     pub canvas_has_focus: Signal<bool>,
     pub canvas_height: Signal<f64>,
-    pub canvas_actual_width: Signal<f64>,
-    pub canvas_actual_height: Signal<f64>,
+    pub view: Signal<ChartView>,
+
+    pub navigation: CreateNavigationReturn,
+    pub zoom: CreateZoomReturn,
+}
+
+#[derive(Clone, Copy)]
+pub struct CreateNavigationReturn {
+    /// Moves the displayed times by the given number of pixels, to the right when positive
+    pub pan_by: Callback<f64>,
+    /// Multiplies the displayed duration by the given factor, the time under the given x staying
+    /// in place
+    pub zoom_by: Callback<(f64, f64)>,
+    /// Moves the displayed times to the start of the process
+    pub jump_to_start: Callback<()>,
+    /// Moves the displayed times to now, or to the end of the process
+    pub jump_to_now: Callback<()>,
+}
+
+#[derive(Clone, Copy)]
+pub struct CreateZoomReturn {
+    /// Zooms on the given part of the chart, the current one being recorded in the history
+    pub zoom_to: Callback<Zoom>,
+    pub zoom_back: Callback<()>,
+    pub zoom_forward: Callback<()>,
+    /// Zooms on the time range covered by the measures
+    pub fit_to_data: Callback<()>,
+    /// Zooms out to the whole range of the chart
+    pub reset: Callback<()>,
+    pub can_zoom_back: Signal<bool>,
+    pub can_zoom_forward: Signal<bool>,
+}
+
+pub struct CreateProcessMeasuresReturn {
+    pub process_id: Signal<String>,
+    pub is_loading: Signal<bool>,
+    pub measures: Signal<Option<ChartMeasures>>,
+    pub range: Signal<Option<(DateTime<Utc>, DateTime<Utc>)>>,
 }
 
-pub fn create_measures() -> CreateMeasuresReturn {
+/// Fetches the measures of the process found in the route parameters.
+///
+/// The imported data of the process, if any, is displayed instead of the analytics server's.
+pub fn create_process_measures() -> CreateProcessMeasuresReturn {
     let id = use_params_id();
+    let imported_process = use_imported_process(move || id.get().to_string());
+
+    let processes = create_request_opt::<_, Vec<ProcessInfo>>(move || {
+        if imported_process.with(Option::is_some) {
+            return None;
+        }
+
+        Some(processes_request(id.get()))
+    });
+
+    let process_end = create_process_end(move || processes.get()?.first().cloned());
+
+    let request = move || {
+        let processes = processes.get()?;
+        let process = processes.first()?;
 
+        Some(measures_request(process, process_end.get()?))
+    };
+
+    // The measures are decoded and grouped by target in the pipeline worker when available, and
+    // kept in the buffers it transferred
+    let table_name = move || measures_table_name(&id.get().to_string());
+    let (server_measures, measures_loading) = if is_pipeline_available() {
+        let CreateMeasuresStreamReturn { series, is_loading } =
+            create_measures_stream(table_name, request);
+        let measures = Signal::derive(move || series.get().map(ChartMeasures::Buffers));
+        (measures, is_loading)
+    } else {
+        let CreateTableStreamReturn { rows, is_loading } =
+            create_table_stream_opt::<Measure>(table_name, "time", request);
+        let measures = create_measures_memo(move || rows.get());
+        let measures = Signal::derive(move || measures.get().map(ChartMeasures::Decoded));
+        (measures, is_loading)
+    };
+
+    let imported_measures = create_measures_memo(move || {
+        imported_process
+            .get()
+            .map(|imported_process| imported_process.measures.clone())
+    });
+    let measures = Signal::derive(move || {
+        imported_measures
+            .get()
+            .map(ChartMeasures::Decoded)
+            .or_else(|| server_measures.get())
+    });
+
+    // The measures are displayed as soon as the first ones are decoded, the progress of the rest
+    // is reported by the progress bar
+    let is_loading = move || {
+        processes.loading().get() || (measures_loading.get() && measures.with(Option::is_none))
+    };
+
+    // The end of a running process is the time its last block was fetched, so the range only
+    // changes when the processes are fetched again, e.g. when navigating to another process
+    let range = create_memo(move |_| {
+        if let Some(imported_process) = imported_process.get() {
+            return Some(imported_process.time_range());
+        }
+
+        let processes = processes.get()?;
+        let process = processes.first()?;
+
+        Some((process.start_time, process_end.get()?))
+    });
+
+    CreateProcessMeasuresReturn {
+        process_id: Signal::derive(move || id.get().to_string()),
+        is_loading: is_loading.into(),
+        measures,
+        range: range.into(),
+    }
+}
+
+/// Creates the state of a measures chart, the visible range is reset every time `range` changes.
+pub fn create_measures(
+    measures: Signal<Option<ChartMeasures>>,
+    range: Signal<Option<(DateTime<Utc>, DateTime<Utc>)>>,
+    style: MeasuresStyle,
+) -> CreateMeasuresReturn {
     let UseWindowSizeReturn {
         width: window_width,
         ..
@@ -64,113 +200,315 @@ pub fn create_measures() -> CreateMeasuresReturn {
         dpr,
         width: canvas_width,
         height: canvas_height,
-        actual_width: canvas_actual_width,
-        actual_height: canvas_actual_height,
+        ..
     } = use_canvas();
+    let overlay_node_ref = create_node_ref::<Canvas>();
 
-    let (canvas, set_canvas) = create_signal::<Option<Rc<RefCell<MeasuresCanvas>>>>(None);
     let (is_dragging, set_dragging) = create_signal(false);
     let (mouse_x, set_mouse_x) = create_signal(0.0);
     let (mouse_y, set_mouse_y) = create_signal(0.0);
 
-    let (begin, set_begin) = create_signal(Utc::now() - Duration::hours(24));
-    let (end, set_end) = create_signal(begin.get() + Duration::hours(25));
-    let duration = move || end.get() - begin.get();
+    let (viewport, set_viewport) = create_signal(initial_viewport());
+    // The process' lifetime, which the viewport can't leave
+    let bounds = create_memo(move |_| range.get().and_then(Viewport::from_range));
+    let begin = create_memo(move |_| viewport.get().begin());
+    let end = create_memo(move |_| viewport.get().end());
+
+    let (value_range, set_value_range) = create_signal(FULL_VALUE_RANGE);
 
     let (canvas_has_focus, set_canvas_has_focus) = create_signal(false);
 
-    let processes = create_request::<_, Vec<ProcessInfo>>(move || processes_request(id.get()));
+    let measures_targets = create_measures_targets_memo(measures);
 
-    let measures_resource = create_request_opt::<_, Option<Vec<Measure>>>(move || {
-        let processes = processes.get()?;
-        let process = processes.first()?;
+    create_effect(move |_| {
+        let Some(bounds) = bounds.get() else {
+            return;
+        };
 
-        Some(measures_request(process))
+        set_viewport.set(bounds.clamp(None));
+        set_value_range.set(FULL_VALUE_RANGE);
     });
 
-    let measures = create_measures_memo(measures_resource);
-    let measures_targets = create_measures_targets_memo(measures);
+    let view = create_memo(move |_| ChartView {
+        begin: begin.get(),
+        end: end.get(),
+        width: canvas_width.get(),
+        height: canvas_height.get(),
+        dpr: dpr.get(),
+        value_range: value_range.get(),
+        cursor: canvas_has_focus
+            .get()
+            .then(|| (mouse_x.get(), mouse_y.get())),
+        time_zone: LabelTimeZone::Local,
+    });
 
-    let is_loading = move || processes.loading().get() || measures_resource.loading().get();
+    create_chart_renderer(
+        canvas_node_ref,
+        overlay_node_ref,
+        style,
+        measures,
+        view.into(),
+    );
 
-    create_effect(move |_| {
-        let Some(processes) = processes.get() else {
+    CreateMeasuresReturn {
+        canvas_node_ref,
+        overlay_node_ref,
+
+        set_dragging,
+        set_mouse_x,
+        set_mouse_y,
+        set_canvas_has_focus,
+
+        is_dragging: is_dragging.into(),
+        canvas_width,
+        mouse_x: mouse_x.into(),
+        mouse_y: mouse_y.into(),
+        window_width,
+        begin: begin.into(),
+        end: end.into(),
+        measures,
+        measures_targets,
+        canvas_has_focus: canvas_has_focus.into(),
+        canvas_height,
+        view: view.into(),
+
+        navigation: create_navigation(set_viewport, bounds.into(), canvas_width),
+        zoom: create_zoom(
+            measures,
+            range,
+            move || Zoom {
+                viewport: viewport.get(),
+                value_range: value_range.get(),
+            },
+            move |zoom: Zoom| {
+                set_viewport.set(zoom.viewport.clamp(bounds.get()));
+                set_value_range.set(zoom.value_range);
+            },
+        ),
+    }
+}
+
+/// Moves the viewport, without leaving `bounds`.
+fn create_navigation(
+    set_viewport: WriteSignal<Viewport>,
+    bounds: Signal<Option<Viewport>>,
+    canvas_width: Signal<f64>,
+) -> CreateNavigationReturn {
+    let pan_by = move |dx: f64| {
+        let width = canvas_width.get();
+        set_viewport.update(|viewport| *viewport = viewport.pan(dx, width, bounds.get()));
+    };
+
+    let zoom_by = move |(factor, x): (f64, f64)| {
+        let width = canvas_width.get();
+        set_viewport.update(|viewport| *viewport = viewport.zoom(factor, x, width, bounds.get()));
+    };
+
+    let jump_to_start = move |()| {
+        let Some(bounds) = bounds.get() else {
             return;
         };
+        set_viewport
+            .update(|viewport| *viewport = viewport.starting_at(bounds.begin_ns(), Some(bounds)));
+    };
 
-        let Some(process) = processes.first() else {
+    let jump_to_now = move |()| {
+        let Some(now) = Utc::now().timestamp_nanos_opt() else {
             return;
         };
+        set_viewport.update(|viewport| *viewport = viewport.ending_at(now, bounds.get()));
+    };
+
+    CreateNavigationReturn {
+        pan_by: pan_by.into(),
+        zoom_by: zoom_by.into(),
+        jump_to_start: jump_to_start.into(),
+        jump_to_now: jump_to_now.into(),
+    }
+}
+
+/// Zooms through the history, which is cleared every time `range` changes.
+///
+/// The changes of the view made by panning aren't recorded.
+fn create_zoom(
+    measures: Signal<Option<ChartMeasures>>,
+    range: Signal<Option<(DateTime<Utc>, DateTime<Utc>)>>,
+    current_zoom: impl Fn() -> Zoom + Copy + 'static,
+    apply_zoom: impl Fn(Zoom) + Copy + 'static,
+) -> CreateZoomReturn {
+    let (history, set_history) = create_signal(ZoomHistory::default());
 
-        set_begin.set(process.start_time);
-        set_end.set(Utc::now());
+    create_effect(move |_| {
+        if range.with(Option::is_some) {
+            set_history.set(ZoomHistory::default());
+        }
     });
 
+    let zoom_to = move |zoom: Zoom| {
+        let current = current_zoom();
+        set_history.update(|history| history.record(current));
+        apply_zoom(zoom);
+    };
+
+    let zoom_back = move |()| {
+        let current = current_zoom();
+        if let Some(zoom) = set_history
+            .try_update(|history| history.back(current))
+            .flatten()
+        {
+            apply_zoom(zoom);
+        }
+    };
+
+    let zoom_forward = move |()| {
+        let current = current_zoom();
+        if let Some(zoom) = set_history
+            .try_update(|history| history.forward(current))
+            .flatten()
+        {
+            apply_zoom(zoom);
+        }
+    };
+
+    let fit_to_data = move |()| {
+        let Some((start, end)) = measures.with(|measures| measures.as_ref()?.time_range()) else {
+            return;
+        };
+        let viewport = Viewport::new(start, end);
+
+        zoom_to(Zoom {
+            viewport,
+            value_range: FULL_VALUE_RANGE,
+        });
+    };
+
+    let reset = move |()| {
+        let Some(viewport) = range.get().and_then(Viewport::from_range) else {
+            return;
+        };
+
+        zoom_to(Zoom {
+            viewport,
+            value_range: FULL_VALUE_RANGE,
+        });
+    };
+
+    CreateZoomReturn {
+        zoom_to: zoom_to.into(),
+        zoom_back: zoom_back.into(),
+        zoom_forward: zoom_forward.into(),
+        fit_to_data: fit_to_data.into(),
+        reset: reset.into(),
+        can_zoom_back: Signal::derive(move || history.with(ZoomHistory::can_go_back)),
+        can_zoom_forward: Signal::derive(move || history.with(ZoomHistory::can_go_forward)),
+    }
+}
+
+/// Displayed until the range of the chart is known.
+fn initial_viewport() -> Viewport {
+    let now = Utc::now();
+    Viewport::from_range((now - Duration::hours(24), now + Duration::hours(1)))
+        .unwrap_or_else(|| Viewport::new(0, 1))
+}
+
+/// Renders the chart every time the measures or the view change, in the renderer worker when
+/// supported.
+fn create_chart_renderer(
+    node_ref: NodeRef<Canvas>,
+    overlay_node_ref: NodeRef<Canvas>,
+    style: MeasuresStyle,
+    measures: Signal<Option<ChartMeasures>>,
+    view: Signal<ChartView>,
+) {
+    let (renderer, set_renderer) = create_signal::<Option<Rc<ChartRenderer>>>(None);
+
     create_effect(move |_| {
-        let Some(node) = canvas_node_ref.get() else {
+        let (Some(node), Some(overlay_node)) = (node_ref.get(), overlay_node_ref.get()) else {
             return;
         };
 
-        let measures_canvas = match MeasuresCanvas::try_new(&node) {
-            Ok(measures_canvas) => measures_canvas,
+        // Rendered by a worker when supported, otherwise on the main thread
+        if let Some(chart) = OffscreenChart::try_attach(&node, &overlay_node, style) {
+            set_renderer.set(Some(Rc::new(ChartRenderer::Offscreen(chart))));
+            return;
+        }
+
+        let chart = match LayeredChart::try_new((*node).clone(), (*overlay_node).clone(), style) {
+            Ok(chart) => Rc::new(RefCell::new(chart)),
             Err(err) => {
                 error!("measures canvas failed to initialize: {err}");
                 return;
             }
         };
 
-        // TODO: Remove force debug when more stable
-        let canvas = Rc::new(RefCell::new(measures_canvas.with_force_debug()));
-        set_canvas.set(Some(canvas));
+        let scheduler = FrameScheduler::new({
+            let chart = Rc::clone(&chart);
+            move || chart.borrow_mut().render()
+        });
+        set_renderer.set(Some(Rc::new(ChartRenderer::Page { chart, scheduler })));
     });
 
-    create_effect(move |_| {
-        canvas_width.track();
-        canvas_height.track();
+    // The request and number of chunks of the pipeline already sent to the renderer worker
+    let sent_chunks = store_value(None::<(u32, usize)>);
 
-        let Some(canvas) = canvas.get() else {
+    // The measures are only copied, or sent to the worker, when they change
+    create_effect(move |_| {
+        let Some(renderer) = renderer.get() else {
             return;
         };
 
-        let measures = measures.get().unwrap_or_default();
-
-        canvas.borrow_mut().render(
-            &measures,
-            begin.get(),
-            end.get(),
-            canvas_width.get(),
-            canvas_height.get(),
-            mouse_x.get(),
-            dpr.get(),
-        );
+        match &*renderer {
+            ChartRenderer::Page { chart, scheduler } => {
+                let measures = measures.with(|measures| {
+                    measures
+                        .as_ref()
+                        .map(|measures| measures.decoded().into_owned())
+                });
+                chart
+                    .borrow_mut()
+                    .set_measures(measures.unwrap_or_default());
+                scheduler.request();
+            }
+            ChartRenderer::Offscreen(chart) => measures.with(|measures| match measures {
+                // Only the new chunks are sent, the worker appending them to the previous ones
+                Some(ChartMeasures::Buffers(series)) => {
+                    let sent = match sent_chunks.get_value() {
+                        Some((request, sent)) if request == series.request => sent,
+                        _ => {
+                            chart.set_measures(&HashMap::new());
+                            0
+                        }
+                    };
+                    for chunk in series.chunks.iter().skip(sent) {
+                        chart.append_measures(chunk);
+                    }
+                    sent_chunks.set_value(Some((series.request, series.chunks.len())));
+                }
+                Some(ChartMeasures::Decoded(measures)) => {
+                    sent_chunks.set_value(None);
+                    chart.set_measures(measures);
+                }
+                None => {
+                    sent_chunks.set_value(None);
+                    chart.set_measures(&HashMap::new());
+                }
+            }),
+        }
     });
 
-    CreateMeasuresReturn {
-        canvas_node_ref,
-
-        set_dragging,
-        set_mouse_x,
-        set_mouse_y,
-        set_begin,
-        set_end,
-        set_canvas_has_focus,
+    create_effect(move |_| {
+        let Some(renderer) = renderer.get() else {
+            return;
+        };
 
-        is_loading: is_loading.into(),
-        is_dragging: is_dragging.into(),
-        canvas_width,
-        mouse_x: mouse_x.into(),
-        mouse_y: mouse_y.into(),
-        window_width,
-        duration: duration.into(),
-        begin: begin.into(),
-        end: end.into(),
-        measures,
-        measures_targets,
-        canvas_has_focus: canvas_has_focus.into(),
-        canvas_height,
-        canvas_actual_width,
-        canvas_actual_height,
-    }
+        match &*renderer {
+            ChartRenderer::Page { chart, scheduler } => {
+                chart.borrow_mut().set_view(view.get());
+                scheduler.request();
+            }
+            ChartRenderer::Offscreen(chart) => chart.set_view(view.get()),
+        }
+    });
 }
 
 #[derive(Params, PartialEq)]
@@ -195,10 +533,10 @@ fn use_params_id() -> Signal<Uuid> {
 // TODO: Use Arrow and replace this function by a proper query in memory
 // Use the same technique for data dissemination (lod)
 fn create_measures_memo(
-    measures: Resource<Option<(String, QueryRequest)>, Option<Vec<Measure>>>,
+    measures: impl Fn() -> Option<Vec<Measure>> + 'static,
 ) -> Signal<Option<HashMap<String, MeasureSet>>> {
     create_memo(move |_| {
-        let measures = measures.get().flatten()?;
+        let measures = measures()?;
 
         let mut measures_data: MeasuresData = HashMap::new();
         for measure in measures {
@@ -237,25 +575,25 @@ fn create_measures_memo(
                 });
         }
 
+        // Imported measures aren't necessarily sorted
+        for measure_set in measures_data.values_mut() {
+            measure_set.measures.sort_by_key(|(time, _value)| *time);
+        }
+
         Some(measures_data)
     })
     .into()
 }
 
 fn create_measures_targets_memo(
-    measures: Signal<Option<HashMap<String, MeasureSet>>>,
+    measures: Signal<Option<ChartMeasures>>,
 ) -> Signal<Option<Vec<(String, String)>>> {
-    create_memo(move |_| {
-        let measures = measures.get()?;
-
-        let measure_targets = measures
-            .iter()
-            .map(|(target, measure_set)| (target.clone(), measure_set.unit.clone()))
-            .collect::<Vec<_>>();
+    create_memo(move |_| measures.with(|measures| Some(measures.as_ref()?.targets()))).into()
+}
 
-        Some(measure_targets)
-    })
-    .into()
+/// The local table holding the measures of a process.
+pub fn measures_table_name(process_id: &str) -> String {
+    format!("measures/{process_id}")
 }
 
 fn processes_request(process_id: Uuid) -> (String, FindProcessRequest) {
@@ -267,19 +605,15 @@ fn processes_request(process_id: Uuid) -> (String, FindProcessRequest) {
     )
 }
 
-fn measures_request(process: &ProcessInfo) -> (String, QueryRequest) {
+fn measures_request(process: &ProcessInfo, end: DateTime<Utc>) -> (String, QueryRequest) {
     let begin = process.start_time;
-    let end = Utc::now();
     let request = QueryRequest {
-        sql: format!(
-            "
-                SELECT target, time, value, unit
-                  FROM measures
-                 WHERE process_id = '{}'
-                 ORDER BY time asc
-            ",
-            process.process_id
-        ),
+        sql: Query::from(Source::Measures)
+            // The process is selected too, for the query to run again over the local table
+            .columns(&["process_id", "target", "time", "value", "unit"])
+            .filter(col("process_id").eq(process.process_id.as_str()))
+            .order_by("time", Order::Asc)
+            .to_string(),
         begin: Some(begin.to_rfc3339()),
         end: Some(end.to_rfc3339()),
     };
@@ -0,0 +1,75 @@
+use anyhow::{anyhow, Result};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::HtmlCanvasElement;
+
+use crate::draw_target::SvgDocument;
+use crate::export::download::{download_file, download_url};
+use crate::use_canvas::BitmapCanvas;
+
+use super::canvas::{export_canvas, ChartView, MeasuresStyle};
+use super::types::MeasuresData;
+
+/// The chart as an SVG document, of the size of `view` in CSS pixels and without the cursor.
+pub fn chart_svg(measures: &MeasuresData, view: &ChartView, style: MeasuresStyle) -> String {
+    let view = ChartView {
+        dpr: 1.0,
+        cursor: None,
+        ..*view
+    };
+
+    let mut chart = export_canvas(SvgDocument::new(view.width, view.height), style);
+    chart.render(measures, &view);
+    chart.into_target().finish()
+}
+
+/// Downloads the chart as an SVG document.
+pub fn export_svg(measures: &MeasuresData, view: &ChartView, style: MeasuresStyle) -> Result<()> {
+    download_file(
+        &file_name(view, "svg"),
+        "image/svg+xml",
+        &JsValue::from_str(&chart_svg(measures, view, style)),
+    )
+}
+
+/// Downloads the chart as a PNG image `width` pixels wide, the layout being the one of `view`
+/// scaled to that width.
+pub fn export_png(
+    measures: &MeasuresData,
+    view: &ChartView,
+    style: MeasuresStyle,
+    width: u32,
+) -> Result<()> {
+    if view.width < f64::EPSILON {
+        return Err(anyhow!("the chart has no width"));
+    }
+
+    let view = ChartView {
+        dpr: f64::from(width) / view.width,
+        cursor: None,
+        ..*view
+    };
+
+    let canvas = leptos::document()
+        .create_element("canvas")
+        .map_err(|err| anyhow!("canvas creation error: {err:?}"))?
+        .dyn_into::<HtmlCanvasElement>()
+        .map_err(|err| anyhow!("canvas dyn conversion error: {err:?}"))?;
+    let (bitmap_width, bitmap_height) = view.bitmap_size();
+    canvas.set_bitmap_size(bitmap_width, bitmap_height);
+
+    let mut chart = export_canvas(canvas.context_2d(false)?, style);
+    chart.render(measures, &view);
+
+    let url = canvas
+        .to_data_url()
+        .map_err(|err| anyhow!("canvas encoding error: {err:?}"))?;
+    download_url(&file_name(&view, "png"), &url)
+}
+
+fn file_name(view: &ChartView, extension: &str) -> String {
+    format!(
+        "measures-{}-{}.{extension}",
+        view.begin.timestamp_nanos_opt().unwrap_or_default(),
+        view.end.timestamp_nanos_opt().unwrap_or_default()
+    )
+}
@@ -0,0 +1,82 @@
+use anyhow::Result;
+
+use crate::use_canvas::BitmapCanvas;
+
+use super::canvas::{chart_canvas, ChartView, MeasuresCanvas, MeasuresOverlay, MeasuresStyle};
+use super::types::{merge_measures, MeasuresData};
+
+/// A measures chart drawn on two stacked canvases, the cursor being drawn on the top one so that
+/// moving it only redraws that layer.
+///
+/// The changes are only drawn by `render`, to be called once per animation frame.
+pub struct LayeredChart<C> {
+    base_canvas: C,
+    overlay_canvas: C,
+    base: MeasuresCanvas,
+    overlay: MeasuresOverlay,
+    measures: MeasuresData,
+    /// Nothing is drawn until the view is known
+    view: Option<ChartView>,
+    base_outdated: bool,
+    overlay_outdated: bool,
+}
+
+impl<C: BitmapCanvas> LayeredChart<C> {
+    pub fn try_new(base_canvas: C, overlay_canvas: C, style: MeasuresStyle) -> Result<Self> {
+        let base = chart_canvas(base_canvas.context_2d(false)?, style);
+        let overlay = MeasuresOverlay::try_new(&overlay_canvas)?;
+
+        Ok(Self {
+            base_canvas,
+            overlay_canvas,
+            base,
+            overlay,
+            measures: MeasuresData::new(),
+            view: None,
+            base_outdated: false,
+            overlay_outdated: false,
+        })
+    }
+
+    pub fn set_measures(&mut self, measures: MeasuresData) {
+        self.measures = measures;
+        self.base_outdated = true;
+        self.overlay_outdated = true;
+    }
+
+    pub fn append_measures(&mut self, chunk: MeasuresData) {
+        merge_measures(&mut self.measures, chunk);
+        self.base_outdated = true;
+        self.overlay_outdated = true;
+    }
+
+    pub fn set_view(&mut self, view: ChartView) {
+        let moves_only_cursor = self
+            .view
+            .is_some_and(|previous| view.moves_only_cursor(&previous));
+
+        self.view = Some(view);
+        self.base_outdated |= !moves_only_cursor;
+        self.overlay_outdated = true;
+    }
+
+    /// Draws the outdated layers.
+    pub fn render(&mut self) {
+        let Some(view) = self.view else {
+            return;
+        };
+
+        let bitmap_size = view.bitmap_size();
+        if self.base_outdated {
+            self.base_canvas.fit_bitmap(bitmap_size);
+            self.base.render(&self.measures, &view);
+            self.base_outdated = false;
+        }
+
+        if self.overlay_outdated {
+            self.overlay_canvas.fit_bitmap(bitmap_size);
+            self.overlay.render(&self.measures, &view);
+            self.overlay_outdated = false;
+        }
+    }
+}
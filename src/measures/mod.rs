@@ -1,6 +1,13 @@
-mod canvas;
+pub mod canvas;
 mod components;
 mod create_measures;
+mod export;
+mod layered_chart;
+mod touch_gesture;
 pub mod types;
+pub mod viewport;
+mod zoom_history;
 
-pub use components::Measures;
+pub use components::{Measures, MeasuresChart};
+pub use create_measures::measures_table_name;
+pub use layered_chart::LayeredChart;
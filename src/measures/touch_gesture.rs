@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+/// How the view changes when the fingers of a gesture move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureChange {
+    /// In pixels, to the right when positive
+    pub pan_dx: f64,
+    /// The factor multiplying the displayed duration, below 1 when the fingers spread
+    pub zoom_factor: f64,
+    /// The x staying under the fingers while zooming
+    pub anchor_x: f64,
+}
+
+/// Tracks the touch pointers over a chart, two fingers panning and pinching it.
+#[derive(Debug, Clone, Default)]
+pub struct TouchGesture {
+    /// The positions of the pointers, by pointer id
+    pointers: HashMap<i32, (f64, f64)>,
+}
+
+impl TouchGesture {
+    pub fn press(&mut self, pointer_id: i32, position: (f64, f64)) {
+        self.pointers.insert(pointer_id, position);
+    }
+
+    pub fn release(&mut self, pointer_id: i32) {
+        self.pointers.remove(&pointer_id);
+    }
+
+    /// Whether two fingers are pinching or panning, in which case the other interactions are
+    /// suspended.
+    pub fn is_active(&self) -> bool {
+        self.pointers.len() == 2
+    }
+
+    /// Moves a pointer, returning the change of the view if two fingers are down.
+    pub fn move_pointer(&mut self, pointer_id: i32, position: (f64, f64)) -> Option<GestureChange> {
+        let previous = self.pointers.get_mut(&pointer_id)?;
+        let before = *previous;
+        *previous = position;
+
+        if !self.is_active() {
+            return None;
+        }
+
+        let (other_x, other_y) = self
+            .pointers
+            .iter()
+            .find_map(|(id, position)| (*id != pointer_id).then_some(*position))?;
+
+        let center_before = f64::midpoint(before.0, other_x);
+        let center_after = f64::midpoint(position.0, other_x);
+        let distance_before = (before.0 - other_x).hypot(before.1 - other_y);
+        let distance_after = (position.0 - other_x).hypot(position.1 - other_y);
+
+        let zoom_factor = if distance_before < f64::EPSILON || distance_after < f64::EPSILON {
+            1.0
+        } else {
+            distance_before / distance_after
+        };
+
+        Some(GestureChange {
+            pan_dx: center_after - center_before,
+            zoom_factor,
+            anchor_x: center_after,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_finger_does_nothing() {
+        let mut gesture = TouchGesture::default();
+        gesture.press(1, (100.0, 100.0));
+
+        assert_eq!(gesture.move_pointer(1, (150.0, 100.0)), None);
+        assert_eq!(gesture.move_pointer(2, (150.0, 100.0)), None);
+    }
+
+    #[test]
+    fn two_fingers_pan_and_pinch() {
+        let mut gesture = TouchGesture::default();
+        gesture.press(1, (100.0, 100.0));
+        gesture.press(2, (200.0, 100.0));
+
+        let pan = gesture.move_pointer(2, (240.0, 100.0)).unwrap();
+        assert!((pan.pan_dx - 20.0).abs() < f64::EPSILON);
+
+        let pinch = gesture.move_pointer(1, (0.0, 100.0)).unwrap();
+        assert!((pinch.zoom_factor - 140.0 / 240.0).abs() < f64::EPSILON);
+        assert!((pinch.anchor_x - 120.0).abs() < f64::EPSILON);
+
+        gesture.release(1);
+        assert!(!gesture.is_active());
+    }
+}
@@ -1,7 +1,12 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use crate::pipeline::SeriesChunks;
+
+use super::canvas::find_closest_measure;
+
 pub type MeasuresData = HashMap<String, MeasureSet>;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -22,8 +27,74 @@ pub struct Measure {
     pub unit: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Stream {
-    pub process_id: String,
-    pub stream_id: String,
+/// The measures displayed by a chart.
+#[derive(Debug, Clone)]
+pub enum ChartMeasures {
+    Decoded(MeasuresData),
+    /// Kept in the buffers transferred by the pipeline worker, and only decoded when all the
+    /// measures are needed on the main thread, e.g. to export the chart
+    Buffers(SeriesChunks),
+}
+
+impl ChartMeasures {
+    /// The target and unit of each series, sorted by target.
+    pub fn targets(&self) -> Vec<(String, String)> {
+        let mut targets = match self {
+            Self::Decoded(measures) => measures
+                .iter()
+                .map(|(target, measure_set)| (target.clone(), measure_set.unit.clone()))
+                .collect(),
+            Self::Buffers(chunks) => chunks.targets(),
+        };
+        targets.sort();
+        targets
+    }
+
+    /// The time range covered by all the series, in nanoseconds.
+    pub fn time_range(&self) -> Option<(i64, i64)> {
+        match self {
+            Self::Decoded(measures) => Some((
+                measures
+                    .values()
+                    .map(|measure_set| measure_set.start)
+                    .min()?,
+                measures.values().map(|measure_set| measure_set.end).max()?,
+            )),
+            Self::Buffers(chunks) => chunks.time_range(),
+        }
+    }
+
+    /// The first measure of `target` at or after `time`.
+    pub fn closest_measure(&self, target: &str, time: i64) -> Option<(i64, f64)> {
+        match self {
+            Self::Decoded(measures) => find_closest_measure(&measures.get(target)?.measures, time),
+            Self::Buffers(chunks) => chunks.closest_measure(target, time),
+        }
+    }
+
+    /// All the measures, decoded if needed.
+    pub fn decoded(&self) -> Cow<'_, MeasuresData> {
+        match self {
+            Self::Decoded(measures) => Cow::Borrowed(measures),
+            Self::Buffers(chunks) => Cow::Owned(chunks.decode()),
+        }
+    }
+}
+
+/// Appends the measures of `chunk` to `measures`, the chunks are expected to be sorted by time.
+pub fn merge_measures(measures: &mut MeasuresData, chunk: MeasuresData) {
+    for (target, chunk_set) in chunk {
+        match measures.get_mut(&target) {
+            Some(measure_set) => {
+                measure_set.min = measure_set.min.min(chunk_set.min);
+                measure_set.max = measure_set.max.max(chunk_set.max);
+                measure_set.start = measure_set.start.min(chunk_set.start);
+                measure_set.end = measure_set.end.max(chunk_set.end);
+                measure_set.measures.extend(chunk_set.measures);
+            }
+            None => {
+                measures.insert(target, chunk_set);
+            }
+        }
+    }
 }
@@ -0,0 +1,200 @@
+use chrono::{DateTime, Utc};
+
+/// The shortest duration displayed, the labels of the time axis being in milliseconds.
+const MIN_DURATION_NS: i64 = 1_000_000;
+
+/// The longest duration displayed when the chart isn't bounded, about a century.
+const MAX_DURATION_NS: i64 = 100 * 365 * 24 * 3600 * 1_000_000_000;
+
+/// The precision of the fractions of durations, which are computed in fixed point so that long
+/// durations keep their nanosecond precision.
+const FRACTION_ONE: i128 = 1 << 32;
+
+/// The time range displayed by a chart, in nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    begin: i64,
+    end: i64,
+}
+
+impl Viewport {
+    /// `end` is moved after `begin` if needed, so that the duration is never zero.
+    pub fn new(begin: i64, end: i64) -> Self {
+        Self {
+            begin,
+            end: end.max(begin.saturating_add(1)),
+        }
+    }
+
+    pub fn from_range((begin, end): (DateTime<Utc>, DateTime<Utc>)) -> Option<Self> {
+        Some(Self::new(
+            begin.timestamp_nanos_opt()?,
+            end.timestamp_nanos_opt()?,
+        ))
+    }
+
+    pub fn begin_ns(&self) -> i64 {
+        self.begin
+    }
+
+    pub fn begin(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_nanos(self.begin)
+    }
+
+    pub fn end(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_nanos(self.end)
+    }
+
+    pub fn duration_ns(&self) -> i64 {
+        self.end - self.begin
+    }
+
+    /// The time displayed `x` pixels from the left of a chart `width` pixels wide.
+    pub fn time_at(&self, x: f64, width: f64) -> i64 {
+        self.begin
+            .saturating_add(multiply(self.duration_ns(), fraction(x, width)))
+    }
+
+    /// Moves the displayed times by `dx` pixels, to the right when positive like when dragging.
+    #[must_use]
+    pub fn pan(self, dx: f64, width: f64, bounds: Option<Self>) -> Self {
+        let delta = multiply(self.duration_ns(), fraction(dx, width));
+        Self::new(
+            self.begin.saturating_sub(delta),
+            self.end.saturating_sub(delta),
+        )
+        .clamp(bounds)
+    }
+
+    /// Multiplies the duration by `factor`, the time under `x` staying under `x`.
+    #[must_use]
+    pub fn zoom(self, factor: f64, x: f64, width: f64, bounds: Option<Self>) -> Self {
+        let anchor = self.time_at(x, width);
+        let duration = multiply(self.duration_ns(), fraction(factor, 1.0))
+            .clamp(MIN_DURATION_NS, max_duration(bounds));
+        let begin = anchor.saturating_sub(multiply(duration, fraction(x, width)));
+
+        Self::new(begin, begin.saturating_add(duration)).clamp(bounds)
+    }
+
+    /// The viewport of the same duration starting at `begin`.
+    #[must_use]
+    pub fn starting_at(self, begin: i64, bounds: Option<Self>) -> Self {
+        Self::new(begin, begin.saturating_add(self.duration_ns())).clamp(bounds)
+    }
+
+    /// The viewport of the same duration ending at `end`.
+    #[must_use]
+    pub fn ending_at(self, end: i64, bounds: Option<Self>) -> Self {
+        Self::new(end.saturating_sub(self.duration_ns()), end).clamp(bounds)
+    }
+
+    /// Limits the duration between the minimum and maximum zoom levels, then moves the viewport
+    /// inside `bounds`, e.g. the lifetime of the process.
+    #[must_use]
+    pub fn clamp(self, bounds: Option<Self>) -> Self {
+        let duration = self
+            .duration_ns()
+            .clamp(MIN_DURATION_NS, max_duration(bounds));
+
+        let begin = match bounds {
+            Some(bounds) => self.begin.clamp(
+                bounds.begin,
+                bounds.end.saturating_sub(duration).max(bounds.begin),
+            ),
+            None => self.begin,
+        };
+
+        Self::new(begin, begin.saturating_add(duration))
+    }
+}
+
+fn max_duration(bounds: Option<Viewport>) -> i64 {
+    bounds
+        .map_or(MAX_DURATION_NS, |bounds| bounds.duration_ns())
+        .max(MIN_DURATION_NS)
+}
+
+/// `numerator / denominator` in fixed point, zero if the denominator is zero.
+#[expect(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn fraction(numerator: f64, denominator: f64) -> i128 {
+    if denominator.abs() < f64::EPSILON {
+        return 0;
+    }
+
+    (numerator / denominator * FRACTION_ONE as f64).round() as i128
+}
+
+/// `duration` multiplied by a fixed point `fraction`, saturated to the range of the timestamps.
+fn multiply(duration: i64, fraction: i128) -> i64 {
+    let product = i128::from(duration).saturating_mul(fraction) / FRACTION_ONE;
+    i64::try_from(product).unwrap_or(if product < 0 { i64::MIN } else { i64::MAX })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_NS: i64 = 3600 * 1_000_000_000;
+
+    /// A year long viewport, too long for its nanoseconds to be represented exactly by floats.
+    fn year() -> Viewport {
+        let begin = 1_700_000_000_123_456_789;
+        Viewport::new(begin, begin + 365 * 24 * HOUR_NS)
+    }
+
+    #[test]
+    fn zoom_keeps_time_under_cursor() {
+        let viewport = year();
+        let anchor = viewport.time_at(300.0, 1000.0);
+
+        let zoomed = viewport.zoom(0.5, 300.0, 1000.0, None);
+        assert_eq!(zoomed.duration_ns(), viewport.duration_ns() / 2);
+        assert!((zoomed.time_at(300.0, 1000.0) - anchor).abs() <= 1);
+
+        let zoomed = zoomed.zoom(2.0, 300.0, 1000.0, None);
+        assert!((zoomed.begin - viewport.begin).abs() <= 1);
+        assert!((zoomed.end - viewport.end).abs() <= 1);
+    }
+
+    #[test]
+    fn zoom_is_clamped() {
+        let viewport = year();
+
+        let zoomed = viewport.zoom(1e-12, 500.0, 1000.0, None);
+        assert_eq!(zoomed.duration_ns(), MIN_DURATION_NS);
+
+        let zoomed = viewport.zoom(1e6, 500.0, 1000.0, Some(viewport));
+        assert_eq!(zoomed, viewport);
+    }
+
+    #[test]
+    fn pan_moves_exactly() {
+        let viewport = year();
+
+        let panned = viewport.pan(250.0, 1000.0, None);
+        assert_eq!(panned.begin, viewport.begin - viewport.duration_ns() / 4);
+        assert_eq!(panned.duration_ns(), viewport.duration_ns());
+        assert_eq!(panned.pan(-250.0, 1000.0, None), viewport);
+    }
+
+    #[test]
+    fn pan_stays_in_bounds() {
+        let bounds = year();
+        let viewport = Viewport::new(bounds.begin, bounds.begin + HOUR_NS);
+
+        let panned = viewport.pan(1000.0, 1000.0, Some(bounds));
+        assert_eq!(panned, viewport);
+
+        let panned = viewport.pan(-1e9, 1000.0, Some(bounds));
+        assert_eq!(panned.end, bounds.end);
+        assert_eq!(panned.duration_ns(), HOUR_NS);
+    }
+
+    #[test]
+    fn empty_chart_does_not_move() {
+        let viewport = year();
+        assert_eq!(viewport.pan(100.0, 0.0, None), viewport);
+        assert_eq!(viewport.time_at(100.0, 0.0), viewport.begin);
+    }
+}
@@ -0,0 +1,54 @@
+use super::viewport::Viewport;
+
+/// Older zooms are forgotten.
+const MAX_ZOOM_HISTORY: usize = 100;
+
+/// The part of a chart zoomed in on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Zoom {
+    pub viewport: Viewport,
+    /// See `ChartView::value_range`
+    pub value_range: (f64, f64),
+}
+
+/// The zooms navigated away from, going back and forward like in a browser's history.
+///
+/// The current zoom isn't stored, since panning changes it without being recorded.
+#[derive(Debug, Clone, Default)]
+pub struct ZoomHistory {
+    back: Vec<Zoom>,
+    forward: Vec<Zoom>,
+}
+
+impl ZoomHistory {
+    /// Records `current` before zooming somewhere else, which forgets the forward zooms.
+    pub fn record(&mut self, current: Zoom) {
+        if self.back.len() == MAX_ZOOM_HISTORY {
+            self.back.remove(0);
+        }
+        self.back.push(current);
+        self.forward.clear();
+    }
+
+    /// The zoom to go back to, `current` becoming the next forward one.
+    pub fn back(&mut self, current: Zoom) -> Option<Zoom> {
+        let previous = self.back.pop()?;
+        self.forward.push(current);
+        Some(previous)
+    }
+
+    /// The zoom to go forward to, `current` becoming the next back one.
+    pub fn forward(&mut self, current: Zoom) -> Option<Zoom> {
+        let next = self.forward.pop()?;
+        self.back.push(current);
+        Some(next)
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+}
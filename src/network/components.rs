@@ -0,0 +1,16 @@
+use leptos::{component, view, IntoView, Show};
+
+use super::status::is_online;
+
+/// Displayed while the browser is offline, the failed requests are performed again once back online.
+#[component]
+pub fn OfflineBanner() -> impl IntoView {
+    view! {
+        <Show when=move || !is_online()>
+            <article class="m-4">
+                <strong>"Offline"</strong>
+                <p>"The data will be reloaded once the connection is back."</p>
+            </article>
+        </Show>
+    }
+}
@@ -0,0 +1,5 @@
+mod components;
+mod status;
+
+pub use components::OfflineBanner;
+pub use status::{is_online, provide_network_status, refetch_on_reconnect, sleep, FailureFlag};
@@ -0,0 +1,90 @@
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use leptos::{
+    create_effect, create_rw_signal, ev, window_event_listener, Resource, RwSignal, SignalGet,
+    SignalSet,
+};
+use tracing::{debug, error};
+use wasm_bindgen::{closure::Closure, prelude::wasm_bindgen, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Window, WorkerGlobalScope};
+
+thread_local! {
+    static ONLINE: Cell<Option<RwSignal<bool>>> = const { Cell::new(None) };
+}
+
+/// Must be called once, from the root of the application, before any request is made.
+pub fn provide_network_status() {
+    let online = create_rw_signal(leptos::window().navigator().on_line());
+    ONLINE.set(Some(online));
+
+    // Listening for the whole lifetime of the application
+    let _ = window_event_listener(ev::online, move |_| online.set(true));
+    let _ = window_event_listener(ev::offline, move |_| online.set(false));
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// Available from both the window and the workers.
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &JsValue, timeout: i32) -> JsValue;
+}
+
+/// Whether the browser is connected to the network, tracked when called in a reactive context.
+pub fn is_online() -> bool {
+    ONLINE
+        .get()
+        .map_or_else(navigator_on_line, |online| online.get())
+}
+
+fn navigator_on_line() -> bool {
+    let global = js_sys::global();
+    if let Some(window) = global.dyn_ref::<Window>() {
+        window.navigator().on_line()
+    } else if let Some(worker) = global.dyn_ref::<WorkerGlobalScope>() {
+        worker.navigator().on_line()
+    } else {
+        true
+    }
+}
+
+/// Whether the last run of a resource failed.
+#[derive(Clone, Default)]
+pub struct FailureFlag(Rc<Cell<bool>>);
+
+impl FailureFlag {
+    pub fn set(&self, failed: bool) {
+        self.0.set(failed);
+    }
+}
+
+/// Re-runs `resource` when the connectivity returns, if its last run failed.
+pub fn refetch_on_reconnect<S, T>(resource: Resource<S, T>, failure: FailureFlag)
+where
+    S: Clone + 'static,
+    T: 'static,
+{
+    create_effect(move |_| {
+        if is_online() && failure.0.get() {
+            debug!("refetching after reconnection");
+            resource.refetch();
+        }
+    });
+}
+
+/// Completes after `duration`.
+pub async fn sleep(duration: Duration) {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let callback = Closure::once_into_js(move || {
+            if let Err(err) = resolve.call0(&JsValue::UNDEFINED) {
+                error!("promise resolution error: {err:?}");
+            }
+        });
+        let timeout = i32::try_from(duration.as_millis()).unwrap_or(i32::MAX);
+        set_timeout(&callback, timeout);
+    });
+
+    if let Err(err) = JsFuture::from(promise).await {
+        error!("sleep error: {err:?}");
+    }
+}
@@ -0,0 +1,153 @@
+use std::cell::RefCell;
+
+use anyhow::{anyhow, Result};
+use js_sys::Reflect;
+use tracing::error;
+use wasm_bindgen::JsValue;
+use web_sys::HtmlCanvasElement;
+
+use crate::measures::canvas::{ChartView, MeasuresStyle};
+use crate::measures::types::MeasuresData;
+use crate::pipeline::{encode_series, SeriesChunk};
+use crate::web_worker::{to_js_message, ModuleWorker, WorkerRole};
+
+use super::messages::RenderRequest;
+
+enum RendererState {
+    NotStarted,
+    Running {
+        worker: ModuleWorker,
+        next_id: u32,
+    },
+    /// The worker couldn't be started, the charts are rendered on the main thread instead
+    Unavailable,
+}
+
+thread_local! {
+    static RENDERER: RefCell<RendererState> = const { RefCell::new(RendererState::NotStarted) };
+}
+
+/// Starts the renderer worker if needed, and allocates an identifier for a new chart.
+fn next_chart_id() -> Option<u32> {
+    RENDERER.with_borrow_mut(|state| {
+        if matches!(state, RendererState::NotStarted) {
+            *state = match ModuleWorker::spawn(
+                WorkerRole::Renderer,
+                |_message| {},
+                || {
+                    // The canvases already transferred can't be rendered by the page anymore
+                    error!("renderer worker failure, rendering the next charts on the main thread");
+                    RENDERER.set(RendererState::Unavailable);
+                },
+            ) {
+                Ok(worker) => RendererState::Running { worker, next_id: 0 },
+                Err(err) => {
+                    error!("renderer worker error: {err}");
+                    RendererState::Unavailable
+                }
+            };
+        }
+
+        let RendererState::Running { next_id, .. } = state else {
+            return None;
+        };
+        let id = *next_id;
+        *next_id += 1;
+        Some(id)
+    })
+}
+
+fn post<T: AsRef<JsValue>>(request: &RenderRequest, transfer: &[T]) -> Result<()> {
+    let (message, transfer) = to_js_message(request, transfer).map_err(|err| anyhow!(err))?;
+
+    RENDERER.with_borrow(|state| {
+        let RendererState::Running { worker, .. } = state else {
+            return Err(anyhow!("renderer worker unavailable"));
+        };
+
+        worker.post(message, transfer)
+    })
+}
+
+/// A measures chart rendered by the renderer worker, on a canvas transferred to it.
+///
+/// The page only sends the measures when they change, and the view on every interaction, so
+/// drawing never blocks the main thread.
+pub struct OffscreenChart {
+    id: u32,
+}
+
+impl OffscreenChart {
+    /// Transfers the control of the base and overlay canvases to the renderer worker.
+    ///
+    /// Returns `None` if offscreen rendering isn't supported, the canvases are then left untouched
+    /// to be rendered on the main thread.
+    pub fn try_attach(
+        base: &HtmlCanvasElement,
+        overlay: &HtmlCanvasElement,
+        style: MeasuresStyle,
+    ) -> Option<Self> {
+        if !Reflect::has(base, &"transferControlToOffscreen".into()).unwrap_or(false) {
+            return None;
+        }
+
+        let id = next_chart_id()?;
+        let canvases = match (
+            base.transfer_control_to_offscreen(),
+            overlay.transfer_control_to_offscreen(),
+        ) {
+            (Ok(base), Ok(overlay)) => [base, overlay],
+            (Err(err), _) | (_, Err(err)) => {
+                error!("canvas transfer error: {err:?}");
+                return None;
+            }
+        };
+
+        let chart = Self { id };
+        chart.post(&RenderRequest::Attach { id, style }, &canvases);
+        Some(chart)
+    }
+
+    pub fn set_measures(&self, measures: &MeasuresData) {
+        let (series, buffers) = encode_series(measures);
+        self.post(
+            &RenderRequest::Measures {
+                id: self.id,
+                series,
+            },
+            &buffers,
+        );
+    }
+
+    /// Sends a copy of the buffers of `chunk`, which the page keeps.
+    pub fn append_measures(&self, chunk: &SeriesChunk) {
+        let buffers = chunk
+            .buffers
+            .iter()
+            .map(|buffer| buffer.slice(0))
+            .collect::<Vec<_>>();
+        self.post(
+            &RenderRequest::AppendMeasures {
+                id: self.id,
+                series: chunk.series.clone(),
+            },
+            &buffers,
+        );
+    }
+
+    pub fn set_view(&self, view: ChartView) {
+        self.post::<JsValue>(&RenderRequest::View { id: self.id, view }, &[]);
+    }
+
+    fn post<T: AsRef<JsValue>>(&self, request: &RenderRequest, transfer: &[T]) {
+        if let Err(err) = post(request, transfer) {
+            error!(id = self.id, "render request error: {err}");
+        }
+    }
+}
+
+impl Drop for OffscreenChart {
+    fn drop(&mut self) {
+        self.post::<JsValue>(&RenderRequest::Detach { id: self.id }, &[]);
+    }
+}
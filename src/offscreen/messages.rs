@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+use crate::measures::canvas::{ChartView, MeasuresStyle};
+use crate::pipeline::SeriesHeader;
+
+/// Sent by the page to the renderer worker, each chart being identified by `id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum RenderRequest {
+    /// The base and overlay canvases are transferred along with the message, in that order
+    Attach {
+        id: u32,
+        style: MeasuresStyle,
+    },
+    /// Replaces the measures of the chart, transferred like the pipeline's chunks
+    Measures {
+        id: u32,
+        series: Vec<SeriesHeader>,
+    },
+    /// Appends measures sorted after the current ones, e.g. a chunk of the pipeline
+    AppendMeasures {
+        id: u32,
+        series: Vec<SeriesHeader>,
+    },
+    View {
+        id: u32,
+        view: ChartView,
+    },
+    Detach {
+        id: u32,
+    },
+}
@@ -0,0 +1,6 @@
+mod client;
+mod messages;
+mod worker;
+
+pub use client::OffscreenChart;
+pub use worker::run_worker;
@@ -0,0 +1,91 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use anyhow::{anyhow, bail, Result};
+use js_sys::ArrayBuffer;
+use tracing::error;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::OffscreenCanvas;
+
+use crate::frame_scheduler::FrameScheduler;
+use crate::measures::LayeredChart;
+use crate::pipeline::decode_series;
+use crate::web_worker::{from_js_message, listen_to_page};
+
+use super::messages::RenderRequest;
+
+thread_local! {
+    static CHARTS: RefCell<HashMap<u32, LayeredChart<OffscreenCanvas>>> =
+        RefCell::new(HashMap::new());
+
+    /// The charts updated during a frame are rendered once, at the end of it
+    static SCHEDULER: FrameScheduler = FrameScheduler::new(|| {
+        CHARTS.with_borrow_mut(|charts| charts.values_mut().for_each(LayeredChart::render));
+    });
+}
+
+/// Renders the charts of the page, for the whole lifetime of the worker.
+pub fn run_worker() {
+    listen_to_page(|message| {
+        if let Err(err) = handle_request(message) {
+            error!("render request error: {err}");
+        }
+    });
+}
+
+fn handle_request(message: &JsValue) -> Result<()> {
+    let (request, transfer) =
+        from_js_message::<RenderRequest, JsValue>(message).map_err(|err| anyhow!(err))?;
+
+    match request {
+        RenderRequest::Attach { id, style } => {
+            let mut canvases = transfer
+                .into_iter()
+                .filter_map(|object| object.dyn_into::<OffscreenCanvas>().ok());
+            let (Some(base_canvas), Some(overlay_canvas)) = (canvases.next(), canvases.next())
+            else {
+                bail!("chart {id} attached without its canvases");
+            };
+
+            let chart = LayeredChart::try_new(base_canvas, overlay_canvas, style)?;
+            CHARTS.with_borrow_mut(|charts| charts.insert(id, chart));
+        }
+        RenderRequest::Measures { id, series } => {
+            let buffers = transfer
+                .into_iter()
+                .filter_map(|object| object.dyn_into::<ArrayBuffer>().ok())
+                .collect::<Vec<_>>();
+            update_chart(id, |chart| {
+                chart.set_measures(decode_series(series, &buffers));
+            })?;
+        }
+        RenderRequest::AppendMeasures { id, series } => {
+            let buffers = transfer
+                .into_iter()
+                .filter_map(|object| object.dyn_into::<ArrayBuffer>().ok())
+                .collect::<Vec<_>>();
+            update_chart(id, |chart| {
+                chart.append_measures(decode_series(series, &buffers));
+            })?;
+        }
+        RenderRequest::View { id, view } => update_chart(id, |chart| chart.set_view(view))?,
+        RenderRequest::Detach { id } => {
+            CHARTS.with_borrow_mut(|charts| charts.remove(&id));
+        }
+    }
+
+    Ok(())
+}
+
+/// Updates the chart, which is rendered on the next frame.
+fn update_chart(id: u32, update: impl FnOnce(&mut LayeredChart<OffscreenCanvas>)) -> Result<()> {
+    CHARTS.with_borrow_mut(|charts| {
+        let chart = charts
+            .get_mut(&id)
+            .ok_or_else(|| anyhow!("unknown chart {id}"))?;
+        update(chart);
+        Result::<_>::Ok(())
+    })?;
+
+    SCHEDULER.with(FrameScheduler::request);
+    Ok(())
+}
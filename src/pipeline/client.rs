@@ -0,0 +1,338 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use anyhow::{anyhow, Result};
+use arrow::array::RecordBatch;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::StreamExt;
+use js_sys::{ArrayBuffer, Uint8Array};
+use leptos::{
+    create_resource, create_rw_signal, store_value, Signal, SignalGet, SignalSet, SignalUpdate,
+};
+use tracing::{debug, error};
+use wasm_bindgen::JsValue;
+
+use crate::in_flight::LatestRequest;
+use crate::inspector::InspectedRequest;
+use crate::network::{refetch_on_reconnect, FailureFlag};
+use crate::request::QueryRequest;
+use crate::settings::{load_credentials, set_auth_required};
+use crate::web_worker::{from_js_message, to_js_message, ModuleWorker, WorkerRole};
+
+use super::messages::{batches_from_ipc, PipelineRequest, PipelineResponse};
+use super::series::{SeriesChunk, SeriesChunks};
+
+type ResponseSender = UnboundedSender<(PipelineResponse, Vec<ArrayBuffer>)>;
+type ResponseReceiver = UnboundedReceiver<(PipelineResponse, Vec<ArrayBuffer>)>;
+
+enum PipelineState {
+    NotStarted,
+    Running(Pipeline),
+    /// The worker couldn't be started, the data is processed on the main thread instead
+    Unavailable,
+}
+
+struct Pipeline {
+    worker: ModuleWorker,
+    next_id: u32,
+    pending: HashMap<u32, ResponseSender>,
+}
+
+thread_local! {
+    static PIPELINE: RefCell<PipelineState> = const { RefCell::new(PipelineState::NotStarted) };
+}
+
+/// Whether the data can be processed by the pipeline worker, starting it if needed.
+pub fn is_pipeline_available() -> bool {
+    PIPELINE.with_borrow_mut(|state| {
+        if matches!(state, PipelineState::NotStarted) {
+            *state = match ModuleWorker::spawn(WorkerRole::Pipeline, dispatch, || {
+                error!("pipeline worker failure, processing the data on the main thread");
+                // Dropping the senders fails the pending requests
+                PIPELINE.set(PipelineState::Unavailable);
+            }) {
+                Ok(worker) => PipelineState::Running(Pipeline {
+                    worker,
+                    next_id: 0,
+                    pending: HashMap::new(),
+                }),
+                Err(err) => {
+                    error!("pipeline worker error: {err}");
+                    PipelineState::Unavailable
+                }
+            };
+        }
+
+        matches!(state, PipelineState::Running(_))
+    })
+}
+
+fn dispatch(message: &JsValue) {
+    let (response, buffers) = match from_js_message::<PipelineResponse, ArrayBuffer>(message) {
+        Ok(response) => response,
+        Err(err) => {
+            error!("pipeline response error: {err}");
+            return;
+        }
+    };
+
+    PIPELINE.with_borrow(|state| {
+        let PipelineState::Running(pipeline) = state else {
+            return;
+        };
+
+        if let Some(sender) = pipeline.pending.get(&response.id()) {
+            // The request may have been dropped in the meantime
+            let _ = sender.unbounded_send((response, buffers));
+        }
+    });
+}
+
+fn post(request: &PipelineRequest) -> Result<()> {
+    let (message, transfer) = to_js_message::<JsValue>(request, &[]).map_err(|err| anyhow!(err))?;
+
+    PIPELINE.with_borrow(|state| {
+        let PipelineState::Running(pipeline) = state else {
+            return Err(anyhow!("pipeline worker unavailable"));
+        };
+
+        pipeline.worker.post(message, transfer)
+    })
+}
+
+/// Removes the request when dropped, and cancels it in the worker if it didn't complete.
+struct PendingRequest {
+    id: u32,
+    completed: bool,
+}
+
+impl Drop for PendingRequest {
+    fn drop(&mut self) {
+        PIPELINE.with_borrow_mut(|state| {
+            if let PipelineState::Running(pipeline) = state {
+                pipeline.pending.remove(&self.id);
+            }
+        });
+
+        if !self.completed {
+            debug!(id = self.id, "cancelling pipeline request");
+            if let Err(err) = post(&PipelineRequest::Cancel { id: self.id }) {
+                error!("{err}");
+            }
+        }
+    }
+}
+
+/// Allocates a request, the responses of the worker being sent to the returned receiver.
+fn start_request() -> Result<(PendingRequest, ResponseReceiver)> {
+    let (sender, receiver) = unbounded();
+    let id = PIPELINE.with_borrow_mut(|state| {
+        let PipelineState::Running(pipeline) = state else {
+            return Err(anyhow!("pipeline worker unavailable"));
+        };
+
+        let id = pipeline.next_id;
+        pipeline.next_id += 1;
+        pipeline.pending.insert(id, sender);
+        Ok(id)
+    })?;
+
+    let pending = PendingRequest {
+        id,
+        completed: false,
+    };
+    Ok((pending, receiver))
+}
+
+/// Fetches measures through the worker, `on_chunk` is called with the measures grouped by target
+/// as they are decoded, and the decoded rows are registered in the worker as the local table
+/// `table`.
+async fn fetch_measures(
+    url: &str,
+    request: &QueryRequest,
+    table: &str,
+    mut on_chunk: impl FnMut(SeriesChunk),
+) -> Result<()> {
+    let inspected = InspectedRequest::start(url, request);
+
+    let result = async {
+        let (mut pending, mut receiver) = start_request()?;
+        post(&PipelineRequest::FetchMeasures {
+            id: pending.id,
+            url: url.to_string(),
+            request: request.clone(),
+            credentials: load_credentials(),
+            table: table.to_string(),
+        })?;
+
+        while let Some((response, buffers)) = receiver.next().await {
+            match response {
+                PipelineResponse::Progress {
+                    received, expected, ..
+                } => inspected.progress().set_received(received, expected),
+                PipelineResponse::Decoded {
+                    rows,
+                    batches,
+                    decode,
+                    ..
+                } => inspected.set_decoded(rows, batches, decode),
+                PipelineResponse::Chunk { series, .. } => on_chunk(SeriesChunk { series, buffers }),
+                PipelineResponse::Done {
+                    source,
+                    bytes,
+                    network,
+                    shaping,
+                    ..
+                } => {
+                    pending.completed = true;
+                    set_auth_required(false);
+                    inspected.set_payload(source, bytes, network);
+                    inspected.set_deserialized(shaping);
+                    return Ok(());
+                }
+                PipelineResponse::Error {
+                    message,
+                    auth_required,
+                    ..
+                } => {
+                    pending.completed = true;
+                    if auth_required {
+                        set_auth_required(true);
+                    }
+                    return Err(anyhow!(message));
+                }
+                PipelineResponse::Table { .. } => {}
+            }
+        }
+
+        pending.completed = true;
+        Err(anyhow!("pipeline worker failure"))
+    }
+    .await;
+
+    inspected.finish(&result);
+    result
+}
+
+/// The result of `sql` over the local table `name` of the worker between `begin` and `end`
+/// (inclusive), or `None` when the range isn't covered or the worker isn't running.
+pub async fn query_local_table(
+    name: &str,
+    sql: &str,
+    begin: i64,
+    end: i64,
+) -> Result<Option<Vec<RecordBatch>>> {
+    // Not started here, since the worker has no tables before it fetches measures
+    if !PIPELINE.with_borrow(|state| matches!(state, PipelineState::Running(_))) {
+        return Ok(None);
+    }
+
+    let (mut pending, mut receiver) = start_request()?;
+    post(&PipelineRequest::QueryTable {
+        id: pending.id,
+        name: name.to_string(),
+        sql: sql.to_string(),
+        begin,
+        end,
+    })?;
+
+    while let Some((response, buffers)) = receiver.next().await {
+        match response {
+            PipelineResponse::Table { covered, .. } => {
+                pending.completed = true;
+                if !covered {
+                    return Ok(None);
+                }
+
+                // An empty table isn't encoded
+                return match buffers.first() {
+                    Some(buffer) => Ok(Some(batches_from_ipc(&Uint8Array::new(buffer).to_vec())?)),
+                    None => Ok(Some(Vec::new())),
+                };
+            }
+            PipelineResponse::Error { message, .. } => {
+                pending.completed = true;
+                return Err(anyhow!(message));
+            }
+            _ => {}
+        }
+    }
+
+    pending.completed = true;
+    Err(anyhow!("pipeline worker failure"))
+}
+
+pub struct CreateMeasuresStreamReturn {
+    /// The chunks received so far, `None` before the first request completes or if it failed
+    pub series: Signal<Option<SeriesChunks>>,
+    pub is_loading: Signal<bool>,
+}
+
+/// Same as `create_request_opt`, but the measures are fetched, decoded and grouped by target in the
+/// pipeline worker, and available as soon as the first ones are decoded.
+///
+/// The previous measures are kept until the first ones of a new request are decoded. The rows are
+/// also registered in the worker as the local table `name`, so that they can be queried again.
+pub fn create_measures_stream(
+    name: impl Fn() -> String + 'static,
+    request: impl Fn() -> Option<(String, QueryRequest)> + 'static,
+) -> CreateMeasuresStreamReturn {
+    let latest_request = LatestRequest::new();
+    let failure = FailureFlag::default();
+    let series = create_rw_signal(None);
+    let requests = store_value(0);
+
+    let resource = create_resource(move || (name(), request()), {
+        let failure = failure.clone();
+        move |(name, request): (String, Option<(String, QueryRequest)>)| {
+            let latest_request = latest_request.clone();
+            let failure = failure.clone();
+            async move {
+                let Some((url, request)) = request else {
+                    failure.set(false);
+                    series.set(None);
+                    return;
+                };
+
+                let id = requests.get_value();
+                requests.set_value(id + 1);
+                let mut is_first_chunk = true;
+                let on_chunk = move |chunk| {
+                    if is_first_chunk {
+                        is_first_chunk = false;
+                        series.set(Some(SeriesChunks {
+                            request: id,
+                            chunks: vec![chunk],
+                        }));
+                    } else {
+                        series.update(|series| {
+                            if let Some(series) = series {
+                                series.chunks.push(chunk);
+                            }
+                        });
+                    }
+                };
+
+                // TODO: Return an error instead of this
+                match latest_request
+                    .run(fetch_measures(&url, &request, &name, on_chunk))
+                    .await
+                {
+                    Some(Ok(())) => failure.set(false),
+                    Some(Err(err)) => {
+                        error!("request error: {err}");
+                        series.set(None);
+                        failure.set(true);
+                    }
+                    None => {}
+                }
+            }
+        }
+    });
+
+    refetch_on_reconnect(resource, failure);
+
+    CreateMeasuresStreamReturn {
+        series: series.into(),
+        is_loading: Signal::derive(move || resource.loading().get()),
+    }
+}
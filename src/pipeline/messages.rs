@@ -0,0 +1,188 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use arrow::array::RecordBatch;
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::StreamWriter;
+use js_sys::{ArrayBuffer, BigInt64Array, Float64Array};
+use serde::{Deserialize, Serialize};
+
+use crate::measures::types::{MeasureSet, MeasuresData};
+use crate::request::{PayloadSource, QueryRequest};
+use crate::settings::Credentials;
+
+/// Sent by the main thread to the worker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum PipelineRequest {
+    /// Fetches measures and groups them by target, the decoded batches being registered as the
+    /// local table `table`
+    FetchMeasures {
+        id: u32,
+        url: String,
+        request: QueryRequest,
+        /// The worker has no access to the settings
+        credentials: Credentials,
+        table: String,
+    },
+    /// Runs `sql` over the rows of a local table between `begin` and `end` (inclusive, in
+    /// nanoseconds)
+    QueryTable {
+        id: u32,
+        name: String,
+        sql: String,
+        begin: i64,
+        end: i64,
+    },
+    Cancel {
+        id: u32,
+    },
+}
+
+/// Sent by the worker to the main thread, all the durations are in milliseconds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum PipelineResponse {
+    Progress {
+        id: u32,
+        received: usize,
+        expected: Option<usize>,
+    },
+    Decoded {
+        id: u32,
+        rows: usize,
+        batches: usize,
+        decode: f64,
+    },
+    /// The measures decoded since the previous chunk, the times and values of each series are
+    /// transferred along with the message, in the same order
+    Chunk { id: u32, series: Vec<SeriesHeader> },
+    Done {
+        id: u32,
+        source: PayloadSource,
+        bytes: usize,
+        network: f64,
+        shaping: f64,
+    },
+    /// The rows of a local table, transferred along with the message as an Arrow IPC stream
+    /// unless the table is empty, or `covered` is false when the range wasn't downloaded
+    Table { id: u32, covered: bool },
+    Error {
+        id: u32,
+        message: String,
+        auth_required: bool,
+    },
+}
+
+impl PipelineResponse {
+    pub fn id(&self) -> u32 {
+        match self {
+            Self::Progress { id, .. }
+            | Self::Decoded { id, .. }
+            | Self::Chunk { id, .. }
+            | Self::Done { id, .. }
+            | Self::Table { id, .. }
+            | Self::Error { id, .. } => *id,
+        }
+    }
+}
+
+/// The measures of a target, without the times and values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeriesHeader {
+    pub target: String,
+    pub unit: String,
+    pub min: f64,
+    pub max: f64,
+    pub start: i64,
+    pub end: i64,
+}
+
+/// Splits the measures into headers and buffers to be transferred, two per series: the times
+/// then the values.
+pub fn encode_series(measures: &MeasuresData) -> (Vec<SeriesHeader>, Vec<ArrayBuffer>) {
+    let mut headers = Vec::with_capacity(measures.len());
+    let mut buffers = Vec::with_capacity(measures.len() * 2);
+    for (target, measure_set) in measures {
+        let (times, values): (Vec<_>, Vec<_>) = measure_set.measures.iter().copied().unzip();
+        headers.push(SeriesHeader {
+            target: target.clone(),
+            unit: measure_set.unit.clone(),
+            min: measure_set.min,
+            max: measure_set.max,
+            start: measure_set.start,
+            end: measure_set.end,
+        });
+        buffers.push(BigInt64Array::from(times.as_slice()).buffer());
+        buffers.push(Float64Array::from(values.as_slice()).buffer());
+    }
+
+    (headers, buffers)
+}
+
+/// Rebuilds the measures from the headers and the buffers of `encode_series`.
+pub fn decode_series(series: Vec<SeriesHeader>, buffers: &[ArrayBuffer]) -> MeasuresData {
+    series
+        .into_iter()
+        .zip(buffers.chunks_exact(2))
+        .map(|(header, buffers)| {
+            let times = BigInt64Array::new(&buffers[0]).to_vec();
+            let values = Float64Array::new(&buffers[1]).to_vec();
+            let measure_set = MeasureSet {
+                measures: times.into_iter().zip(values).collect(),
+                unit: header.unit,
+                min: header.min,
+                max: header.max,
+                start: header.start,
+                end: header.end,
+            };
+            (header.target, measure_set)
+        })
+        .collect()
+}
+
+/// Serializes the batches as an Arrow IPC stream, `None` if there are none.
+pub fn batches_to_ipc(batches: &[RecordBatch]) -> Result<Option<Vec<u8>>> {
+    let Some(first) = batches.first() else {
+        return Ok(None);
+    };
+
+    let mut writer = StreamWriter::try_new(Vec::new(), &first.schema())?;
+    for batch in batches {
+        writer.write(batch)?;
+    }
+
+    Ok(Some(writer.into_inner()?))
+}
+
+/// Reads the batches of an Arrow IPC stream written by `batches_to_ipc`.
+pub fn batches_from_ipc(bytes: &[u8]) -> Result<Vec<RecordBatch>> {
+    Ok(StreamReader::try_new(Cursor::new(bytes), None)?.collect::<Result<_, _>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{Float64Array, StringArray};
+
+    use super::*;
+
+    #[test]
+    fn ipc_round_trip() {
+        assert!(batches_to_ipc(&[]).unwrap().is_none());
+
+        let batch = RecordBatch::try_from_iter([
+            (
+                "target",
+                Arc::new(StringArray::from(vec!["cpu", "memory"])) as _,
+            ),
+            ("value", Arc::new(Float64Array::from(vec![1.5, 2.5])) as _),
+        ])
+        .unwrap();
+        let batches = vec![batch.clone(), batch.slice(1, 1)];
+
+        let bytes = batches_to_ipc(&batches).unwrap().unwrap();
+        assert_eq!(batches_from_ipc(&bytes).unwrap(), batches);
+    }
+}
@@ -0,0 +1,12 @@
+mod client;
+mod messages;
+mod series;
+mod shaping;
+mod worker;
+
+pub use client::{
+    create_measures_stream, is_pipeline_available, query_local_table, CreateMeasuresStreamReturn,
+};
+pub use messages::{decode_series, encode_series, SeriesHeader};
+pub use series::{SeriesChunk, SeriesChunks};
+pub use worker::run_worker;
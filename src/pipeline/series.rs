@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use js_sys::{ArrayBuffer, BigInt64Array, Float64Array};
+
+use crate::measures::types::{merge_measures, MeasuresData};
+
+use super::messages::{decode_series, SeriesHeader};
+
+/// The measures decoded by the pipeline worker since the previous chunk, the times and values of
+/// each series being in the transferred buffers, in the same order.
+#[derive(Debug, Clone)]
+pub struct SeriesChunk {
+    pub series: Vec<SeriesHeader>,
+    pub buffers: Vec<ArrayBuffer>,
+}
+
+impl SeriesChunk {
+    /// The times and values of each series of the chunk.
+    fn columns(&self) -> impl Iterator<Item = (&SeriesHeader, BigInt64Array, Float64Array)> {
+        self.series
+            .iter()
+            .zip(self.buffers.chunks_exact(2))
+            .map(|(header, buffers)| {
+                (
+                    header,
+                    BigInt64Array::new(&buffers[0]),
+                    Float64Array::new(&buffers[1]),
+                )
+            })
+    }
+}
+
+/// The chunks received for a request, in the order they were sent.
+///
+/// The buffers are read in place, so that the main thread never copies the measures unless all of
+/// them are needed.
+#[derive(Debug, Clone)]
+pub struct SeriesChunks {
+    /// Identifies the request, the chunks of a new request replacing the previous ones
+    pub request: u32,
+    pub chunks: Vec<SeriesChunk>,
+}
+
+impl SeriesChunks {
+    /// The target and unit of each series.
+    pub fn targets(&self) -> Vec<(String, String)> {
+        let units = self
+            .chunks
+            .iter()
+            .flat_map(|chunk| &chunk.series)
+            .map(|header| (header.target.as_str(), header.unit.as_str()))
+            .collect::<HashMap<_, _>>();
+
+        units
+            .into_iter()
+            .map(|(target, unit)| (target.to_string(), unit.to_string()))
+            .collect()
+    }
+
+    /// The time range covered by all the series, in nanoseconds.
+    pub fn time_range(&self) -> Option<(i64, i64)> {
+        let headers = || self.chunks.iter().flat_map(|chunk| &chunk.series);
+        Some((
+            headers().map(|header| header.start).min()?,
+            headers().map(|header| header.end).max()?,
+        ))
+    }
+
+    /// The first measure of `target` at or after `time`, found by binary searches in the buffers.
+    pub fn closest_measure(&self, target: &str, time: i64) -> Option<(i64, f64)> {
+        // The first chunk ending after `time` holds the measure, since they are sorted by time
+        let (_header, times, values) = self
+            .chunks
+            .iter()
+            .flat_map(SeriesChunk::columns)
+            .find(|(header, _times, _values)| header.target == target && header.end >= time)?;
+
+        let (mut low, mut high) = (0, times.length());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if times.get_index(middle) < time {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+
+        (low < times.length()).then(|| (times.get_index(low), values.get_index(low)))
+    }
+
+    /// Copies all the measures out of the buffers.
+    pub fn decode(&self) -> MeasuresData {
+        let mut measures = MeasuresData::new();
+        for chunk in &self.chunks {
+            merge_measures(
+                &mut measures,
+                decode_series(chunk.series.clone(), &chunk.buffers),
+            );
+        }
+
+        measures
+    }
+}
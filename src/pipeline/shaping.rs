@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use arrow::array::{Array, AsArray, RecordBatch};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Float64Type, Int64Type};
+use js_sys::{ArrayBuffer, BigInt64Array, Float64Array};
+
+use crate::local_query::time_column_nanos;
+
+use super::messages::SeriesHeader;
+
+/// The measures of a target, stored as columns to be transferred to the main thread.
+struct Series {
+    header: SeriesHeader,
+    times: Vec<i64>,
+    values: Vec<f64>,
+}
+
+/// Groups the measures by target, the measures are expected to be sorted by time.
+#[derive(Default)]
+pub struct MeasuresShaper {
+    series: HashMap<String, Series>,
+}
+
+impl MeasuresShaper {
+    pub fn push(&mut self, batch: &RecordBatch) -> Result<()> {
+        let column = |name: &str, data_type: &DataType| {
+            let column = batch
+                .column_by_name(name)
+                .ok_or_else(|| anyhow!("missing column {name}"))?;
+            cast(column, data_type).map_err(anyhow::Error::from)
+        };
+
+        let targets = column("target", &DataType::Utf8)?;
+        let units = column("unit", &DataType::Utf8)?;
+        let values = column("value", &DataType::Float64)?;
+        let times = time_column_nanos(batch, "time")?;
+
+        let targets = targets.as_string::<i32>();
+        let units = units.as_string::<i32>();
+        let values = values.as_primitive::<Float64Type>();
+        let times = times.as_primitive::<Int64Type>();
+
+        for row in 0..batch.num_rows() {
+            if targets.is_null(row) || times.is_null(row) || values.is_null(row) {
+                continue;
+            }
+            let (target, time, value) = (targets.value(row), times.value(row), values.value(row));
+
+            let series = self
+                .series
+                .entry(target.to_string())
+                .or_insert_with(|| Series {
+                    header: SeriesHeader {
+                        target: target.to_string(),
+                        // Assuming the unit never changes for any given target
+                        unit: units.value(row).to_string(),
+                        min: value,
+                        max: value,
+                        start: time,
+                        end: time,
+                    },
+                    times: Vec::new(),
+                    values: Vec::new(),
+                });
+
+            series.header.min = series.header.min.min(value);
+            series.header.max = series.header.max.max(value);
+            series.header.start = series.header.start.min(time);
+            series.header.end = series.header.end.max(time);
+            series.times.push(time);
+            series.values.push(value);
+        }
+
+        Ok(())
+    }
+
+    /// The series grouped since the previous call, along with their times and values buffers.
+    pub fn take(&mut self) -> (Vec<SeriesHeader>, Vec<ArrayBuffer>) {
+        let mut headers = Vec::new();
+        let mut buffers = Vec::new();
+        for (_target, series) in self.series.drain() {
+            headers.push(series.header);
+            buffers.push(BigInt64Array::from(series.times.as_slice()).buffer());
+            buffers.push(Float64Array::from(series.values.as_slice()).buffer());
+        }
+
+        (headers, buffers)
+    }
+}
@@ -0,0 +1,197 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use anyhow::Result;
+use futures::future::{AbortHandle, Abortable};
+use js_sys::{ArrayBuffer, Uint8Array};
+use tracing::error;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+
+use crate::local_query::{request_range, LocalCatalog};
+use crate::request::{decode_batches_yielding, fetch_cached, is_auth_error, QueryRequest};
+use crate::settings::Credentials;
+use crate::web_worker::{from_js_message, listen_to_page, post_to_page, to_js_message};
+
+use super::messages::{batches_to_ipc, PipelineRequest, PipelineResponse};
+use super::shaping::MeasuresShaper;
+
+/// The measures grouped so far are sent at most this often, in milliseconds.
+const CHUNK_INTERVAL_MS: f64 = 250.0;
+
+thread_local! {
+    static TASKS: RefCell<HashMap<u32, AbortHandle>> = RefCell::new(HashMap::new());
+
+    /// The batches decoded by the worker, which are never sent to the page
+    static CATALOG: RefCell<LocalCatalog> = RefCell::new(LocalCatalog::default());
+}
+
+/// Handles the requests of the page, for the whole lifetime of the worker.
+pub fn run_worker() {
+    listen_to_page(|message| {
+        match from_js_message::<PipelineRequest, JsValue>(message) {
+            Ok((
+                PipelineRequest::FetchMeasures {
+                    id,
+                    url,
+                    request,
+                    credentials,
+                    table,
+                },
+                _transfer,
+            )) => {
+                let (abort_handle, registration) = AbortHandle::new_pair();
+                TASKS.with_borrow_mut(|tasks| tasks.insert(id, abort_handle));
+                spawn_local(async move {
+                    let task = fetch_measures(id, &url, &request, &credentials, &table);
+                    // Cancelled tasks don't report anything
+                    let _ = Abortable::new(task, registration).await;
+                    TASKS.with_borrow_mut(|tasks| tasks.remove(&id));
+                });
+            }
+            Ok((
+                PipelineRequest::QueryTable {
+                    id,
+                    name,
+                    sql,
+                    begin,
+                    end,
+                },
+                _transfer,
+            )) => spawn_local(query_table(id, name, sql, begin, end)),
+            Ok((PipelineRequest::Cancel { id }, _transfer)) => {
+                if let Some(abort_handle) = TASKS.with_borrow_mut(|tasks| tasks.remove(&id)) {
+                    abort_handle.abort();
+                }
+            }
+            Err(err) => error!("pipeline request error: {err}"),
+        }
+    });
+}
+
+fn post(response: &PipelineResponse, buffers: &[ArrayBuffer]) {
+    match to_js_message(response, buffers) {
+        Ok((message, transfer)) => post_to_page(&message, &transfer),
+        Err(err) => error!("pipeline response serialization error: {err}"),
+    }
+}
+
+/// Fetches, decodes and groups the measures by target, sending them in chunks as they are decoded.
+///
+/// The decoded batches are then registered as the local table `table`.
+async fn fetch_measures(
+    id: u32,
+    url: &str,
+    request: &QueryRequest,
+    credentials: &Credentials,
+    table: &str,
+) {
+    let result = async {
+        let (begin, end) = request_range(request)?;
+        let start = js_sys::Date::now();
+        let on_progress = |received, expected| {
+            post(
+                &PipelineResponse::Progress {
+                    id,
+                    received,
+                    expected,
+                },
+                &[],
+            );
+        };
+        let payload = fetch_cached(url, request, credentials, &on_progress).await?;
+        let network = js_sys::Date::now() - start;
+        let (source, bytes) = (payload.source, payload.bytes.len());
+
+        let mut shaper = MeasuresShaper::default();
+        let mut shaping = 0.0;
+        let mut last_chunk = js_sys::Date::now();
+        let on_decoded = |rows, batches, decode| {
+            post(
+                &PipelineResponse::Decoded {
+                    id,
+                    rows,
+                    batches,
+                    decode,
+                },
+                &[],
+            );
+        };
+        let (schema, batches) = decode_batches_yielding(payload.bytes, &on_decoded, |batch| {
+            let start = js_sys::Date::now();
+            shaper.push(batch)?;
+            shaping += js_sys::Date::now() - start;
+
+            if js_sys::Date::now() - last_chunk > CHUNK_INTERVAL_MS {
+                post_chunk(id, &mut shaper);
+                last_chunk = js_sys::Date::now();
+            }
+            Ok(())
+        })
+        .await?;
+        // Sent even if empty, signaling that there are no measures at all
+        post_chunk(id, &mut shaper);
+
+        CATALOG.with_borrow_mut(|catalog| {
+            catalog.register(table, begin, end, "time", schema, batches)
+        })?;
+
+        Result::<_>::Ok(PipelineResponse::Done {
+            id,
+            source,
+            bytes,
+            network,
+            shaping,
+        })
+    }
+    .await;
+
+    match result {
+        Ok(done) => post(&done, &[]),
+        Err(err) => post(
+            &PipelineResponse::Error {
+                id,
+                message: err.to_string(),
+                auth_required: is_auth_error(&err),
+            },
+            &[],
+        ),
+    }
+}
+
+fn post_chunk(id: u32, shaper: &mut MeasuresShaper) {
+    let (series, buffers) = shaper.take();
+    post(&PipelineResponse::Chunk { id, series }, &buffers);
+}
+
+/// Answers a local query of the page, e.g. to export the measures already fetched.
+async fn query_table(id: u32, name: String, sql: String, begin: i64, end: i64) {
+    // The batches are shared by the clones, so that the catalog isn't borrowed across the query
+    let catalog = CATALOG.with_borrow(Clone::clone);
+
+    let result = async {
+        let Some(batches) = catalog.query(&name, &sql, begin, end).await else {
+            return Ok(None);
+        };
+        Result::<_>::Ok(Some(batches_to_ipc(&batches?)?))
+    }
+    .await;
+
+    match result {
+        Ok(None) => post(&PipelineResponse::Table { id, covered: false }, &[]),
+        Ok(Some(bytes)) => {
+            let buffers = bytes
+                .map(|bytes| Uint8Array::from(bytes.as_slice()).buffer())
+                .into_iter()
+                .collect::<Vec<_>>();
+            post(&PipelineResponse::Table { id, covered: true }, &buffers);
+        }
+        Err(err) => post(
+            &PipelineResponse::Error {
+                id,
+                message: err.to_string(),
+                auth_required: false,
+            },
+            &[],
+        ),
+    }
+}
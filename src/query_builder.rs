@@ -0,0 +1,428 @@
+use std::fmt::{self, Display, Formatter};
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// The tables and views exposed by the analytics server.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Processes,
+    Streams,
+    Blocks,
+    LogEntries,
+    Measures,
+    /// The spans of a thread stream
+    ThreadSpans {
+        stream_id: String,
+    },
+    /// The columns of all the tables, from the information schema
+    Columns,
+    /// Any other table, e.g. listed by the schema browser
+    Table {
+        schema: String,
+        name: String,
+    },
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Processes => write!(f, "processes"),
+            Self::Streams => write!(f, "streams"),
+            Self::Blocks => write!(f, "blocks"),
+            Self::LogEntries => write!(f, "log_entries"),
+            Self::Measures => write!(f, "measures"),
+            Self::ThreadSpans { stream_id } => write!(
+                f,
+                "view_instance({}, {})",
+                Literal::from("thread_spans"),
+                Literal::from(stream_id.as_str())
+            ),
+            Self::Columns => write!(f, "information_schema.columns"),
+            Self::Table { schema, name } => {
+                write!(f, "{}.{}", Identifier(schema), Identifier(name))
+            }
+        }
+    }
+}
+
+/// A value escaped as a SQL literal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Text(String),
+    Integer(i64),
+    Timestamp(DateTime<Utc>),
+}
+
+impl Display for Literal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(text) => write!(f, "'{}'", text.replace('\'', "''")),
+            Self::Integer(value) => write!(f, "{value}"),
+            Self::Timestamp(time) => write!(f, "'{}'", time.to_rfc3339()),
+        }
+    }
+}
+
+impl From<&str> for Literal {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<String> for Literal {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<Uuid> for Literal {
+    fn from(id: Uuid) -> Self {
+        Self::Text(id.to_string())
+    }
+}
+
+impl From<i64> for Literal {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<DateTime<Utc>> for Literal {
+    fn from(time: DateTime<Utc>) -> Self {
+        Self::Timestamp(time)
+    }
+}
+
+/// A column, schema or table name, always quoted since some are named after SQL keywords (e.g.
+/// `end`), which makes it case-sensitive.
+struct Identifier<'a>(&'a str);
+
+impl Display for Identifier<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"", self.0.replace('"', "\"\""))
+    }
+}
+
+/// Refers to the column `name`, to build conditions.
+pub fn col(name: &str) -> Column {
+    Column(name.to_string())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column(String);
+
+impl Column {
+    fn compare(self, operator: &'static str, value: impl Into<Literal>) -> Condition {
+        Condition::Compare {
+            column: self.0,
+            operator,
+            value: value.into(),
+        }
+    }
+
+    pub fn eq(self, value: impl Into<Literal>) -> Condition {
+        self.compare("=", value)
+    }
+
+    pub fn ne(self, value: impl Into<Literal>) -> Condition {
+        self.compare("<>", value)
+    }
+
+    pub fn ge(self, value: impl Into<Literal>) -> Condition {
+        self.compare(">=", value)
+    }
+
+    pub fn le(self, value: impl Into<Literal>) -> Condition {
+        self.compare("<=", value)
+    }
+
+    /// The column is equal to one of the values.
+    pub fn in_list<L: Into<Literal>>(self, values: impl IntoIterator<Item = L>) -> Condition {
+        Condition::In {
+            column: self.0,
+            values: values.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// The array column contains the value.
+    pub fn contains(self, value: impl Into<Literal>) -> Condition {
+        Condition::ArrayHas {
+            column: self.0,
+            value: value.into(),
+        }
+    }
+}
+
+/// A predicate of a where clause, the conditions of a query are combined with `AND`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Compare {
+        column: String,
+        operator: &'static str,
+        value: Literal,
+    },
+    In {
+        column: String,
+        values: Vec<Literal>,
+    },
+    ArrayHas {
+        column: String,
+        value: Literal,
+    },
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Compare {
+                column,
+                operator,
+                value,
+            } => write!(f, "{} {operator} {value}", Identifier(column)),
+            // `IN ()` is invalid SQL, and matches nothing anyway
+            Self::In { values, .. } if values.is_empty() => write!(f, "false"),
+            Self::In { column, values } => {
+                write!(f, "{} IN (", Identifier(column))?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, ")")
+            }
+            Self::ArrayHas { column, value } => {
+                write!(f, "array_has({}, {value})", Identifier(column))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum SelectItem {
+    Column(String),
+    /// A constant value, named `alias`
+    Literal {
+        value: Literal,
+        alias: String,
+    },
+}
+
+/// A `SELECT` statement over one of the micromegas views, rendered with `to_string`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    source: Source,
+    /// All the columns when empty
+    columns: Vec<SelectItem>,
+    conditions: Vec<Condition>,
+    order_by: Vec<(String, Order)>,
+    limit: Option<usize>,
+}
+
+impl Query {
+    pub fn from(source: Source) -> Self {
+        Self {
+            source,
+            columns: Vec::new(),
+            conditions: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+        }
+    }
+
+    #[must_use]
+    pub fn columns(mut self, columns: &[&str]) -> Self {
+        self.columns.extend(
+            columns
+                .iter()
+                .map(|column| SelectItem::Column((*column).to_string())),
+        );
+        self
+    }
+
+    /// Selects a constant value for all the rows.
+    #[must_use]
+    pub fn literal(mut self, value: impl Into<Literal>, alias: &str) -> Self {
+        self.columns.push(SelectItem::Literal {
+            value: value.into(),
+            alias: alias.to_string(),
+        });
+        self
+    }
+
+    /// Adds a condition, all the conditions must hold.
+    #[must_use]
+    pub fn filter(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    #[must_use]
+    pub fn order_by(mut self, column: &str, order: Order) -> Self {
+        self.order_by.push((column.to_string(), order));
+        self
+    }
+
+    #[must_use]
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+impl Display for Query {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "SELECT ")?;
+        if self.columns.is_empty() {
+            write!(f, "*")?;
+        }
+        for (index, column) in self.columns.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            match column {
+                SelectItem::Column(name) => write!(f, "{}", Identifier(name))?,
+                SelectItem::Literal { value, alias } => {
+                    write!(f, "{value} AS {}", Identifier(alias))?;
+                }
+            }
+        }
+
+        write!(f, "\n  FROM {}", self.source)?;
+
+        for (index, condition) in self.conditions.iter().enumerate() {
+            let keyword = if index == 0 { " WHERE" } else { "   AND" };
+            write!(f, "\n{keyword} {condition}")?;
+        }
+
+        for (index, (column, order)) in self.order_by.iter().enumerate() {
+            let keyword = if index == 0 { "\n ORDER BY" } else { "," };
+            let order = match order {
+                Order::Asc => "ASC",
+                Order::Desc => "DESC",
+            };
+            write!(f, "{keyword} {} {order}", Identifier(column))?;
+        }
+
+        if let Some(limit) = self.limit {
+            write!(f, "\n LIMIT {limit}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Concatenates the rows of all the queries, which must have the same columns.
+pub fn union_all(queries: &[Query]) -> String {
+    queries
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\nUNION ALL\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_literals_double_their_quotes() {
+        assert_eq!(Literal::from("it's").to_string(), "'it''s'");
+        assert_eq!(Literal::from("''").to_string(), "''''''");
+        assert_eq!(Literal::from(-3).to_string(), "-3");
+
+        let time = DateTime::from_timestamp_nanos(1_500_000_000);
+        assert_eq!(
+            Literal::from(time).to_string(),
+            "'1970-01-01T00:00:01.500+00:00'"
+        );
+    }
+
+    #[test]
+    fn identifiers_double_their_quotes() {
+        assert_eq!(Identifier("end").to_string(), "\"end\"");
+        assert_eq!(Identifier("a\"b").to_string(), "\"a\"\"b\"");
+    }
+
+    #[test]
+    fn conditions() {
+        assert_eq!(col("id").eq("a'b").to_string(), "\"id\" = 'a''b'");
+        assert_eq!(col("n").ne(1).to_string(), "\"n\" <> 1");
+        assert_eq!(
+            col("id").in_list(["a", "b"]).to_string(),
+            "\"id\" IN ('a', 'b')"
+        );
+        assert_eq!(
+            col("tags").contains("cpu").to_string(),
+            "array_has(\"tags\", 'cpu')"
+        );
+    }
+
+    #[test]
+    fn empty_in_lists_match_nothing() {
+        assert_eq!(col("id").in_list(Vec::<String>::new()).to_string(), "false");
+    }
+
+    #[test]
+    fn full_query() {
+        let sql = Query::from(Source::Measures)
+            .literal("m", "kind")
+            .columns(&["time", "value"])
+            .filter(col("process_id").eq("p"))
+            .filter(col("value").ge(0))
+            .order_by("time", Order::Asc)
+            .order_by("value", Order::Desc)
+            .limit(10)
+            .to_string();
+
+        assert_eq!(
+            sql,
+            "SELECT 'm' AS \"kind\", \"time\", \"value\"\n  \
+               FROM measures\n \
+              WHERE \"process_id\" = 'p'\n   \
+                AND \"value\" >= 0\n \
+              ORDER BY \"time\" ASC, \"value\" DESC\n \
+              LIMIT 10"
+        );
+    }
+
+    #[test]
+    fn tables_quote_their_schema_and_name_separately() {
+        let source = Source::Table {
+            schema: "my.schema".to_string(),
+            name: "my\"table".to_string(),
+        };
+        assert_eq!(
+            Query::from(source).to_string(),
+            "SELECT *\n  FROM \"my.schema\".\"my\"\"table\""
+        );
+    }
+
+    #[test]
+    fn thread_spans_view_escapes_the_stream_id() {
+        let source = Source::ThreadSpans {
+            stream_id: "a'b".to_string(),
+        };
+        assert_eq!(source.to_string(), "view_instance('thread_spans', 'a''b')");
+    }
+
+    #[test]
+    fn union_all_concatenates_the_queries() {
+        assert_eq!(union_all(&[]), "");
+        assert_eq!(
+            union_all(&[Query::from(Source::Streams)]),
+            "SELECT *\n  FROM streams"
+        );
+        assert_eq!(
+            union_all(&[Query::from(Source::Streams), Query::from(Source::Blocks)]),
+            "SELECT *\n  FROM streams\nUNION ALL\nSELECT *\n  FROM blocks"
+        );
+    }
+}
@@ -1,11 +1,26 @@
+use std::fmt;
 use std::io::Cursor;
 
 use anyhow::{anyhow, Result};
+use arrow::array::{RecordBatch, RecordBatchReader};
+use arrow::datatypes::SchemaRef;
 use arrow_json::ArrayWriter;
-use leptos::{create_resource, Resource, Serializable};
+use bytes::{Bytes, BytesMut};
+use chrono::{DateTime, Duration, Utc};
+use futures::StreamExt;
+use leptos::{create_memo, create_resource, Memo, Resource, Serializable, SignalGet};
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
-use serde::Serialize;
-use tracing::{debug, error};
+use reqwest::{Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, warn};
+
+use crate::cache;
+use crate::in_flight::{fetch_shared, LatestRequest};
+use crate::inspector::{DownloadProgress, InspectedRequest};
+use crate::network::{is_online, refetch_on_reconnect, sleep, FailureFlag};
+use crate::query_builder::{col, Order, Query, Source};
+use crate::settings::{load_credentials, set_auth_required, Credentials};
+use crate::types::{BlockEnd, ProcessInfo};
 
 pub const FIND_PROCESS_URL: &str = "http://localhost:8082/analytics/find_process";
 pub const QUERY_URL: &str = "http://localhost:8082/analytics/query";
@@ -16,72 +31,505 @@ pub struct FindProcessRequest {
     pub process_id: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct QueryRequest {
     pub begin: Option<String>,
     pub end: Option<String>,
     pub sql: String,
 }
 
+/// Requests failing with a transient error are attempted at most this many times.
+const MAX_ATTEMPTS: u32 = 4;
+/// Delay before the first retry, doubled after each attempt.
+const INITIAL_BACKOFF_MS: f64 = 500.0;
+
+/// The download progress is reported at most this often, in milliseconds.
+const PROGRESS_INTERVAL_MS: f64 = 100.0;
+/// Decoding yields to the event loop at least this often, in milliseconds.
+const YIELD_INTERVAL_MS: f64 = 50.0;
+
+/// Data is considered final once it is older than this delay (in minutes).
+const IMMUTABILITY_DELAY: i64 = 5;
+
+/// Requests whose response never changes can be served from the client cache.
+pub trait CacheableRequest: Serialize {
+    fn is_cacheable(&self) -> bool {
+        false
+    }
+}
+
+impl CacheableRequest for FindProcessRequest {}
+
+impl CacheableRequest for QueryRequest {
+    /// Only the queries whose range ended a few minutes ago.
+    fn is_cacheable(&self) -> bool {
+        let Some(end) = &self.end else {
+            return false;
+        };
+
+        DateTime::parse_from_rfc3339(end)
+            .is_ok_and(|end| end < Utc::now() - Duration::minutes(IMMUTABILITY_DELAY))
+    }
+}
+
+/// The end of the queries over the whole lifetime of a process, given the end of its last block.
+///
+/// Once finished, a process ends with its last block, so that its queries stay identical from one
+/// navigation to the next and are served from the client cache.
+pub fn process_end(last_block_end: Option<DateTime<Utc>>) -> DateTime<Utc> {
+    let now = Utc::now();
+    last_block_end
+        .filter(|last_block_end| *last_block_end < now - Duration::minutes(IMMUTABILITY_DELAY))
+        .unwrap_or(now)
+}
+
+/// Fetches the end of the last block sent by the process.
+fn last_block_request(process: &ProcessInfo) -> (String, QueryRequest) {
+    let request = QueryRequest {
+        sql: Query::from(Source::Blocks)
+            .columns(&["end_time"])
+            .filter(col("process_id").eq(process.process_id.as_str()))
+            .order_by("end_time", Order::Desc)
+            .limit(1)
+            .to_string(),
+        begin: Some(process.start_time.to_rfc3339()),
+        end: Some(Utc::now().to_rfc3339()),
+    };
+
+    (QUERY_URL.to_string(), request)
+}
+
+/// The end of the queries over the whole lifetime of the process, see `process_end`.
+///
+/// `None` until the last block of the process is fetched, the end of a running process being the
+/// time it was fetched at. "Now" is thus only read again when the process changes or the page is
+/// navigated to, so that the charts bounded by the process lifetime don't move while panning.
+pub fn create_process_end(
+    process: impl Fn() -> Option<ProcessInfo> + 'static,
+) -> Memo<Option<DateTime<Utc>>> {
+    let last_block =
+        create_request_opt::<_, Vec<BlockEnd>>(move || Some(last_block_request(&process()?)));
+
+    create_memo(move |_| {
+        let last_block = last_block.get()?;
+        Some(process_end(
+            last_block.first().map(|last_block| last_block.end_time),
+        ))
+    })
+}
+
 pub fn create_request<Request, T>(
     request: impl Fn() -> (String, Request) + 'static,
 ) -> Resource<(String, Request), T>
 where
-    Request: PartialEq + Clone + Serialize + 'static,
+    Request: PartialEq + Clone + CacheableRequest + 'static,
     // TODO: Temporarily handle errors in this function using the default value of the expected type.
     // In the long run this constraint should be lifted.
     T: Serializable + Default + 'static,
 {
-    create_resource(request, move |(url, request)| async move {
-        match perform_request(&url, &request).await {
-            Ok(value) => value,
-            Err(err) => {
-                // TODO: Return an error instead of this
-                error!("request error: {err}");
-                T::default()
+    let latest_request = LatestRequest::new();
+    let failure = FailureFlag::default();
+
+    let resource = create_resource(request, {
+        let failure = failure.clone();
+        move |(url, request)| {
+            let latest_request = latest_request.clone();
+            let failure = failure.clone();
+            async move {
+                match latest_request.run(perform_request(&url, &request)).await {
+                    Some(Ok(value)) => {
+                        failure.set(false);
+                        value
+                    }
+                    Some(Err(err)) => {
+                        // TODO: Return an error instead of this
+                        error!("request error: {err}");
+                        failure.set(true);
+                        T::default()
+                    }
+                    // Superseded, the value is discarded anyway
+                    None => T::default(),
+                }
             }
         }
-    })
+    });
+
+    refetch_on_reconnect(resource, failure);
+    resource
 }
 
 pub fn create_request_opt<Request, T>(
     request: impl Fn() -> Option<(String, Request)> + 'static,
 ) -> Resource<Option<(String, Request)>, T>
 where
-    Request: PartialEq + Clone + Serialize + 'static,
+    Request: PartialEq + Clone + CacheableRequest + 'static,
     T: Serializable + Default + 'static,
 {
-    create_resource(request, move |request| async move {
-        if let Some((url, request)) = request {
-            // TODO: Return an error instead of this
-            match perform_request(&url, &request).await {
-                Ok(value) => value,
-                Err(err) => {
-                    error!("request error: {err}");
+    let latest_request = LatestRequest::new();
+    let failure = FailureFlag::default();
+
+    let resource = create_resource(request, {
+        let failure = failure.clone();
+        move |request| {
+            let latest_request = latest_request.clone();
+            let failure = failure.clone();
+            async move {
+                if let Some((url, request)) = request {
+                    // TODO: Return an error instead of this
+                    match latest_request.run(perform_request(&url, &request)).await {
+                        Some(Ok(value)) => {
+                            failure.set(false);
+                            value
+                        }
+                        Some(Err(err)) => {
+                            error!("request error: {err}");
+                            failure.set(true);
+                            T::default()
+                        }
+                        None => T::default(),
+                    }
+                } else {
+                    failure.set(false);
                     T::default()
                 }
             }
-        } else {
-            T::default()
         }
+    });
+
+    refetch_on_reconnect(resource, failure);
+    resource
+}
+
+/// Where the payload of a response comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PayloadSource {
+    Server {
+        status: u16,
+    },
+    Cache,
+    /// Filtered from the tables already downloaded
+    Local,
+}
+
+#[derive(Debug, Clone)]
+pub struct Payload {
+    pub bytes: Bytes,
+    pub source: PayloadSource,
+}
+
+pub async fn perform_request<R: Serializable>(
+    url: &str,
+    request: &(impl CacheableRequest + Clone + 'static),
+) -> Result<R> {
+    let inspected = InspectedRequest::start(url, request);
+
+    let result = async {
+        let (_schema, batches, _bytes) = fetch_record_batches(url, request, &inspected).await?;
+
+        let start = js_sys::Date::now();
+        let value = deserialize_batches(&batches);
+        inspected.set_deserialized(js_sys::Date::now() - start);
+
+        value
+    }
+    .await;
+
+    inspected.finish(&result);
+    result
+}
+
+/// Fetches and decodes the response, recording the progress and timings in the request inspector.
+///
+/// Returns the record batches along with their schema and the size of the payload.
+pub async fn fetch_record_batches(
+    url: &str,
+    request: &(impl CacheableRequest + Clone + 'static),
+    inspected: &InspectedRequest,
+) -> Result<(SchemaRef, Vec<RecordBatch>, usize)> {
+    fetch_record_batches_with(url, request, inspected, |_batch| Ok(())).await
+}
+
+/// Same as `fetch_record_batches`, but `on_batch` is called as soon as each batch is decoded, once
+/// the whole payload has been received.
+pub async fn fetch_record_batches_with(
+    url: &str,
+    request: &(impl CacheableRequest + Clone + 'static),
+    inspected: &InspectedRequest,
+    on_batch: impl FnMut(&RecordBatch) -> Result<()>,
+) -> Result<(SchemaRef, Vec<RecordBatch>, usize)> {
+    let start = js_sys::Date::now();
+    let payload = fetch_parquet(url, request, inspected.progress()).await?;
+    inspected.set_payload(
+        payload.source,
+        payload.bytes.len(),
+        js_sys::Date::now() - start,
+    );
+
+    let bytes = payload.bytes.len();
+    let (schema, batches) = decode_batches_yielding(
+        payload.bytes,
+        &|rows, batches, decode| inspected.set_decoded(rows, batches, decode),
+        on_batch,
+    )
+    .await?;
+
+    Ok((schema, batches, bytes))
+}
+
+/// Decodes the record batches of a complete payload one at a time, regularly yielding to the event
+/// loop so that the page stays responsive while decoding large payloads.
+///
+/// This doesn't overlap the download: Parquet metadata is written at the end of the file, so
+/// decoding can only start once the whole payload has been received. `on_decoded` is regularly
+/// called with the number of rows and batches decoded so far, and the time spent decoding them in
+/// milliseconds.
+pub async fn decode_batches_yielding(
+    bytes: Bytes,
+    on_decoded: &dyn Fn(usize, usize, f64),
+    mut on_batch: impl FnMut(&RecordBatch) -> Result<()>,
+) -> Result<(SchemaRef, Vec<RecordBatch>)> {
+    let mut reader = ParquetRecordBatchReaderBuilder::try_new(bytes)?.build()?;
+    let schema = reader.schema();
+
+    let mut batches = Vec::new();
+    let mut rows = 0;
+    let mut decode = 0.0;
+    let mut last_yield = js_sys::Date::now();
+    loop {
+        let start = js_sys::Date::now();
+        let Some(batch) = reader.next() else {
+            break;
+        };
+        let batch = batch?;
+        decode += js_sys::Date::now() - start;
+
+        rows += batch.num_rows();
+        on_batch(&batch)?;
+        batches.push(batch);
+
+        if js_sys::Date::now() - last_yield > YIELD_INTERVAL_MS {
+            on_decoded(rows, batches.len(), decode);
+            sleep(std::time::Duration::ZERO).await;
+            last_yield = js_sys::Date::now();
+        }
+    }
+
+    on_decoded(rows, batches.len(), decode);
+
+    Ok((schema, batches))
+}
+
+/// Returns the raw Parquet payload of the response, identical requests in flight are shared.
+///
+/// The download progress is only reported to the request starting the fetch.
+pub async fn fetch_parquet(
+    url: &str,
+    request: &(impl CacheableRequest + Clone + 'static),
+    progress: DownloadProgress,
+) -> Result<Payload> {
+    let key = cache_key(url, request)?;
+    let url = url.to_string();
+    let request = request.clone();
+    let credentials = load_credentials();
+
+    fetch_shared(key, async move {
+        let on_progress = |received, expected| progress.set_received(received, expected);
+        fetch_cached(&url, &request, &credentials, &on_progress).await
     })
+    .await
 }
 
-async fn perform_request<R: Serializable>(url: &str, request: &impl Serialize) -> Result<R> {
+fn cache_key(url: &str, request: &impl Serialize) -> Result<String> {
+    Ok(format!("{url} {}", serde_json::to_string(request)?))
+}
+
+/// Returns the raw Parquet payload of the response, from the client cache when possible.
+///
+/// `on_progress` is regularly called with the bytes received so far, and the expected size of the
+/// response if known.
+pub async fn fetch_cached(
+    url: &str,
+    request: &impl CacheableRequest,
+    credentials: &Credentials,
+    on_progress: &dyn Fn(usize, Option<usize>),
+) -> Result<Payload> {
+    if !request.is_cacheable() {
+        return send_with_retry(url, request, credentials, on_progress).await;
+    }
+
+    let key = cache_key(url, request)?;
+    match cache::get(&key).await {
+        Ok(Some(bytes)) => {
+            return Ok(Payload {
+                bytes,
+                source: PayloadSource::Cache,
+            })
+        }
+        Ok(None) => {}
+        Err(err) => error!("cache read error: {err}"),
+    }
+
+    let payload = send_with_retry(url, request, credentials, on_progress).await?;
+    if let Err(err) = cache::put(&key, &payload.bytes).await {
+        error!("cache write error: {err}");
+    }
+
+    Ok(payload)
+}
+
+/// Retries the transient failures with an exponential backoff, which is safe since all the requests
+/// to the analytics server are read-only.
+async fn send_with_retry(
+    url: &str,
+    request: &impl Serialize,
+    credentials: &Credentials,
+    on_progress: &dyn Fn(usize, Option<usize>),
+) -> Result<Payload> {
+    let mut attempt = 1;
+    loop {
+        match send_request(url, request, credentials, on_progress).await {
+            Err(err) if attempt < MAX_ATTEMPTS && is_transient(&err) && is_online() => {
+                let delay = backoff_delay(attempt);
+                warn!(attempt, "request error, retrying in {delay:?}: {err}");
+                sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether the analytics server rejected the credentials.
+pub fn is_auth_error(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<StatusError>().is_some_and(|err| {
+        err.status == StatusCode::UNAUTHORIZED || err.status == StatusCode::FORBIDDEN
+    })
+}
+
+/// Network errors and server errors which may not happen again.
+fn is_transient(err: &anyhow::Error) -> bool {
+    if let Some(err) = err.downcast_ref::<StatusError>() {
+        err.status.is_server_error()
+            || err.status == StatusCode::TOO_MANY_REQUESTS
+            || err.status == StatusCode::REQUEST_TIMEOUT
+    } else {
+        err.downcast_ref::<reqwest::Error>().is_some()
+    }
+}
+
+/// Doubles after each attempt, with a random jitter so that clients don't retry all at once.
+fn backoff_delay(attempt: u32) -> std::time::Duration {
+    let delay = INITIAL_BACKOFF_MS * 2_f64.powi(i32::try_from(attempt - 1).unwrap_or(i32::MAX));
+    std::time::Duration::from_secs_f64(delay * (0.5 + js_sys::Math::random() / 2.0) / 1_000.0)
+}
+
+/// A response with an unexpected status.
+#[derive(Debug)]
+struct StatusError {
+    status: StatusCode,
+    message: String,
+}
+
+impl fmt::Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "server error ({}): {}", self.status, self.message)
+    }
+}
+
+impl std::error::Error for StatusError {}
+
+async fn send_request(
+    url: &str,
+    request: &impl Serialize,
+    credentials: &Credentials,
+    on_progress: &dyn Fn(usize, Option<usize>),
+) -> Result<Payload> {
     let mut buffer = Vec::new();
     let writer = Cursor::new(&mut buffer);
     ciborium::into_writer(&request, writer)?;
 
-    let client = reqwest::Client::new();
-    let response = client.post(url).body(buffer).send().await?;
-    let bytes = response.bytes().await?;
+    let mut request = reqwest::Client::new().post(url).body(buffer);
+    if !credentials.bearer_token.is_empty() {
+        request = request.bearer_auth(&credentials.bearer_token);
+    }
+    for header in &credentials.headers {
+        request = request.header(&header.name, &header.value);
+    }
+
+    let response = request.send().await?;
+    let status = response.status();
+    if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+        set_auth_required(true);
+        return Err(StatusError {
+            status,
+            message: "authentication required".to_string(),
+        }
+        .into());
+    }
+    if !status.is_success() {
+        let message = response.text().await.unwrap_or_default();
+        return Err(StatusError { status, message }.into());
+    }
+
+    set_auth_required(false);
 
+    Ok(Payload {
+        bytes: receive_body(response, on_progress).await?,
+        source: PayloadSource::Server {
+            status: status.as_u16(),
+        },
+    })
+}
+
+/// Receives the whole response body, reporting the progress as the chunks arrive.
+///
+/// The chunks are only buffered, the Parquet payload can't be decoded before it is complete.
+async fn receive_body(
+    response: Response,
+    on_progress: &dyn Fn(usize, Option<usize>),
+) -> Result<Bytes> {
+    let expected = response
+        .content_length()
+        .and_then(|length| usize::try_from(length).ok());
+    let mut body = BytesMut::with_capacity(expected.unwrap_or_default());
+
+    let mut stream = response.bytes_stream();
+    let mut last_report = js_sys::Date::now();
+    while let Some(chunk) = stream.next().await {
+        body.extend_from_slice(&chunk?);
+
+        if js_sys::Date::now() - last_report > PROGRESS_INTERVAL_MS {
+            on_progress(body.len(), expected);
+            last_report = js_sys::Date::now();
+        }
+    }
+
+    on_progress(body.len(), expected);
+
+    Ok(body.freeze())
+}
+
+/// Decodes a Parquet payload into Arrow record batches, along with their schema.
+pub fn decode_record_batches(bytes: Bytes) -> Result<(SchemaRef, Vec<RecordBatch>)> {
     let reader = ParquetRecordBatchReaderBuilder::try_new(bytes)?.build()?;
+    let schema = reader.schema();
 
     let batches = reader
         .map(|res| res.map_err(Into::into))
         .collect::<Result<Vec<_>>>()?;
 
+    Ok((schema, batches))
+}
+
+/// Decodes a Parquet payload, and deserializes its rows as if they were JSON objects.
+pub fn decode_parquet<R: Serializable>(bytes: Bytes) -> Result<R> {
+    let (_schema, batches) = decode_record_batches(bytes)?;
+
+    deserialize_batches(&batches)
+}
+
+/// Deserializes the rows of record batches as if they were JSON objects.
+pub fn deserialize_batches<R: Serializable>(batches: &[RecordBatch]) -> Result<R> {
     let mut writer = ArrayWriter::new(Vec::new());
     writer.write_batches(&batches.iter().collect::<Vec<_>>())?;
     writer.finish()?;
@@ -92,3 +540,66 @@ async fn perform_request<R: Serializable>(url: &str, request: &impl Serialize) -
 
     R::de(&json).map_err(|err| anyhow!("deserialization error: {err}"))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::TimestampNanosecondArray;
+    use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+
+    use super::*;
+
+    fn request(end: DateTime<Utc>) -> QueryRequest {
+        QueryRequest {
+            begin: Some((end - Duration::hours(1)).to_rfc3339()),
+            end: Some(end.to_rfc3339()),
+            sql: "SELECT 1".to_string(),
+        }
+    }
+
+    #[test]
+    fn finished_processes_end_with_their_last_block() {
+        let last_block_end = Some(Utc::now() - Duration::hours(1));
+
+        assert_eq!(Some(process_end(last_block_end)), last_block_end);
+        assert!(request(process_end(last_block_end)).is_cacheable());
+        // The key of the request doesn't depend on the time it is built at
+        assert_eq!(
+            cache_key(QUERY_URL, &request(process_end(last_block_end))).unwrap(),
+            cache_key(QUERY_URL, &request(process_end(last_block_end))).unwrap(),
+        );
+    }
+
+    #[test]
+    fn block_ends_are_deserialized() {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "end_time",
+            DataType::Timestamp(TimeUnit::Nanosecond, Some("+00:00".into())),
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(
+                TimestampNanosecondArray::from(vec![1_500_000_000]).with_timezone("+00:00"),
+            )],
+        )
+        .unwrap();
+
+        assert_eq!(
+            deserialize_batches::<Vec<BlockEnd>>(&[batch]).unwrap(),
+            [BlockEnd {
+                end_time: DateTime::from_timestamp_nanos(1_500_000_000)
+            }]
+        );
+    }
+
+    #[test]
+    fn running_processes_end_now() {
+        for last_block_end in [None, Some(Utc::now() - Duration::minutes(1))] {
+            let end = process_end(last_block_end);
+            assert!(end > Utc::now() - Duration::minutes(1));
+            assert!(!request(end).is_cacheable());
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use chrono::{Duration, Utc};
+use leptos::{
+    component, create_memo, create_signal, event_target_value, view, Callable, Callback, For,
+    IntoView, SignalGet, SignalSet,
+};
+use leptos_router::A;
+
+use crate::components::Spinner;
+use crate::query_builder::{col, Order, Query, Source};
+use crate::request::{create_request, QueryRequest, QUERY_URL};
+
+use super::types::{group_columns_by_table, ColumnInfo, Table};
+
+/// Lists the tables and views exposed by the analytics server along with their columns.
+#[component]
+pub fn SchemaBrowser(#[prop(into)] on_close: Callback<()>) -> impl IntoView {
+    let columns = create_request::<_, Vec<ColumnInfo>>(columns_request);
+    let tables = create_memo(move |_| group_columns_by_table(columns.get().unwrap_or_default()));
+
+    let (filter, set_filter) = create_signal(String::new());
+    let filtered_tables = move || {
+        let filter = filter.get().to_lowercase();
+        tables
+            .get()
+            .into_iter()
+            .filter(|table| table.qualified_name().to_lowercase().contains(&filter))
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <aside class="fixed top-0 right-0 h-full w-96 p-4 overflow-auto z-10 bg-slate-900 shadow-lg">
+            <div class="flex justify-between items-baseline">
+                <strong>"Schema"</strong>
+                <a
+                    href="#"
+                    on:click=move |evt| {
+                        evt.prevent_default();
+                        on_close.call(());
+                    }
+                >
+                    "Close"
+                </a>
+            </div>
+            <input
+                type="search"
+                placeholder="Filter tables"
+                prop:value=move || filter.get()
+                on:input=move |evt| set_filter.set(event_target_value(&evt))
+            />
+            {move || {
+                if columns.loading().get() {
+                    view! { <Spinner /> }.into_view()
+                } else {
+                    view! {
+                        <For
+                            each=filtered_tables
+                            key=Table::qualified_name
+                            let:table
+                        >
+                            <TableSchema table />
+                        </For>
+                    }
+                        .into_view()
+                }
+            }}
+        </aside>
+    }
+}
+
+#[component]
+fn TableSchema(table: Table) -> impl IntoView {
+    let query_href = format!(
+        "/sql?sql={}",
+        js_sys::encode_uri_component(&template_query(&table))
+    );
+
+    view! {
+        <details>
+            <summary>
+                <span class="font-mono">{table.qualified_name()}</span>
+                " "
+                <A href=query_href class="text-sm">"Query"</A>
+            </summary>
+            <table class="text-sm">
+                <tbody>
+                    {table
+                        .columns
+                        .into_iter()
+                        .map(|column| {
+                            view! {
+                                <tr>
+                                    <td class="font-mono">{column.column_name}</td>
+                                    <td>{column.data_type}</td>
+                                </tr>
+                            }
+                        })
+                        .collect::<Vec<_>>()}
+                </tbody>
+            </table>
+        </details>
+    }
+}
+
+/// Selects all the columns of the table, explicitly to ease editing the query.
+fn template_query(table: &Table) -> String {
+    let columns = table
+        .columns
+        .iter()
+        .map(|column| column.column_name.as_str())
+        .collect::<Vec<_>>();
+
+    Query::from(Source::Table {
+        schema: table.schema.clone(),
+        name: table.name.clone(),
+    })
+    .columns(&columns)
+    .limit(100)
+    .to_string()
+}
+
+fn columns_request() -> (String, QueryRequest) {
+    let end = Utc::now();
+    let begin = end - Duration::days(1);
+    let request = QueryRequest {
+        sql: Query::from(Source::Columns)
+            .columns(&["table_schema", "table_name", "column_name", "data_type"])
+            .filter(col("table_schema").ne("information_schema"))
+            .order_by("table_schema", Order::Asc)
+            .order_by("table_name", Order::Asc)
+            .order_by("ordinal_position", Order::Asc)
+            .to_string(),
+        begin: Some(begin.to_rfc3339()),
+        end: Some(end.to_rfc3339()),
+    };
+
+    (QUERY_URL.to_string(), request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_query_selects_all_the_columns() {
+        let column = |name: &str| ColumnInfo {
+            table_schema: "public".to_string(),
+            table_name: "log".to_string(),
+            column_name: name.to_string(),
+            data_type: "Utf8".to_string(),
+        };
+        let table = Table {
+            schema: "public".to_string(),
+            name: "log".to_string(),
+            columns: vec![column("time"), column("msg")],
+        };
+
+        assert_eq!(
+            template_query(&table),
+            "SELECT \"time\", \"msg\"\n  FROM \"public\".\"log\"\n LIMIT 100"
+        );
+    }
+}
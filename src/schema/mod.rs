@@ -0,0 +1,4 @@
+mod components;
+mod types;
+
+pub use components::SchemaBrowser;
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnInfo {
+    pub table_schema: String,
+    pub table_name: String,
+    pub column_name: String,
+    pub data_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub schema: String,
+    pub name: String,
+    pub columns: Vec<ColumnInfo>,
+}
+
+impl Table {
+    /// The name prefixed by the schema, unique across the schemas.
+    pub fn qualified_name(&self) -> String {
+        format!("{}.{}", self.schema, self.name)
+    }
+}
+
+/// Groups the columns by table, the columns are expected to be sorted by schema and table.
+pub fn group_columns_by_table(columns: Vec<ColumnInfo>) -> Vec<Table> {
+    let mut tables: Vec<Table> = Vec::new();
+
+    for column in columns {
+        match tables.last_mut() {
+            Some(table)
+                if table.schema == column.table_schema && table.name == column.table_name =>
+            {
+                table.columns.push(column);
+            }
+            _ => tables.push(Table {
+                schema: column.table_schema.clone(),
+                name: column.table_name.clone(),
+                columns: vec![column],
+            }),
+        }
+    }
+
+    tables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(table_schema: &str, table_name: &str, column_name: &str) -> ColumnInfo {
+        ColumnInfo {
+            table_schema: table_schema.to_string(),
+            table_name: table_name.to_string(),
+            column_name: column_name.to_string(),
+            data_type: "Utf8".to_string(),
+        }
+    }
+
+    #[test]
+    fn same_named_tables_of_different_schemas() {
+        let tables = group_columns_by_table(vec![
+            column("archive", "log_entries", "msg"),
+            column("public", "log_entries", "msg"),
+            column("public", "log_entries", "time"),
+            column("public", "measures", "value"),
+        ]);
+
+        let tables = tables
+            .iter()
+            .map(|table| (table.qualified_name(), table.columns.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tables,
+            [
+                ("archive.log_entries".to_string(), 1),
+                ("public.log_entries".to_string(), 2),
+                ("public.measures".to_string(), 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::cell::Cell;
+
+use leptos::{create_rw_signal, RwSignal, SignalGetUntracked, SignalSet};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use wasm_bindgen::JsCast;
+use web_sys::{Storage, Window};
+
+const CREDENTIALS_KEY: &str = "sirius.credentials";
+
+thread_local! {
+    static AUTH_REQUIRED: Cell<Option<RwSignal<bool>>> = const { Cell::new(None) };
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Header {
+    pub name: String,
+    pub value: String,
+}
+
+/// Sent along with every request to the analytics server, e.g. to get through an auth proxy.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Credentials {
+    /// Sent as `Authorization: Bearer <token>` when not empty
+    pub bearer_token: String,
+    pub headers: Vec<Header>,
+}
+
+/// The local storage of the page, workers having none: their requests get the credentials of the
+/// page through their messages.
+fn local_storage() -> Option<Storage> {
+    js_sys::global()
+        .dyn_ref::<Window>()?
+        .local_storage()
+        .map_err(|err| error!("local storage error: {err:?}"))
+        .ok()
+        .flatten()
+}
+
+pub fn load_credentials() -> Credentials {
+    let Some(credentials) =
+        local_storage().and_then(|storage| storage.get_item(CREDENTIALS_KEY).ok().flatten())
+    else {
+        return Credentials::default();
+    };
+
+    serde_json::from_str(&credentials).unwrap_or_else(|err| {
+        error!("credentials deserialization error: {err}");
+        Credentials::default()
+    })
+}
+
+pub fn save_credentials(credentials: &Credentials) {
+    let Some(storage) = local_storage() else {
+        return;
+    };
+
+    match serde_json::to_string(credentials) {
+        Ok(credentials) => {
+            if let Err(err) = storage.set_item(CREDENTIALS_KEY, &credentials) {
+                error!("credentials storage error: {err:?}");
+            }
+        }
+        Err(err) => error!("credentials serialization error: {err}"),
+    }
+}
+
+/// Must be called once, from the root of the application, before any request is made.
+pub fn provide_auth_state() {
+    AUTH_REQUIRED.set(Some(create_rw_signal(false)));
+}
+
+/// Whether the analytics server rejected the credentials of the last request.
+pub fn use_auth_required() -> Option<RwSignal<bool>> {
+    AUTH_REQUIRED.get()
+}
+
+pub fn set_auth_required(auth_required: bool) {
+    if let Some(signal) = AUTH_REQUIRED.get() {
+        if signal.get_untracked() != auth_required {
+            signal.set(auth_required);
+        }
+    }
+}
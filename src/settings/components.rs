@@ -0,0 +1,190 @@
+use leptos::{
+    component, create_action, create_memo, create_resource, create_rw_signal, create_signal,
+    event_target_value, view, IntoView, Show, SignalGet, SignalSet, SignalUpdate, SignalWith,
+};
+use leptos_router::A;
+use tracing::error;
+
+use crate::cache::{self, CacheUsage};
+
+use super::auth::{
+    load_credentials, save_credentials, set_auth_required, use_auth_required, Credentials, Header,
+};
+
+#[component]
+pub fn Settings() -> impl IntoView {
+    view! {
+        <div class="w-full p-4 flex flex-col gap-4">
+            <AuthSettings />
+            <CacheSettings />
+        </div>
+    }
+}
+
+/// Displayed while the analytics server rejects the credentials.
+#[component]
+pub fn AuthRequiredBanner() -> impl IntoView {
+    let auth_required = use_auth_required();
+
+    view! {
+        <Show when=move || auth_required.is_some_and(|auth_required| auth_required.get())>
+            <article class="m-4">
+                <strong>"Authentication required"</strong>
+                <p>
+                    "The analytics server rejected the request, please check the credentials in "
+                    <A href="/settings">"Settings"</A> "."
+                </p>
+            </article>
+        </Show>
+    }
+}
+
+/// Bearer token and custom headers sent along with every request to the analytics server.
+#[component]
+fn AuthSettings() -> impl IntoView {
+    let credentials = load_credentials();
+    let bearer_token = create_rw_signal(credentials.bearer_token);
+    let headers = create_rw_signal(credentials.headers);
+    let (saved, set_saved) = create_signal(false);
+    // Only re-rendering the rows when added or removed, not to lose the focus while typing
+    let header_count = create_memo(move |_| headers.with(Vec::len));
+
+    let save = move |_| {
+        let credentials = Credentials {
+            bearer_token: bearer_token.get(),
+            headers: headers.with(|headers| {
+                headers
+                    .iter()
+                    .filter(|header| !header.name.trim().is_empty())
+                    .cloned()
+                    .collect()
+            }),
+        };
+        save_credentials(&credentials);
+        headers.set(credentials.headers);
+        set_auth_required(false);
+        set_saved.set(true);
+    };
+
+    let header_rows = move || {
+        (0..header_count.get())
+            .map(|index| {
+                let field = move |get: fn(&Header) -> &String| {
+                    headers.with(|headers| headers.get(index).map(get).cloned())
+                };
+                view! {
+                    <div class="flex gap-2">
+                        <input
+                            type="text"
+                            placeholder="Name"
+                            prop:value=move || field(|header| &header.name)
+                            on:input=move |evt| {
+                                headers.update(|headers| headers[index].name = event_target_value(&evt));
+                                set_saved.set(false);
+                            }
+                        />
+                        <input
+                            type="text"
+                            placeholder="Value"
+                            prop:value=move || field(|header| &header.value)
+                            on:input=move |evt| {
+                                headers.update(|headers| headers[index].value = event_target_value(&evt));
+                                set_saved.set(false);
+                            }
+                        />
+                        <button
+                            class="outline"
+                            on:click=move |_| {
+                                headers.update(|headers| {
+                                    headers.remove(index);
+                                });
+                                set_saved.set(false);
+                            }
+                        >
+                            "Remove"
+                        </button>
+                    </div>
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <article>
+            <header>"Authentication"</header>
+            <label>
+                "Bearer token"
+                <input
+                    type="password"
+                    autocomplete="off"
+                    prop:value=move || bearer_token.get()
+                    on:input=move |evt| {
+                        bearer_token.set(event_target_value(&evt));
+                        set_saved.set(false);
+                    }
+                />
+            </label>
+            <p>"Custom headers"</p>
+            {header_rows}
+            <div class="flex gap-2">
+                <button
+                    class="outline"
+                    on:click=move |_| headers.update(|headers| headers.push(Header::default()))
+                >
+                    "Add header"
+                </button>
+                <button on:click=save>"Save"</button>
+            </div>
+            <Show when=move || saved.get()>
+                <p>"Saved, the credentials are sent along with the next requests."</p>
+            </Show>
+        </article>
+    }
+}
+
+/// Usage of the client cache of query results, which can be cleared.
+#[component]
+fn CacheSettings() -> impl IntoView {
+    let clear = create_action(move |(): &()| async move {
+        if let Err(err) = cache::clear().await {
+            error!("cache clear error: {err}");
+        }
+    });
+
+    let usage = create_resource(
+        move || clear.version().get(),
+        move |_| async move {
+            cache::usage().await.unwrap_or_else(|err| {
+                error!("cache usage error: {err}");
+                CacheUsage::default()
+            })
+        },
+    );
+
+    let description = move || {
+        usage.get().map(|usage| {
+            format!(
+                "{} cached query result(s), {:.1} MB",
+                usage.count,
+                usage.size / (1_024.0 * 1_024.0)
+            )
+        })
+    };
+
+    view! {
+        <article>
+            <header>"Cache"</header>
+            <p>
+                "The results of the queries over a time range ending more than a few minutes ago are kept in the browser."
+            </p>
+            <p>{description}</p>
+            <button
+                class="outline"
+                disabled=move || clear.pending().get()
+                on:click=move |_| clear.dispatch(())
+            >
+                "Clear cache"
+            </button>
+        </article>
+    }
+}
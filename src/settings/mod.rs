@@ -0,0 +1,5 @@
+mod auth;
+mod components;
+
+pub use auth::{load_credentials, provide_auth_state, set_auth_required, Credentials};
+pub use components::{AuthRequiredBanner, Settings};
@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use leptos::{
+    component, create_memo, create_signal, view, Callable, Callback, For, IntoView, Params, Signal,
+    SignalGet, SignalSet, SignalWith,
+};
+use leptos_router::{use_navigate, use_params, NavigateOptions, Params, A};
+use uuid::Uuid;
+
+use crate::components::Spinner;
+use crate::datetime::display_duration;
+use crate::measures::canvas::MeasuresStyle;
+use crate::measures::types::{ChartMeasures, MeasureSet, MeasuresData};
+use crate::measures::MeasuresChart;
+use crate::spans::create_process_spans::{create_process_spans, CreateProcessSpansReturn};
+use crate::spans::statistics::{compute_span_statistics, SpanStatistics};
+use crate::spans::types::ThreadSpans;
+
+#[derive(Params, PartialEq)]
+struct SpanStatisticsParams {
+    id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortColumn {
+    Name,
+    Count,
+    Total,
+    Mean,
+    P50,
+    P95,
+    P99,
+    Max,
+}
+
+impl SortColumn {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Name => "Name",
+            Self::Count => "Count",
+            Self::Total => "Total",
+            Self::Mean => "Mean",
+            Self::P50 => "p50",
+            Self::P95 => "p95",
+            Self::P99 => "p99",
+            Self::Max => "Max",
+        }
+    }
+
+    fn sort(self, statistics: &mut [SpanStatistics], descending: bool) {
+        match self {
+            Self::Name => statistics.sort_by(|a, b| a.name.cmp(&b.name)),
+            Self::Count => statistics.sort_by_key(|statistics| statistics.count),
+            Self::Total => statistics.sort_by_key(|statistics| statistics.total),
+            Self::Mean => statistics.sort_by_key(|statistics| statistics.mean),
+            Self::P50 => statistics.sort_by_key(|statistics| statistics.p50),
+            Self::P95 => statistics.sort_by_key(|statistics| statistics.p95),
+            Self::P99 => statistics.sort_by_key(|statistics| statistics.p99),
+            Self::Max => statistics.sort_by_key(|statistics| statistics.max),
+        }
+
+        if descending {
+            statistics.reverse();
+        }
+    }
+}
+
+const SORT_COLUMNS: [SortColumn; 8] = [
+    SortColumn::Name,
+    SortColumn::Count,
+    SortColumn::Total,
+    SortColumn::Mean,
+    SortColumn::P50,
+    SortColumn::P95,
+    SortColumn::P99,
+    SortColumn::Max,
+];
+
+#[component]
+pub fn SpanStatistics() -> impl IntoView {
+    let params = use_params::<SpanStatisticsParams>();
+    let id = move || {
+        params.with(|params| {
+            params
+                .as_ref()
+                .ok()
+                .and_then(|params| params.id)
+                .unwrap_or_default()
+        })
+    };
+
+    let CreateProcessSpansReturn {
+        threads,
+        is_loading,
+        ..
+    } = create_process_spans(Signal::derive(id));
+
+    let (sort, set_sort) = create_signal((SortColumn::Total, true));
+    let (selected, set_selected) = create_signal::<Option<String>>(None);
+
+    let statistics = create_memo(move |_| {
+        let mut statistics = threads.with(|threads| compute_span_statistics(threads));
+        let (column, descending) = sort.get();
+        column.sort(&mut statistics, descending);
+        statistics
+    });
+
+    let navigate = use_navigate();
+    let jump_to_timeline = Callback::new(move |(name, begin): (String, i64)| {
+        let Some(end) = threads.with(|threads| find_span_end(threads, &name, begin)) else {
+            return;
+        };
+
+        // Leave some room around the span so that its context is visible
+        let padding = (end - begin).max(1_000_000);
+        navigate(
+            &format!(
+                "/timeline/{}?begin={}&end={}",
+                id(),
+                begin - padding,
+                end + padding
+            ),
+            NavigateOptions::default(),
+        );
+    });
+
+    let toggle_sort = move |column: SortColumn| {
+        let (current_column, descending) = sort.get();
+        if current_column == column {
+            set_sort.set((column, !descending));
+        } else {
+            set_sort.set((column, column != SortColumn::Name));
+        }
+    };
+
+    view! {
+        <div class="w-full p-4 flex flex-col gap-4">
+            <div class="flex gap-4">
+                <A href=move || format!("/timeline/{}", id())>"Timeline"</A>
+                <span class="text-sm">"Click a row to plot its durations, click a point to open it in the timeline"</span>
+            </div>
+            {move || {
+                selected
+                    .get()
+                    .map(|name| {
+                        view! {
+                            <SpanDurations
+                                name
+                                threads
+                                is_loading
+                                on_close=move |()| set_selected.set(None)
+                                on_measure_click=jump_to_timeline
+                            />
+                        }
+                    })
+            }}
+            {move || {
+                if is_loading.get() {
+                    view! { <Spinner /> }.into_view()
+                } else {
+                    view! {
+                        <SpanStatisticsTable
+                            statistics=statistics.into()
+                            sort=sort.into()
+                            on_sort=toggle_sort
+                            on_select=move |name| set_selected.set(Some(name))
+                        />
+                    }
+                        .into_view()
+                }
+            }}
+        </div>
+    }
+}
+
+/// Scatter plot of the durations of all the occurrences of the span named `name`.
+#[component]
+fn SpanDurations(
+    name: String,
+    threads: Signal<Vec<ThreadSpans>>,
+    is_loading: Signal<bool>,
+    #[prop(into)] on_close: Callback<()>,
+    #[prop(into)] on_measure_click: Callback<(String, i64)>,
+) -> impl IntoView {
+    let label = name.clone();
+
+    let durations =
+        create_memo(move |_| threads.with(|threads| create_durations_measures(threads, &name)));
+
+    let durations_range = create_memo(move |_| {
+        durations.with(|durations| {
+            let measure_set = durations.as_ref()?.values().next()?;
+            // A single occurrence would otherwise produce an empty range
+            let padding = ((measure_set.end - measure_set.start) / 20).max(1_000_000);
+            Some((
+                DateTime::from_timestamp_nanos(measure_set.start - padding),
+                DateTime::from_timestamp_nanos(measure_set.end + padding),
+            ))
+        })
+    });
+
+    view! {
+        <div class="flex flex-col gap-1">
+            <div class="flex gap-4">
+                <strong>{label}</strong>
+                <a
+                    href="#"
+                    on:click=move |evt| {
+                        evt.prevent_default();
+                        on_close.call(());
+                    }
+                >
+                    "Close"
+                </a>
+            </div>
+            <MeasuresChart
+                measures=Signal::derive(move || durations.get().map(ChartMeasures::Decoded))
+                range=durations_range.into()
+                is_loading
+                style=MeasuresStyle::Scatter
+                on_measure_click
+            />
+        </div>
+    }
+}
+
+#[component]
+fn SpanStatisticsTable(
+    statistics: Signal<Vec<SpanStatistics>>,
+    sort: Signal<(SortColumn, bool)>,
+    #[prop(into)] on_sort: Callback<SortColumn>,
+    #[prop(into)] on_select: Callback<String>,
+) -> impl IntoView {
+    view! {
+        <div class="overflow-auto">
+            <table class="striped">
+                <thead>
+                    <tr>
+                        {SORT_COLUMNS
+                            .into_iter()
+                            .map(|column| {
+                                view! {
+                                    <th
+                                        scope="col"
+                                        class="cursor-pointer select-none"
+                                        on:click=move |_| on_sort.call(column)
+                                    >
+                                        {column.label()}
+                                        {move || match sort.get() {
+                                            (current, true) if current == column => " ▾",
+                                            (current, false) if current == column => " ▴",
+                                            _ => "",
+                                        }}
+                                    </th>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </tr>
+                </thead>
+                <tbody>
+                    <For
+                        each=move || statistics.get()
+                        key=|statistics| statistics.name.clone()
+                        let:statistics
+                    >
+                        <SpanStatisticsRow statistics on_click=move |name| on_select.call(name) />
+                    </For>
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
+#[component]
+fn SpanStatisticsRow(
+    statistics: SpanStatistics,
+    on_click: impl Fn(String) + 'static,
+) -> impl IntoView {
+    let name = statistics.name.clone();
+
+    view! {
+        <tr class="cursor-pointer" on:click=move |_| on_click(name.clone())>
+            <td>{statistics.name}</td>
+            <td>{statistics.count}</td>
+            <td>{display_duration(statistics.total)}</td>
+            <td>{display_duration(statistics.mean)}</td>
+            <td>{display_duration(statistics.p50)}</td>
+            <td>{display_duration(statistics.p95)}</td>
+            <td>{display_duration(statistics.p99)}</td>
+            <td>{display_duration(statistics.max)}</td>
+        </tr>
+    }
+}
+
+/// Builds a single measure set of the durations (in milliseconds) of the spans named `name`,
+/// indexed by their begin time.
+#[expect(clippy::cast_precision_loss)]
+fn create_durations_measures(threads: &[ThreadSpans], name: &str) -> Option<MeasuresData> {
+    let mut measures = threads
+        .iter()
+        .flat_map(|thread| thread.spans.iter())
+        .filter(|span| span.name == name)
+        .map(|span| (span.begin, (span.end - span.begin) as f64 / 1_000_000.0))
+        .collect::<Vec<_>>();
+
+    measures.sort_by_key(|(time, _)| *time);
+
+    let (start, _) = measures.first().copied()?;
+    let (end, _) = measures.last().copied()?;
+    let min = measures
+        .iter()
+        .map(|(_, value)| *value)
+        .fold(f64::MAX, f64::min);
+    let max = measures
+        .iter()
+        .map(|(_, value)| *value)
+        .fold(f64::MIN, f64::max);
+
+    Some(HashMap::from([(
+        name.to_string(),
+        MeasureSet {
+            measures,
+            unit: "ms".to_string(),
+            min,
+            max,
+            start,
+            end,
+        },
+    )]))
+}
+
+fn find_span_end(threads: &[ThreadSpans], name: &str, begin: i64) -> Option<i64> {
+    threads
+        .iter()
+        .flat_map(|thread| thread.spans.iter())
+        .find(|span| span.begin == begin && span.name == name)
+        .map(|span| span.end)
+}
@@ -0,0 +1,3 @@
+mod components;
+
+pub use components::SpanStatistics;
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+
+use super::types::ThreadSpans;
+
+pub const ROOT_INDEX: usize = 0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallTreeNode {
+    pub name: String,
+    pub depth: u32,
+    /// Time spent in the node, including its children, in nanoseconds
+    pub total: i64,
+    /// Time spent in the node itself, in nanoseconds
+    pub self_time: i64,
+    pub calls: u64,
+    pub children: Vec<usize>,
+}
+
+impl CallTreeNode {
+    fn new(name: String, depth: u32) -> Self {
+        Self {
+            name,
+            depth,
+            total: 0,
+            self_time: 0,
+            calls: 0,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// An arena based call tree, the node at `ROOT_INDEX` is a synthetic root spanning all the threads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallTree {
+    pub nodes: Vec<CallTreeNode>,
+}
+
+impl CallTree {
+    fn new() -> Self {
+        Self {
+            nodes: vec![CallTreeNode::new("all".to_string(), 0)],
+        }
+    }
+
+    pub fn root(&self) -> &CallTreeNode {
+        &self.nodes[ROOT_INDEX]
+    }
+
+    fn child(
+        &mut self,
+        lookup: &mut HashMap<(usize, String), usize>,
+        parent: usize,
+        name: &str,
+    ) -> usize {
+        if let Some(index) = lookup.get(&(parent, name.to_string())) {
+            return *index;
+        }
+
+        let index = self.nodes.len();
+        let depth = self.nodes[parent].depth + 1;
+        self.nodes.push(CallTreeNode::new(name.to_string(), depth));
+        self.nodes[parent].children.push(index);
+        lookup.insert((parent, name.to_string()), index);
+        index
+    }
+
+    /// Sorts the children of every node by decreasing total time.
+    fn sort(&mut self) {
+        for index in 0..self.nodes.len() {
+            let mut children = std::mem::take(&mut self.nodes[index].children);
+            children.sort_by_key(|child| std::cmp::Reverse(self.nodes[*child].total));
+            self.nodes[index].children = children;
+        }
+    }
+}
+
+/// Time spent in each span clipped to the selection, and the index of its parent span if known.
+struct ClippedSpans {
+    durations: Vec<i64>,
+    self_times: Vec<i64>,
+    parents: Vec<Option<usize>>,
+}
+
+fn clip_spans(thread: &ThreadSpans, begin: i64, end: i64) -> ClippedSpans {
+    let indices = thread
+        .spans
+        .iter()
+        .enumerate()
+        .map(|(index, span)| (span.id, index))
+        .collect::<HashMap<_, _>>();
+
+    let durations = thread
+        .spans
+        .iter()
+        .map(|span| (span.end.min(end) - span.begin.max(begin)).max(0))
+        .collect::<Vec<_>>();
+
+    let parents = thread
+        .spans
+        .iter()
+        .map(|span| indices.get(&span.parent).copied())
+        .collect::<Vec<_>>();
+
+    let mut self_times = durations.clone();
+    for (index, parent) in parents.iter().enumerate() {
+        if let Some(parent) = parent {
+            self_times[*parent] -= durations[index];
+        }
+    }
+    for self_time in &mut self_times {
+        *self_time = (*self_time).max(0);
+    }
+
+    ClippedSpans {
+        durations,
+        self_times,
+        parents,
+    }
+}
+
+/// Builds the top-down call tree of the spans overlapping the `begin..end` range (in nanoseconds).
+///
+/// Spans partially outside of the range only account for the time spent inside of it.
+pub fn top_down(threads: &[ThreadSpans], begin: i64, end: i64) -> CallTree {
+    let mut tree = CallTree::new();
+    let mut lookup = HashMap::new();
+
+    for thread in threads {
+        let clipped = clip_spans(thread, begin, end);
+
+        // Parents are always shallower than their children, visiting the spans by depth ensures
+        // the node of a parent span is known before its children are inserted.
+        let mut order = (0..thread.spans.len()).collect::<Vec<_>>();
+        order.sort_by_key(|index| thread.spans[*index].depth);
+
+        let mut span_nodes = vec![ROOT_INDEX; thread.spans.len()];
+        for index in order {
+            let span = &thread.spans[index];
+            let parent_node =
+                clipped.parents[index].map_or(ROOT_INDEX, |parent| span_nodes[parent]);
+            let node = tree.child(&mut lookup, parent_node, &span.name);
+            span_nodes[index] = node;
+
+            if clipped.durations[index] == 0 {
+                continue;
+            }
+
+            let node = &mut tree.nodes[node];
+            node.total += clipped.durations[index];
+            node.self_time += clipped.self_times[index];
+            node.calls += 1;
+        }
+    }
+
+    let total = tree.nodes[ROOT_INDEX]
+        .children
+        .iter()
+        .map(|child| tree.nodes[*child].total)
+        .sum();
+    tree.nodes[ROOT_INDEX].total = total;
+
+    prune_empty(&mut tree);
+    tree.sort();
+    tree
+}
+
+/// Builds the bottom-up (inverted) call tree of the spans overlapping the `begin..end` range.
+///
+/// First level nodes are the spans in which the time is actually spent, their children are the
+/// callers. Every node accounts for the self time of the first level span reached through it,
+/// which is why the self time of the nodes deeper than the first level is always zero.
+pub fn bottom_up(threads: &[ThreadSpans], begin: i64, end: i64) -> CallTree {
+    let mut tree = CallTree::new();
+    let mut lookup = HashMap::new();
+
+    for thread in threads {
+        let clipped = clip_spans(thread, begin, end);
+
+        for (index, span) in thread.spans.iter().enumerate() {
+            let self_time = clipped.self_times[index];
+            if clipped.durations[index] == 0 {
+                continue;
+            }
+
+            let mut node = tree.child(&mut lookup, ROOT_INDEX, &span.name);
+            tree.nodes[node].total += self_time;
+            tree.nodes[node].self_time += self_time;
+            tree.nodes[node].calls += 1;
+
+            let mut parent = clipped.parents[index];
+            while let Some(parent_index) = parent {
+                node = tree.child(&mut lookup, node, &thread.spans[parent_index].name);
+                tree.nodes[node].total += self_time;
+                tree.nodes[node].calls += 1;
+                parent = clipped.parents[parent_index];
+            }
+
+            tree.nodes[ROOT_INDEX].total += self_time;
+        }
+    }
+
+    prune_empty(&mut tree);
+    tree.sort();
+    tree
+}
+
+/// Detaches the nodes that didn't receive any call, typically the ancestors of a selection
+/// that were inserted only to preserve the tree structure.
+fn prune_empty(tree: &mut CallTree) {
+    for index in 0..tree.nodes.len() {
+        let children = std::mem::take(&mut tree.nodes[index].children);
+        tree.nodes[index].children = children
+            .into_iter()
+            .filter(|child| tree.nodes[*child].calls > 0)
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spans::types::SpanData;
+
+    fn span(id: i64, parent: i64, depth: u32, (begin, end): (i64, i64), name: &str) -> SpanData {
+        SpanData {
+            id,
+            parent,
+            depth,
+            begin,
+            end,
+            name: name.to_string(),
+        }
+    }
+
+    /// `main` calls `a`, then `b` which ends after the selection and calls `a` again, then `c`
+    /// which starts after the selection.
+    fn thread() -> ThreadSpans {
+        ThreadSpans {
+            stream_id: "thread".to_string(),
+            max_depth: 2,
+            spans: vec![
+                span(1, 0, 1, (0, 200), "main"),
+                span(2, 1, 2, (10, 40), "a"),
+                span(3, 1, 2, (50, 120), "b"),
+                span(4, 3, 3, (60, 70), "a"),
+                span(5, 1, 2, (150, 180), "c"),
+            ],
+            blocks: Vec::new(),
+        }
+    }
+
+    fn find<'a>(tree: &'a CallTree, parent: usize, name: &str) -> (usize, &'a CallTreeNode) {
+        let index = tree.nodes[parent]
+            .children
+            .iter()
+            .copied()
+            .find(|child| tree.nodes[*child].name == name)
+            .unwrap();
+        (index, &tree.nodes[index])
+    }
+
+    fn names(tree: &CallTree, parent: usize) -> Vec<&str> {
+        tree.nodes[parent]
+            .children
+            .iter()
+            .map(|child| tree.nodes[*child].name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn top_down_clips_to_selection() {
+        let tree = top_down(&[thread()], 0, 100);
+        assert_eq!(tree.root().total, 100);
+
+        let (main_index, main) = find(&tree, ROOT_INDEX, "main");
+        assert_eq!((main.total, main.self_time, main.calls), (100, 20, 1));
+        // `c` is outside of the selection, and the children are sorted by total time
+        assert_eq!(names(&tree, main_index), ["b", "a"]);
+
+        let (b_index, b) = find(&tree, main_index, "b");
+        assert_eq!((b.total, b.self_time, b.depth), (50, 40, 2));
+
+        let (_, nested_a) = find(&tree, b_index, "a");
+        assert_eq!(
+            (nested_a.total, nested_a.self_time, nested_a.depth),
+            (10, 10, 3)
+        );
+    }
+
+    #[test]
+    fn bottom_up_inverts_self_times() {
+        let tree = bottom_up(&[thread()], 0, 100);
+        assert_eq!(tree.root().total, 100);
+        assert_eq!(names(&tree, ROOT_INDEX), ["a", "b", "main"]);
+
+        let (a_index, a) = find(&tree, ROOT_INDEX, "a");
+        assert_eq!((a.total, a.self_time, a.calls), (40, 40, 2));
+
+        let (_, direct_caller) = find(&tree, a_index, "main");
+        assert_eq!((direct_caller.total, direct_caller.self_time), (30, 0));
+
+        let (b_index, b_caller) = find(&tree, a_index, "b");
+        assert_eq!((b_caller.total, b_caller.self_time), (10, 0));
+        let (_, main_caller) = find(&tree, b_index, "main");
+        assert_eq!(main_caller.total, 10);
+    }
+
+    #[test]
+    fn selection_inside_a_span() {
+        let tree = top_down(&[thread()], 55, 65);
+        let (main_index, main) = find(&tree, ROOT_INDEX, "main");
+        assert_eq!((main.total, main.self_time), (10, 0));
+
+        let (b_index, b) = find(&tree, main_index, "b");
+        assert_eq!((b.total, b.self_time), (10, 5));
+        assert_eq!(find(&tree, b_index, "a").1.total, 5);
+        assert_eq!(names(&tree, main_index), ["b"]);
+    }
+}
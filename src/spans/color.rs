@@ -0,0 +1,9 @@
+/// Picks a warm color deterministically from the span name so that it stays stable across renders.
+pub fn get_span_color(name: &str) -> String {
+    let hash = name.bytes().fold(0u32, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(u32::from(byte))
+    });
+    let hue = hash % 50;
+    let lightness = 50 + (hash / 50) % 20;
+    format!("hsl({hue}, 90%, {lightness}%)")
+}
@@ -0,0 +1,143 @@
+use chrono::{DateTime, Utc};
+use leptos::{create_memo, Memo, Signal, SignalGet, SignalWith};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::{
+    import::imported_data::use_imported_process,
+    request::{create_process_end, create_request_opt, FindProcessRequest, FIND_PROCESS_URL},
+    ticks::TicksConverter,
+    types::{Block, ProcessInfo, Stream},
+};
+
+use super::{
+    request::{spans_request, thread_blocks_request, thread_streams_request},
+    types::{assign_thread_blocks, group_spans_by_thread, Span, ThreadSpans},
+};
+
+pub struct CreateProcessSpansReturn {
+    pub process: Memo<Option<ProcessInfo>>,
+    pub threads: Signal<Vec<ThreadSpans>>,
+    /// The process lifetime until its last block, or the time range of the imported data
+    pub range: Signal<Option<(DateTime<Utc>, DateTime<Utc>)>>,
+    pub is_loading: Signal<bool>,
+}
+
+/// Fetches the spans of all the threads of a process, from its start until now.
+///
+/// The imported data of the process, if any, is displayed instead of the analytics server's.
+pub fn create_process_spans(id: Signal<Uuid>) -> CreateProcessSpansReturn {
+    let imported_process = use_imported_process(move || id.get().to_string());
+
+    let processes = create_request_opt::<_, Vec<ProcessInfo>>(move || {
+        if imported_process.with(Option::is_some) {
+            return None;
+        }
+
+        Some(processes_request(id.get()))
+    });
+
+    let process = create_memo(move |_| match imported_process.get() {
+        Some(imported_process) => Some(imported_process.process.clone()),
+        None => processes.get()?.first().cloned(),
+    });
+
+    let process_end = create_process_end(move || {
+        if imported_process.with(Option::is_some) {
+            return None;
+        }
+
+        process.get()
+    });
+
+    let streams = create_request_opt::<_, Vec<Stream>>(move || {
+        if imported_process.with(Option::is_some) {
+            return None;
+        }
+
+        let process = process.get()?;
+
+        Some(thread_streams_request(
+            &process.process_id,
+            process.start_time,
+            process_end.get()?,
+        ))
+    });
+
+    let spans = create_request_opt::<_, Vec<Span>>(move || {
+        let process = process.get()?;
+
+        spans_request(&streams.get()?, process.start_time, process_end.get()?)
+    });
+
+    let blocks = create_request_opt::<_, Vec<Block>>(move || {
+        let process = process.get()?;
+
+        thread_blocks_request(&streams.get()?, process.start_time, process_end.get()?)
+    });
+
+    let ticks_converter = create_memo(move |_| {
+        let process = process.get()?;
+
+        TicksConverter::try_new(&process)
+            .map_err(|err| error!("ticks converter failed to initialize: {err}"))
+            .ok()
+    });
+
+    let threads = create_memo(move |_| {
+        if let Some(imported_process) = imported_process.get() {
+            return group_spans_by_thread(imported_process.spans.clone());
+        }
+
+        let mut threads = group_spans_by_thread(spans.get().unwrap_or_default());
+
+        if let (Some(blocks), Some(ticks_converter)) = (blocks.get(), ticks_converter.get()) {
+            assign_thread_blocks(&mut threads, &blocks, &ticks_converter);
+        }
+
+        threads
+    });
+
+    let range = create_memo(move |_| {
+        if let Some(imported_process) = imported_process.get() {
+            return Some(imported_process.time_range());
+        }
+
+        let process = process.get()?;
+
+        // The end of the last block, converted like the blocks drawn on the timeline, is the
+        // latest time for which the process recorded data
+        let last_block_end = blocks
+            .get()
+            .and_then(|blocks| blocks.iter().map(|block| block.end_ticks).max())
+            .zip(ticks_converter.get())
+            .map(|(end_ticks, ticks_converter)| ticks_converter.ticks_to_datetime(end_ticks));
+
+        Some((
+            process.start_time,
+            match last_block_end {
+                Some(end) => end.max(process.start_time),
+                None => process_end.get()?,
+            },
+        ))
+    });
+
+    let is_loading =
+        move || processes.loading().get() || streams.loading().get() || spans.loading().get();
+
+    CreateProcessSpansReturn {
+        process,
+        threads: threads.into(),
+        range: range.into(),
+        is_loading: is_loading.into(),
+    }
+}
+
+fn processes_request(process_id: Uuid) -> (String, FindProcessRequest) {
+    (
+        FIND_PROCESS_URL.to_string(),
+        FindProcessRequest {
+            process_id: process_id.to_string(),
+        },
+    )
+}
@@ -0,0 +1,6 @@
+pub mod call_tree;
+pub mod color;
+pub mod create_process_spans;
+pub mod request;
+pub mod statistics;
+pub mod types;
@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+
+use crate::query_builder::{col, union_all, Order, Query, Source};
+use crate::request::{QueryRequest, QUERY_URL};
+use crate::types::Stream;
+
+pub fn thread_streams_request(
+    process_id: &str,
+    begin: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> (String, QueryRequest) {
+    let request = QueryRequest {
+        sql: Query::from(Source::Streams)
+            .columns(&["process_id", "stream_id"])
+            .filter(col("process_id").eq(process_id))
+            .filter(col("tags").contains("cpu"))
+            .to_string(),
+        begin: Some(begin.to_rfc3339()),
+        end: Some(end.to_rfc3339()),
+    };
+
+    (QUERY_URL.to_string(), request)
+}
+
+/// Fetches the blocks of all the given thread streams, their boundaries are expressed in raw ticks.
+///
+/// Returns `None` when there is no stream to query.
+pub fn thread_blocks_request(
+    streams: &[Stream],
+    begin: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Option<(String, QueryRequest)> {
+    if streams.is_empty() {
+        return None;
+    }
+
+    let stream_ids = streams.iter().map(|stream| stream.stream_id.clone());
+
+    let request = QueryRequest {
+        sql: Query::from(Source::Blocks)
+            .columns(&["stream_id", "begin_ticks", "end_ticks", "nb_objects"])
+            .filter(col("stream_id").in_list(stream_ids))
+            .order_by("begin_ticks", Order::Asc)
+            .to_string(),
+        begin: Some(begin.to_rfc3339()),
+        end: Some(end.to_rfc3339()),
+    };
+
+    Some((QUERY_URL.to_string(), request))
+}
+
+/// Fetches the spans of all the given thread streams overlapping the provided time range.
+///
+/// Returns `None` when there is no stream to query.
+pub fn spans_request(
+    streams: &[Stream],
+    begin: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Option<(String, QueryRequest)> {
+    if streams.is_empty() {
+        return None;
+    }
+
+    let queries = streams
+        .iter()
+        .map(|stream| {
+            Query::from(Source::ThreadSpans {
+                stream_id: stream.stream_id.clone(),
+            })
+            .literal(stream.stream_id.as_str(), "stream_id")
+            .columns(&["id", "parent", "depth", "begin", "end", "name", "target"])
+            .filter(col("end").ge(begin))
+            .filter(col("begin").le(end))
+        })
+        .collect::<Vec<_>>();
+
+    let request = QueryRequest {
+        sql: union_all(&queries),
+        begin: Some(begin.to_rfc3339()),
+        end: Some(end.to_rfc3339()),
+    };
+
+    Some((QUERY_URL.to_string(), request))
+}
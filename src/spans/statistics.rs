@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use super::types::ThreadSpans;
+
+/// Duration statistics of all the occurrences of a given span, durations are in nanoseconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanStatistics {
+    pub name: String,
+    pub count: u64,
+    pub total: i64,
+    pub mean: i64,
+    pub p50: i64,
+    pub p95: i64,
+    pub p99: i64,
+    pub max: i64,
+}
+
+pub fn compute_span_statistics(threads: &[ThreadSpans]) -> Vec<SpanStatistics> {
+    let mut durations: HashMap<&str, Vec<i64>> = HashMap::new();
+
+    for thread in threads {
+        for span in &thread.spans {
+            durations
+                .entry(span.name.as_str())
+                .or_default()
+                .push(span.end - span.begin);
+        }
+    }
+
+    durations
+        .into_iter()
+        .map(|(name, mut durations)| {
+            durations.sort_unstable();
+
+            let total = durations.iter().sum::<i64>();
+            // There is always at least one duration per name
+            let count = durations.len() as u64;
+            #[expect(clippy::cast_possible_wrap)]
+            let mean = total / count as i64;
+
+            SpanStatistics {
+                name: name.to_string(),
+                count,
+                total,
+                mean,
+                p50: percentile(&durations, 50),
+                p95: percentile(&durations, 95),
+                p99: percentile(&durations, 99),
+                max: durations.last().copied().unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+/// Nearest-rank percentile of already sorted durations.
+fn percentile(sorted_durations: &[i64], percentile: usize) -> i64 {
+    if sorted_durations.is_empty() {
+        return 0;
+    }
+
+    let rank = (percentile * sorted_durations.len()).div_ceil(100).max(1);
+    sorted_durations[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spans::types::SpanData;
+
+    #[test]
+    fn nearest_rank_percentiles() {
+        assert_eq!(percentile(&[], 50), 0);
+
+        assert_eq!(percentile(&[7], 0), 7);
+        assert_eq!(percentile(&[7], 50), 7);
+        assert_eq!(percentile(&[7], 99), 7);
+
+        assert_eq!(percentile(&[10, 20], 0), 10);
+        assert_eq!(percentile(&[10, 20], 50), 10);
+        assert_eq!(percentile(&[10, 20], 51), 20);
+        assert_eq!(percentile(&[10, 20], 95), 20);
+
+        let hundred = (1..=100).collect::<Vec<_>>();
+        assert_eq!(percentile(&hundred, 50), 50);
+        assert_eq!(percentile(&hundred, 95), 95);
+        assert_eq!(percentile(&hundred, 99), 99);
+        assert_eq!(percentile(&hundred, 100), 100);
+    }
+
+    #[test]
+    fn statistics_by_name() {
+        let span = |id, name: &str, begin, end| SpanData {
+            id,
+            parent: 0,
+            depth: 1,
+            begin,
+            end,
+            name: name.to_string(),
+        };
+        let threads = [
+            ThreadSpans {
+                stream_id: "first".to_string(),
+                max_depth: 1,
+                spans: vec![span(1, "a", 0, 10), span(2, "b", 10, 15)],
+                blocks: Vec::new(),
+            },
+            ThreadSpans {
+                stream_id: "second".to_string(),
+                max_depth: 1,
+                spans: vec![span(1, "a", 0, 30)],
+                blocks: Vec::new(),
+            },
+        ];
+
+        let mut statistics = compute_span_statistics(&threads);
+        statistics.sort_by(|left, right| left.name.cmp(&right.name));
+
+        assert_eq!(
+            statistics,
+            [
+                SpanStatistics {
+                    name: "a".to_string(),
+                    count: 2,
+                    total: 40,
+                    mean: 20,
+                    p50: 10,
+                    p95: 30,
+                    p99: 30,
+                    max: 30,
+                },
+                SpanStatistics {
+                    name: "b".to_string(),
+                    count: 1,
+                    total: 5,
+                    mean: 5,
+                    p50: 5,
+                    p95: 5,
+                    p99: 5,
+                    max: 5,
+                },
+            ]
+        );
+    }
+}
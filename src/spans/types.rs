@@ -0,0 +1,117 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::ticks::TicksConverter;
+use crate::types::Block;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Span {
+    pub stream_id: String,
+    pub id: i64,
+    pub parent: i64,
+    pub depth: u32,
+    pub begin: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub name: String,
+    pub target: String,
+}
+
+/// A span with its boundaries converted to nanoseconds, ready to be aggregated or drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanData {
+    pub id: i64,
+    pub parent: i64,
+    pub depth: u32,
+    pub begin: i64,
+    pub end: i64,
+    pub name: String,
+}
+
+/// All the spans of a given thread, sorted by begin time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreadSpans {
+    pub stream_id: String,
+    pub max_depth: u32,
+    pub spans: Vec<SpanData>,
+    /// Time ranges (in nanoseconds) covered by the blocks of the thread's stream
+    pub blocks: Vec<(i64, i64)>,
+}
+
+/// Groups the spans by thread, preserving the order in which the threads first appear.
+pub fn group_spans_by_thread(spans: Vec<Span>) -> Vec<ThreadSpans> {
+    let mut threads: Vec<ThreadSpans> = Vec::new();
+
+    for span in spans {
+        let Some(begin) = span.begin.timestamp_nanos_opt() else {
+            error!(
+                begin = span.begin.to_rfc3339(),
+                "conversion to nanoseconds overflow"
+            );
+            continue;
+        };
+        let Some(end) = span.end.timestamp_nanos_opt() else {
+            error!(
+                end = span.end.to_rfc3339(),
+                "conversion to nanoseconds overflow"
+            );
+            continue;
+        };
+
+        let span_data = SpanData {
+            id: span.id,
+            parent: span.parent,
+            depth: span.depth,
+            begin,
+            end,
+            name: span.name,
+        };
+
+        match threads
+            .iter_mut()
+            .find(|thread| thread.stream_id == span.stream_id)
+        {
+            Some(thread) => {
+                thread.max_depth = thread.max_depth.max(span.depth);
+                thread.spans.push(span_data);
+            }
+            None => threads.push(ThreadSpans {
+                stream_id: span.stream_id,
+                max_depth: span.depth,
+                spans: vec![span_data],
+                blocks: Vec::new(),
+            }),
+        }
+    }
+
+    for thread in &mut threads {
+        thread.spans.sort_by_key(|span| span.begin);
+    }
+
+    threads
+}
+
+/// Converts the raw ticks of the blocks and assigns them to their thread.
+pub fn assign_thread_blocks(
+    threads: &mut [ThreadSpans],
+    blocks: &[Block],
+    ticks_converter: &TicksConverter,
+) {
+    for block in blocks {
+        let Some(thread) = threads
+            .iter_mut()
+            .find(|thread| thread.stream_id == block.stream_id)
+        else {
+            continue;
+        };
+
+        thread.blocks.push((
+            ticks_converter.ticks_to_ns(block.begin_ticks),
+            ticks_converter.ticks_to_ns(block.end_ticks),
+        ));
+    }
+
+    for thread in threads {
+        thread.blocks.sort_unstable();
+    }
+}
@@ -0,0 +1,417 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use leptos::{
+    component, create_action, create_effect, create_memo, create_rw_signal, create_signal, ev,
+    event_target_value, store_value, view, For, IntoView, Params, RwSignal, Signal, SignalGet,
+    SignalGetUntracked, SignalSet, SignalUpdate, SignalWith,
+};
+use leptos_router::{use_query, Params};
+
+use crate::components::Spinner;
+use crate::inspector::InspectedRequest;
+use crate::measures::types::ChartMeasures;
+use crate::measures::MeasuresChart;
+use crate::request::{fetch_record_batches, QueryRequest, QUERY_URL};
+
+use super::history::{load_history, push_history, HistoryEntry};
+use super::types::{Cell, QueryResult};
+use super::visualize::{
+    measures_range, time_columns, to_measures_data, value_columns, ChartColumns,
+};
+
+/// Only the first rows are rendered, the row count is displayed in any case.
+const MAX_DISPLAYED_ROWS: usize = 1_000;
+
+const DEFAULT_SQL: &str = "SELECT * FROM processes ORDER BY start_time DESC LIMIT 10";
+
+#[derive(Params, PartialEq)]
+struct SqlConsoleQuery {
+    sql: Option<String>,
+    begin: Option<String>,
+    end: Option<String>,
+}
+
+/// Runs arbitrary queries against the analytics server, the SQL and time range can be provided with
+/// `?sql=`, `?begin=` and `?end=`.
+#[component]
+pub fn SqlConsole() -> impl IntoView {
+    let now = Utc::now();
+    let sql = create_rw_signal(DEFAULT_SQL.to_string());
+    let begin = create_rw_signal((now - Duration::days(1)).to_rfc3339());
+    let end = create_rw_signal(now.to_rfc3339());
+    let history = create_rw_signal(load_history());
+
+    apply_query_params(sql, begin, end);
+
+    let run = create_action(move |entry: &HistoryEntry| {
+        let entry = entry.clone();
+        async move { run_query(&entry).await.map_err(|err| err.to_string()) }
+    });
+
+    let handle_run = move || {
+        let entry = HistoryEntry {
+            sql: sql.get_untracked(),
+            begin: begin.get_untracked(),
+            end: end.get_untracked(),
+        };
+        history.update(|history| push_history(history, entry.clone()));
+        run.dispatch(entry);
+    };
+
+    let handle_keydown = move |evt: ev::KeyboardEvent| {
+        if evt.key() == "Enter" && (evt.ctrl_key() || evt.meta_key()) {
+            evt.prevent_default();
+            handle_run();
+        }
+    };
+
+    let restore = move |entry: HistoryEntry| {
+        sql.set(entry.sql);
+        begin.set(entry.begin);
+        end.set(entry.end);
+    };
+
+    view! {
+        <div class="w-full p-4 flex flex-col gap-4">
+            <textarea
+                class="font-mono"
+                rows="8"
+                spellcheck="false"
+                prop:value=move || sql.get()
+                on:input=move |evt| sql.set(event_target_value(&evt))
+                on:keydown=handle_keydown
+            ></textarea>
+            <div class="flex gap-4 items-baseline">
+                <label>
+                    "Begin" <input type="text" prop:value=move || begin.get() on:input=move |evt| begin.set(event_target_value(&evt)) />
+                </label>
+                <label>
+                    "End" <input type="text" prop:value=move || end.get() on:input=move |evt| end.set(event_target_value(&evt)) />
+                </label>
+                <button disabled=move || run.pending().get() on:click=move |_| handle_run()>
+                    "Run"
+                </button>
+                <span class="text-sm">"Ctrl + Enter to run"</span>
+            </div>
+            {move || {
+                if run.pending().get() {
+                    return view! { <Spinner /> }.into_view();
+                }
+                match run.value().get() {
+                    None => ().into_view(),
+                    Some(Err(err)) => view! { <p class="text-red-500">{err}</p> }.into_view(),
+                    Some(Ok(result)) => view! { <QueryResultView result /> }.into_view(),
+                }
+            }}
+            <details>
+                <summary>"History"</summary>
+                <ul>
+                    <For
+                        each=move || history.get().into_iter().enumerate()
+                        key=|(index, entry)| (*index, entry.sql.clone())
+                        let:entry
+                    >
+                        {
+                            let (_index, entry) = entry;
+                            let label = entry.sql.clone();
+                            view! {
+                                <li>
+                                    <a href="#" class="font-mono" on:click=move |evt| {
+                                        evt.prevent_default();
+                                        restore(entry.clone());
+                                    }>
+                                        {label}
+                                    </a>
+                                </li>
+                            }
+                        }
+                    </For>
+                </ul>
+            </details>
+        </div>
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResultMode {
+    Table,
+    Visualize,
+}
+
+#[component]
+fn QueryResultView(result: QueryResult) -> impl IntoView {
+    let (mode, set_mode) = create_signal(ResultMode::Table);
+    let result = store_value(result);
+
+    view! {
+        <div class="flex gap-2">
+            <button
+                class:outline=move || mode.get() != ResultMode::Table
+                on:click=move |_| set_mode.set(ResultMode::Table)
+            >
+                "Table"
+            </button>
+            <button
+                class:outline=move || mode.get() != ResultMode::Visualize
+                on:click=move |_| set_mode.set(ResultMode::Visualize)
+            >
+                "Visualize"
+            </button>
+        </div>
+        {move || match mode.get() {
+            ResultMode::Table => view! { <ResultGrid result=result.get_value() /> }.into_view(),
+            ResultMode::Visualize => view! { <ResultChart result=result.get_value() /> }.into_view(),
+        }}
+    }
+}
+
+/// Plots the numeric columns of a result against one of its timestamp columns.
+#[component]
+fn ResultChart(result: QueryResult) -> impl IntoView {
+    let time_columns = time_columns(&result);
+    let value_columns = value_columns(&result);
+    let column_names = result
+        .schema
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .collect::<Vec<_>>();
+
+    let columns = create_rw_signal(ChartColumns::guess(&result));
+    let result = store_value(result);
+
+    let measures = create_memo(move |_| {
+        columns.with(|columns| result.with_value(|result| Some(to_measures_data(result, columns))))
+    });
+    let range = create_memo(move |_| measures.with(|measures| measures_range(measures.as_ref()?)));
+    let measures = Signal::derive(move || measures.get().map(ChartMeasures::Decoded));
+
+    if time_columns.is_empty() || value_columns.is_empty() {
+        return view! { <p>"Plotting requires a timestamp column and a numeric column"</p> }
+            .into_view();
+    }
+
+    let name = move |column: usize| column_names[column].clone();
+    let name = store_value(name);
+
+    let handle_time_change = move |evt: ev::Event| {
+        let time = event_target_value(&evt).parse().ok();
+        columns.update(|columns| columns.time = time);
+    };
+
+    let handle_group_by_change = move |evt: ev::Event| {
+        let group_by = event_target_value(&evt).parse().ok();
+        columns.update(|columns| columns.group_by = group_by);
+    };
+
+    let toggle_value = move |column: usize| {
+        columns.update(|columns| {
+            if let Some(index) = columns.values.iter().position(|value| *value == column) {
+                columns.values.remove(index);
+            } else {
+                columns.values.push(column);
+            }
+        });
+    };
+
+    let group_by_options = (0..result.with_value(|result| result.schema.fields().len()))
+        .filter(|column| !time_columns.contains(column) && !value_columns.contains(column))
+        .map(|column| view! { <option value=column.to_string()>{name.with_value(|name| name(column))}</option> })
+        .collect::<Vec<_>>();
+
+    view! {
+        <div class="flex gap-4 items-baseline flex-wrap">
+            <label>
+                "Time"
+                <select on:change=handle_time_change>
+                    {time_columns
+                        .iter()
+                        .map(|column| view! { <option value=column.to_string()>{name.with_value(|name| name(*column))}</option> })
+                        .collect::<Vec<_>>()}
+                </select>
+            </label>
+            <fieldset class="flex gap-2">
+                <legend>"Values"</legend>
+                {value_columns
+                    .iter()
+                    .copied()
+                    .map(|column| {
+                        view! {
+                            <label>
+                                <input
+                                    type="checkbox"
+                                    prop:checked=move || columns.with(|columns| columns.values.contains(&column))
+                                    on:change=move |_| toggle_value(column)
+                                />
+                                {name.with_value(|name| name(column))}
+                            </label>
+                        }
+                    })
+                    .collect::<Vec<_>>()}
+            </fieldset>
+            <label>
+                "Group by"
+                <select on:change=handle_group_by_change>
+                    <option value="">"None"</option>
+                    {group_by_options}
+                </select>
+            </label>
+        </div>
+        <MeasuresChart measures range=range.into() is_loading=Signal::derive(|| false) />
+    }
+    .into_view()
+}
+
+#[component]
+fn ResultGrid(result: QueryResult) -> impl IntoView {
+    let QueryResult {
+        schema,
+        rows,
+        batch_count,
+        bytes,
+        latency,
+    } = result;
+
+    let row_count = rows.len();
+    let sort = create_rw_signal::<Option<(usize, bool)>>(None);
+
+    let sorted_rows = create_memo(move |_| {
+        let mut rows = rows.clone();
+        if let Some((column, ascending)) = sort.get() {
+            rows.sort_by(|a, b| {
+                let ordering = a[column].compare(&b[column]);
+                if ascending {
+                    ordering
+                } else {
+                    ordering.reverse()
+                }
+            });
+        }
+        rows.truncate(MAX_DISPLAYED_ROWS);
+        rows
+    });
+
+    let toggle_sort = move |column: usize| {
+        sort.update(|sort| {
+            *sort = match *sort {
+                Some((current, ascending)) if current == column => Some((column, !ascending)),
+                _ => Some((column, true)),
+            };
+        });
+    };
+
+    let sort_indicator = move |column: usize| match sort.get() {
+        Some((current, true)) if current == column => " ▲",
+        Some((current, false)) if current == column => " ▼",
+        _ => "",
+    };
+
+    let headers = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(column, field)| {
+            view! {
+                <th scope="col" class="cursor-pointer whitespace-nowrap" title=field.data_type().to_string() on:click=move |_| toggle_sort(column)>
+                    {field.name().clone()}
+                    {move || sort_indicator(column)}
+                </th>
+            }
+        })
+        .collect::<Vec<_>>();
+
+    #[expect(clippy::cast_precision_loss)]
+    let summary = format!(
+        "{row_count} row(s) in {batch_count} batch(es), {:.1} KB, {latency:.0} ms",
+        bytes as f64 / 1_024.0
+    );
+    let truncated = (row_count > MAX_DISPLAYED_ROWS)
+        .then(|| format!(", showing the first {MAX_DISPLAYED_ROWS}"));
+
+    view! {
+        <p class="text-sm">{summary} {truncated}</p>
+        <div class="overflow-auto">
+            <table class="striped">
+                <thead>
+                    <tr>{headers}</tr>
+                </thead>
+                <tbody>
+                    {move || {
+                        sorted_rows
+                            .get()
+                            .into_iter()
+                            .map(|row| {
+                                view! {
+                                    <tr>
+                                        {row
+                                            .into_iter()
+                                            .map(|cell| {
+                                                view! {
+                                                    <td
+                                                        class:text-right=cell.is_numeric()
+                                                        class:opacity-50=cell == Cell::Null
+                                                    >
+                                                        {cell.display()}
+                                                    </td>
+                                                }
+                                            })
+                                            .collect::<Vec<_>>()}
+                                    </tr>
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    }}
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
+/// Applies the SQL and time range provided in the URL, if any.
+fn apply_query_params(sql: RwSignal<String>, begin: RwSignal<String>, end: RwSignal<String>) {
+    let query = use_query::<SqlConsoleQuery>();
+
+    create_effect(move |_| {
+        query.with(|query| {
+            let Ok(query) = query else {
+                return;
+            };
+            if let Some(query_sql) = &query.sql {
+                sql.set(query_sql.clone());
+            }
+            if let Some(query_begin) = &query.begin {
+                begin.set(query_begin.clone());
+            }
+            if let Some(query_end) = &query.end {
+                end.set(query_end.clone());
+            }
+        });
+    });
+}
+
+async fn run_query(entry: &HistoryEntry) -> Result<QueryResult> {
+    let parse = |time: &str| {
+        DateTime::parse_from_rfc3339(time)
+            .map(|time| time.with_timezone(&Utc).to_rfc3339())
+            .map_err(|err| anyhow!("invalid time {time:?}: {err}"))
+    };
+
+    let request = QueryRequest {
+        sql: entry.sql.clone(),
+        begin: Some(parse(&entry.begin)?),
+        end: Some(parse(&entry.end)?),
+    };
+
+    let inspected = InspectedRequest::start(QUERY_URL, &request);
+
+    let start = js_sys::Date::now();
+    let result = fetch_record_batches(QUERY_URL, &request, &inspected)
+        .await
+        .and_then(|(schema, batches, bytes)| {
+            let latency = js_sys::Date::now() - start;
+            QueryResult::try_new(schema, &batches, bytes, latency)
+        });
+
+    inspected.finish(&result);
+    result
+}
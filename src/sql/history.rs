@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use web_sys::Storage;
+
+const HISTORY_KEY: &str = "sirius.sql.history";
+const MAX_HISTORY_LEN: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub sql: String,
+    pub begin: String,
+    pub end: String,
+}
+
+fn local_storage() -> Option<Storage> {
+    leptos::window()
+        .local_storage()
+        .map_err(|err| error!("local storage error: {err:?}"))
+        .ok()
+        .flatten()
+}
+
+/// The previously executed queries, most recent first.
+pub fn load_history() -> Vec<HistoryEntry> {
+    let Some(history) =
+        local_storage().and_then(|storage| storage.get_item(HISTORY_KEY).ok().flatten())
+    else {
+        return Vec::new();
+    };
+
+    serde_json::from_str(&history).unwrap_or_else(|err| {
+        error!("sql history deserialization error: {err}");
+        Vec::new()
+    })
+}
+
+/// Adds `entry` on top of the history, removing its previous occurrence if any.
+pub fn push_history(history: &mut Vec<HistoryEntry>, entry: HistoryEntry) {
+    history.retain(|previous| *previous != entry);
+    history.insert(0, entry);
+    history.truncate(MAX_HISTORY_LEN);
+
+    let Some(storage) = local_storage() else {
+        return;
+    };
+
+    match serde_json::to_string(history) {
+        Ok(history) => {
+            if let Err(err) = storage.set_item(HISTORY_KEY, &history) {
+                error!("sql history storage error: {err:?}");
+            }
+        }
+        Err(err) => error!("sql history serialization error: {err}"),
+    }
+}
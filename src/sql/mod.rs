@@ -0,0 +1,6 @@
+mod components;
+mod history;
+pub mod types;
+mod visualize;
+
+pub use components::SqlConsole;
@@ -0,0 +1,151 @@
+use std::cmp::Ordering;
+
+use anyhow::Result;
+use arrow::array::{Array, ArrayRef, AsArray, RecordBatch};
+use arrow::compute::cast;
+use arrow::datatypes::{
+    DataType, Float64Type, Int64Type, SchemaRef, TimeUnit, TimestampNanosecondType,
+};
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+use chrono::DateTime;
+
+use crate::datetime::display_datetime;
+
+/// A value of the result grid, typed after the Arrow type of its column.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    /// Nanoseconds since the Unix epoch
+    Timestamp(i64),
+    Text(String),
+}
+
+impl Cell {
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Self::Integer(_) | Self::Float(_))
+    }
+
+    pub fn display(&self) -> String {
+        match self {
+            Self::Null => "null".to_string(),
+            Self::Boolean(value) => value.to_string(),
+            Self::Integer(value) => value.to_string(),
+            Self::Float(value) => value.to_string(),
+            Self::Timestamp(value) => display_datetime(DateTime::from_timestamp_nanos(*value)),
+            Self::Text(value) => value.clone(),
+        }
+    }
+
+    /// Nulls come first, values of different types are considered equal.
+    pub fn compare(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Null, Self::Null) => Ordering::Equal,
+            (Self::Null, _) => Ordering::Less,
+            (_, Self::Null) => Ordering::Greater,
+            (Self::Boolean(a), Self::Boolean(b)) => a.cmp(b),
+            (Self::Integer(a), Self::Integer(b)) | (Self::Timestamp(a), Self::Timestamp(b)) => {
+                a.cmp(b)
+            }
+            (Self::Float(a), Self::Float(b)) => a.total_cmp(b),
+            (Self::Text(a), Self::Text(b)) => a.cmp(b),
+            _ => Ordering::Equal,
+        }
+    }
+}
+
+/// The result of a query, converted to rows of typed cells.
+#[derive(Debug, Clone)]
+pub struct QueryResult {
+    pub schema: SchemaRef,
+    pub rows: Vec<Vec<Cell>>,
+    pub batch_count: usize,
+    pub bytes: usize,
+    /// Time spent sending the request and decoding the response, in milliseconds
+    pub latency: f64,
+}
+
+impl QueryResult {
+    pub fn try_new(
+        schema: SchemaRef,
+        batches: &[RecordBatch],
+        bytes: usize,
+        latency: f64,
+    ) -> Result<Self> {
+        let mut rows = Vec::new();
+
+        for batch in batches {
+            let columns = batch
+                .columns()
+                .iter()
+                .map(column_cells)
+                .collect::<Result<Vec<_>>>()?;
+
+            for row_index in 0..batch.num_rows() {
+                rows.push(
+                    columns
+                        .iter()
+                        .map(|column| column[row_index].clone())
+                        .collect(),
+                );
+            }
+        }
+
+        Ok(Self {
+            schema,
+            rows,
+            batch_count: batches.len(),
+            bytes,
+            latency,
+        })
+    }
+}
+
+fn column_cells(array: &ArrayRef) -> Result<Vec<Cell>> {
+    let cells = match array.data_type() {
+        DataType::Boolean => array
+            .as_boolean()
+            .iter()
+            .map(|value| value.map_or(Cell::Null, Cell::Boolean))
+            .collect(),
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32 => cast(array, &DataType::Int64)?
+            .as_primitive::<Int64Type>()
+            .iter()
+            .map(|value| value.map_or(Cell::Null, Cell::Integer))
+            .collect(),
+        DataType::UInt64 | DataType::Float16 | DataType::Float32 | DataType::Float64 => {
+            cast(array, &DataType::Float64)?
+                .as_primitive::<Float64Type>()
+                .iter()
+                .map(|value| value.map_or(Cell::Null, Cell::Float))
+                .collect()
+        }
+        DataType::Timestamp(_, _) => cast(array, &DataType::Timestamp(TimeUnit::Nanosecond, None))?
+            .as_primitive::<TimestampNanosecondType>()
+            .iter()
+            .map(|value| value.map_or(Cell::Null, Cell::Timestamp))
+            .collect(),
+        _ => {
+            let formatter = ArrayFormatter::try_new(array.as_ref(), &FormatOptions::default())?;
+            (0..array.len())
+                .map(|index| {
+                    if array.is_null(index) {
+                        Cell::Null
+                    } else {
+                        Cell::Text(formatter.value(index).to_string())
+                    }
+                })
+                .collect()
+        }
+    };
+
+    Ok(cells)
+}
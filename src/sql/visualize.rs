@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+
+use crate::measures::types::{MeasureSet, MeasuresData};
+
+use super::types::{Cell, QueryResult};
+
+/// The columns of a query result to plot.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChartColumns {
+    pub time: Option<usize>,
+    pub values: Vec<usize>,
+    /// Column whose values name the series
+    pub group_by: Option<usize>,
+}
+
+impl ChartColumns {
+    /// Picks the first timestamp column and all the numeric columns.
+    pub fn guess(result: &QueryResult) -> Self {
+        Self {
+            time: time_columns(result).first().copied(),
+            values: value_columns(result),
+            group_by: None,
+        }
+    }
+}
+
+/// The indices of the columns containing timestamps.
+pub fn time_columns(result: &QueryResult) -> Vec<usize> {
+    columns_matching(result, |cell| matches!(cell, Cell::Timestamp(_)))
+}
+
+/// The indices of the columns containing numbers.
+pub fn value_columns(result: &QueryResult) -> Vec<usize> {
+    columns_matching(result, Cell::is_numeric)
+}
+
+fn columns_matching(result: &QueryResult, predicate: impl Fn(&Cell) -> bool) -> Vec<usize> {
+    (0..result.schema.fields().len())
+        .filter(|column| {
+            result
+                .rows
+                .iter()
+                .map(|row| &row[*column])
+                .find(|cell| **cell != Cell::Null)
+                .is_some_and(&predicate)
+        })
+        .collect()
+}
+
+/// Converts the rows of a query result to series, one per value column and per group.
+pub fn to_measures_data(result: &QueryResult, columns: &ChartColumns) -> MeasuresData {
+    let mut measures_data = MeasuresData::new();
+    let Some(time_column) = columns.time else {
+        return measures_data;
+    };
+
+    for row in &result.rows {
+        let Cell::Timestamp(time) = row[time_column] else {
+            continue;
+        };
+
+        let group = columns.group_by.map(|group_by| row[group_by].display());
+
+        for value_column in &columns.values {
+            #[expect(clippy::cast_precision_loss)]
+            let value = match row[*value_column] {
+                Cell::Integer(value) => value as f64,
+                Cell::Float(value) => value,
+                _ => continue,
+            };
+
+            let column_name = result.schema.field(*value_column).name();
+            let name = match (&group, columns.values.len()) {
+                (Some(group), 1) => group.clone(),
+                (Some(group), _) => format!("{group} {column_name}"),
+                (None, _) => column_name.clone(),
+            };
+
+            measures_data
+                .entry(name)
+                .and_modify(|measure_set| {
+                    measure_set.min = measure_set.min.min(value);
+                    measure_set.max = measure_set.max.max(value);
+                    measure_set.start = measure_set.start.min(time);
+                    measure_set.end = measure_set.end.max(time);
+                    measure_set.measures.push((time, value));
+                })
+                .or_insert(MeasureSet {
+                    min: value,
+                    max: value,
+                    start: time,
+                    end: time,
+                    unit: String::new(),
+                    measures: vec![(time, value)],
+                });
+        }
+    }
+
+    for measure_set in measures_data.values_mut() {
+        measure_set.measures.sort_by_key(|(time, _value)| *time);
+    }
+
+    measures_data
+}
+
+/// The time range covered by all the series.
+pub fn measures_range(measures_data: &MeasuresData) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let start = measures_data
+        .values()
+        .map(|measure_set| measure_set.start)
+        .min()?;
+    let end = measures_data
+        .values()
+        .map(|measure_set| measure_set.end)
+        .max()?;
+    // A single timestamp would otherwise produce an empty range
+    let end = end.max(start + 1_000_000);
+
+    Some((
+        DateTime::from_timestamp_nanos(start),
+        DateTime::from_timestamp_nanos(end),
+    ))
+}
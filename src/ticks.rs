@@ -0,0 +1,150 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+
+use crate::types::ProcessInfo;
+
+const NANOSECONDS_PER_SECOND: i128 = 1_000_000_000;
+
+/// Converts the raw TSC ticks recorded by a process to wall-clock time.
+///
+/// Computations are done using 128 bits integers: a 64 bits float can't represent the tick
+/// count of a process running for more than a few weeks without losing nanosecond precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TicksConverter {
+    start_ticks: i64,
+    start_time_ns: i64,
+    tsc_frequency: i64,
+}
+
+impl TicksConverter {
+    pub fn try_new(process: &ProcessInfo) -> Result<Self> {
+        if process.tsc_frequency <= 0 {
+            bail!(
+                "invalid tsc frequency {} for process {}",
+                process.tsc_frequency,
+                process.process_id
+            );
+        }
+
+        let Some(start_time_ns) = process.start_time.timestamp_nanos_opt() else {
+            bail!(
+                "start time {} conversion to nanoseconds overflow",
+                process.start_time.to_rfc3339()
+            );
+        };
+
+        Ok(Self {
+            start_ticks: process.start_ticks,
+            start_time_ns,
+            tsc_frequency: process.tsc_frequency,
+        })
+    }
+
+    /// Converts a tick delta to nanoseconds, rounded to the nearest nanosecond.
+    pub fn delta_ticks_to_ns(&self, delta_ticks: i64) -> i64 {
+        let frequency = i128::from(self.tsc_frequency);
+        let scaled = i128::from(delta_ticks) * NANOSECONDS_PER_SECOND;
+        let rounded = (scaled + scaled.signum() * frequency / 2) / frequency;
+
+        // Saturates instead of wrapping, ~292 years of nanoseconds is out of reach anyway
+        i64::try_from(rounded).unwrap_or(if rounded < 0 { i64::MIN } else { i64::MAX })
+    }
+
+    /// Converts an absolute tick count to nanoseconds since the Unix epoch.
+    pub fn ticks_to_ns(&self, ticks: i64) -> i64 {
+        self.start_time_ns
+            .saturating_add(self.delta_ticks_to_ns(ticks.saturating_sub(self.start_ticks)))
+    }
+
+    /// Converts an absolute tick count to wall-clock time.
+    pub fn ticks_to_datetime(&self, ticks: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp_nanos(self.ticks_to_ns(ticks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GHZ: i64 = 1_000_000_000;
+    const YEAR_S: i64 = 365 * 24 * 3600;
+
+    fn process(tsc_frequency: i64, start_ticks: i64) -> ProcessInfo {
+        ProcessInfo {
+            tsc_frequency,
+            start_ticks,
+            ..ProcessInfo::for_tests(
+                "process",
+                DateTime::from_timestamp_nanos(1_700_000_000_123_456_789),
+            )
+        }
+    }
+
+    fn converter(tsc_frequency: i64, start_ticks: i64) -> TicksConverter {
+        TicksConverter::try_new(&process(tsc_frequency, start_ticks)).unwrap()
+    }
+
+    #[test]
+    fn large_tick_counts_keep_nanosecond_precision() {
+        let converter = converter(3 * GHZ, 0);
+        let year_ticks = 3 * GHZ * YEAR_S;
+
+        assert_eq!(converter.delta_ticks_to_ns(year_ticks), YEAR_S * GHZ);
+        // A third of a nanosecond is rounded down, two thirds up
+        assert_eq!(converter.delta_ticks_to_ns(year_ticks + 1), YEAR_S * GHZ);
+        assert_eq!(
+            converter.delta_ticks_to_ns(year_ticks + 2),
+            YEAR_S * GHZ + 1
+        );
+        assert_eq!(
+            converter.delta_ticks_to_ns(year_ticks + 3),
+            YEAR_S * GHZ + 1
+        );
+    }
+
+    #[test]
+    fn uneven_frequencies_round_to_nearest() {
+        // 2.4 GHz plus one tick per second, one year being 75_686_400_031_536_000 ticks
+        let converter = converter(2_400_000_001, 0);
+        assert_eq!(
+            converter.delta_ticks_to_ns(2_400_000_001 * YEAR_S),
+            YEAR_S * GHZ
+        );
+        assert_eq!(converter.delta_ticks_to_ns(1), 0);
+        assert_eq!(converter.delta_ticks_to_ns(2), 1);
+        // 499_999_999.79 ns
+        assert_eq!(converter.delta_ticks_to_ns(1_200_000_000), 500_000_000);
+        assert_eq!(converter.delta_ticks_to_ns(1_199_999_999), 499_999_999);
+
+        // Rounding is symmetric around zero
+        assert_eq!(converter.delta_ticks_to_ns(-2), -1);
+        assert_eq!(converter.delta_ticks_to_ns(-1_200_000_000), -500_000_000);
+    }
+
+    #[test]
+    fn absolute_ticks_are_relative_to_start() {
+        let converter = converter(GHZ, 5_000);
+        let start_ns = 1_700_000_000_123_456_789;
+
+        assert_eq!(converter.ticks_to_ns(5_000), start_ns);
+        assert_eq!(converter.ticks_to_ns(4_000), start_ns - 1_000);
+        assert_eq!(
+            converter.ticks_to_datetime(5_000 + GHZ * YEAR_S),
+            DateTime::from_timestamp_nanos(start_ns + YEAR_S * GHZ)
+        );
+    }
+
+    #[test]
+    fn conversions_saturate() {
+        let converter = converter(1, 0);
+        assert_eq!(converter.delta_ticks_to_ns(i64::MAX), i64::MAX);
+        assert_eq!(converter.delta_ticks_to_ns(i64::MIN), i64::MIN);
+        assert_eq!(converter.ticks_to_ns(i64::MAX), i64::MAX);
+    }
+
+    #[test]
+    fn invalid_frequency_is_rejected() {
+        assert!(TicksConverter::try_new(&process(0, 0)).is_err());
+        assert!(TicksConverter::try_new(&process(-GHZ, 0)).is_err());
+    }
+}
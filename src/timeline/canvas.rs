@@ -0,0 +1,184 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use leptos::{html::Canvas, HtmlElement};
+use tracing::{debug, error};
+use web_sys::CanvasRenderingContext2d;
+
+use crate::flamegraph::truncate_label;
+use crate::measures::canvas::{render_time_axis, time_to_x, x_to_time, LabelTimeZone};
+use crate::spans::color::get_span_color;
+use crate::spans::types::ThreadSpans;
+use crate::use_canvas::context_2d;
+
+const AXIS_HEIGHT: f64 = 40.0;
+const LANE_HEADER_HEIGHT: f64 = 18.0;
+const ROW_HEIGHT: f64 = 16.0;
+
+/// Background of the parts of a lane covered by the blocks of the thread.
+const BLOCK_COLOR: &str = "#1f2633";
+
+/// Spans narrower than this (in pixels) don't display their name.
+const MIN_LABEL_WIDTH: f64 = 32.0;
+
+pub struct TimelineCanvas {
+    ctx: CanvasRenderingContext2d,
+}
+
+impl TimelineCanvas {
+    pub fn try_new(node: &HtmlElement<Canvas>) -> Result<Self> {
+        Ok(Self {
+            ctx: context_2d(node)?,
+        })
+    }
+
+    #[expect(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
+        threads: &[ThreadSpans],
+        begin: DateTime<Utc>,
+        end: DateTime<Utc>,
+        width: f64,
+        height: f64,
+        hovered: Option<(usize, usize)>,
+        dpr: f64,
+    ) {
+        debug!("rendering timeline");
+
+        if width < f64::EPSILON || height < f64::EPSILON {
+            return;
+        }
+
+        let Some(begin_ns) = begin.timestamp_nanos_opt() else {
+            error!(begin = begin.to_rfc3339(), "nanoseconds conversion error");
+            return;
+        };
+        let Some(end_ns) = end.timestamp_nanos_opt() else {
+            error!(end = end.to_rfc3339(), "nanoseconds conversion error");
+            return;
+        };
+
+        self.ctx.save();
+        if let Err(err) = self.ctx.scale(dpr, dpr) {
+            error!(dpr, "context scaling failed: {err:?}");
+        }
+        self.ctx.set_font("12px Arial");
+        self.ctx.set_fill_style(&"#13171f".into());
+        self.ctx.fill_rect(0.0, 0.0, width, height);
+        self.ctx.set_fill_style(&"white".into());
+
+        render_time_axis(&mut self.ctx, width, 0.0, begin, end, LabelTimeZone::Local);
+
+        let mut lane_y = AXIS_HEIGHT;
+        for (thread_index, thread) in threads.iter().enumerate() {
+            self.ctx.set_fill_style(&"white".into());
+            if let Err(err) = self.ctx.fill_text(
+                &format!("thread {}", thread.stream_id),
+                4.0,
+                lane_y + LANE_HEADER_HEIGHT - 5.0,
+            ) {
+                error!("fill text error: {err:?}");
+            }
+
+            let rows_y = lane_y + LANE_HEADER_HEIGHT;
+
+            self.ctx.set_fill_style(&BLOCK_COLOR.into());
+            for (block_begin, block_end) in &thread.blocks {
+                if *block_end < begin_ns || *block_begin > end_ns {
+                    continue;
+                }
+
+                let x = time_to_x(*block_begin, begin_ns, end_ns, width).max(0.0);
+                let right = time_to_x(*block_end, begin_ns, end_ns, width).min(width);
+                self.ctx.fill_rect(
+                    x,
+                    rows_y,
+                    (right - x).max(1.0),
+                    lane_height(thread) - LANE_HEADER_HEIGHT,
+                );
+            }
+
+            for (span_index, span) in thread.spans.iter().enumerate() {
+                if span.end < begin_ns || span.begin > end_ns {
+                    continue;
+                }
+
+                let x = time_to_x(span.begin, begin_ns, end_ns, width).max(0.0);
+                let right = time_to_x(span.end, begin_ns, end_ns, width).min(width);
+                let span_width = (right - x).max(1.0);
+                let y = rows_y + f64::from(span.depth) * ROW_HEIGHT;
+
+                let color = if hovered == Some((thread_index, span_index)) {
+                    "#ffffff".to_string()
+                } else {
+                    get_span_color(&span.name)
+                };
+                self.ctx.set_fill_style(&color.into());
+                self.ctx.fill_rect(x, y, span_width, ROW_HEIGHT - 1.0);
+
+                if span_width < MIN_LABEL_WIDTH {
+                    continue;
+                }
+
+                self.ctx.set_fill_style(&"#13171f".into());
+                if let Err(err) = self.ctx.fill_text(
+                    &truncate_label(&span.name, span_width),
+                    x + 4.0,
+                    y + ROW_HEIGHT - 4.0,
+                ) {
+                    error!("fill text error: {err:?}");
+                }
+            }
+
+            lane_y += lane_height(thread);
+        }
+
+        self.ctx.restore();
+    }
+}
+
+fn lane_height(thread: &ThreadSpans) -> f64 {
+    LANE_HEADER_HEIGHT + f64::from(thread.max_depth + 1) * ROW_HEIGHT
+}
+
+/// The height (in css pixels) required to display all the threads.
+pub fn timeline_height(threads: &[ThreadSpans]) -> f64 {
+    AXIS_HEIGHT + threads.iter().map(lane_height).sum::<f64>()
+}
+
+/// Returns the thread and span indices of the span under the provided position, if any.
+pub fn find_span_at(
+    threads: &[ThreadSpans],
+    x: f64,
+    y: f64,
+    begin_ns: i64,
+    end_ns: i64,
+    width: f64,
+) -> Option<(usize, usize)> {
+    let time = x_to_time(x, begin_ns, end_ns, width);
+
+    let mut lane_y = AXIS_HEIGHT;
+    for (thread_index, thread) in threads.iter().enumerate() {
+        let lane_height = lane_height(thread);
+        if y < lane_y || y >= lane_y + lane_height {
+            lane_y += lane_height;
+            continue;
+        }
+
+        let rows_y = lane_y + LANE_HEADER_HEIGHT;
+        if y < rows_y {
+            return None;
+        }
+
+        #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let depth = ((y - rows_y) / ROW_HEIGHT) as u32;
+
+        // Spans are sorted by begin time, so only those starting before the cursor can match
+        let candidates = thread.spans.partition_point(|span| span.begin <= time);
+        return thread.spans[..candidates]
+            .iter()
+            .rposition(|span| span.depth == depth && span.end >= time)
+            .map(|span_index| (thread_index, span_index));
+    }
+
+    None
+}
@@ -0,0 +1,232 @@
+use ev::{MouseEvent, WheelEvent};
+use leptos::html::Canvas as CanvasNode;
+use leptos::{
+    component, ev, view, Callable, IntoView, NodeRef, Signal, SignalGet, SignalSet, SignalWith,
+};
+use leptos_router::A;
+
+use crate::components::{Spinner, TimeSelectionOverlay};
+use crate::datetime::display_duration;
+use crate::export::ExportTraceButton;
+use crate::measures::canvas::x_to_time;
+use crate::measures::viewport::Viewport;
+use crate::use_time_selection::{use_time_selection, UseTimeSelectionReturn};
+
+use super::canvas::find_span_at;
+use super::create_timeline::{create_timeline, CreateTimelineReturn};
+
+/// The change of the displayed duration for each wheel event.
+const WHEEL_ZOOM_FACTOR: f64 = 1.1;
+
+#[expect(clippy::too_many_lines)]
+#[component]
+pub fn Timeline() -> impl IntoView {
+    let CreateTimelineReturn {
+        canvas_node_ref,
+
+        set_dragging,
+        set_mouse_x,
+        set_mouse_y,
+        set_begin,
+        set_end,
+        set_hovered,
+
+        process_id,
+        process,
+        is_loading,
+        is_dragging,
+        canvas_width,
+        mouse_x,
+        mouse_y,
+        begin,
+        end,
+        threads,
+        hovered,
+        timeline_height,
+        canvas_actual_width,
+        canvas_actual_height,
+    } = create_timeline();
+
+    let UseTimeSelectionReturn {
+        selection,
+        is_selecting,
+        start_selection,
+        update_selection,
+        finish_selection,
+        set_raw_selection,
+    } = use_time_selection();
+
+    let mouse_time = move || {
+        Some(x_to_time(
+            mouse_x.get(),
+            begin.get().timestamp_nanos_opt()?,
+            end.get().timestamp_nanos_opt()?,
+            canvas_width.get(),
+        ))
+    };
+
+    let viewport = move || Viewport::from_range((begin.get(), end.get()));
+    let set_viewport = move |viewport: Viewport| {
+        set_begin.set(viewport.begin());
+        set_end.set(viewport.end());
+    };
+
+    let handle_mousedown = move |evt: MouseEvent| {
+        if evt.alt_key() {
+            if let Some(time) = mouse_time() {
+                start_selection.call(time);
+            }
+        } else {
+            set_dragging.set(true);
+        }
+    };
+
+    let handle_mouseup = move |_evt: MouseEvent| {
+        set_dragging.set(false);
+        finish_selection.call(());
+    };
+
+    let handle_mousemove = move |evt: MouseEvent| {
+        let Some(canvas_node) = canvas_node_ref.get() else {
+            return;
+        };
+
+        let dom_rect = canvas_node.get_bounding_client_rect();
+
+        set_mouse_x.set(f64::from(evt.client_x()) - dom_rect.left());
+        set_mouse_y.set(f64::from(evt.client_y()) - dom_rect.top());
+
+        if is_selecting.get() {
+            if let Some(time) = mouse_time() {
+                update_selection.call(time);
+            }
+            return;
+        }
+
+        if !is_dragging.get() {
+            let hovered_span = threads.with(|threads| {
+                find_span_at(
+                    threads,
+                    mouse_x.get(),
+                    mouse_y.get(),
+                    begin.get().timestamp_nanos_opt()?,
+                    end.get().timestamp_nanos_opt()?,
+                    canvas_width.get(),
+                )
+            });
+            if hovered_span != hovered.get() {
+                set_hovered.set(hovered_span);
+            }
+            return;
+        }
+
+        if let Some(viewport) = viewport() {
+            set_viewport(viewport.pan(f64::from(evt.movement_x()), canvas_width.get(), None));
+        }
+    };
+
+    let handle_mouseleave = move |_evt: MouseEvent| {
+        set_hovered.set(None);
+    };
+
+    let handle_wheel = move |evt: WheelEvent| {
+        let factor = if evt.delta_y() < 0.0 {
+            1.0 / WHEEL_ZOOM_FACTOR
+        } else if evt.delta_y() > 0.0 {
+            WHEEL_ZOOM_FACTOR
+        } else {
+            return;
+        };
+
+        if let Some(viewport) = viewport() {
+            set_viewport(viewport.zoom(factor, mouse_x.get(), canvas_width.get(), None));
+        }
+    };
+
+    let export_range = Signal::derive(move || {
+        selection.get().unwrap_or_else(|| {
+            (
+                begin.get().timestamp_nanos_opt().unwrap_or_default(),
+                end.get().timestamp_nanos_opt().unwrap_or_default(),
+            )
+        })
+    });
+
+    let hovered_label = move || {
+        let (thread_index, span_index) = hovered.get()?;
+        threads.with(|threads| {
+            let span = threads.get(thread_index)?.spans.get(span_index)?;
+            Some(format!(
+                "{} — {}",
+                span.name,
+                display_duration(span.end - span.begin)
+            ))
+        })
+    };
+
+    view! {
+        <div class="w-full p-4 flex flex-col gap-1">
+            <div class="flex gap-4">
+                <A href=move || format!("/measures/{}", process_id.get())>"Measures"</A>
+                <A href=move || format!("/log/{}", process_id.get())>"Log"</A>
+                <A href=move || format!("/spans/{}", process_id.get())>"Span statistics"</A>
+                <span class="text-sm">"Drag to pan, scroll to zoom, alt + drag to select"</span>
+                <ExportTraceButton process threads range=export_range />
+            </div>
+            <div class="h-6 truncate">{move || hovered_label().unwrap_or_default()}</div>
+            <div
+                class="w-full relative"
+                on:mousedown=handle_mousedown
+                on:mouseup=handle_mouseup
+                on:mousemove=handle_mousemove
+                on:mouseleave=handle_mouseleave
+                on:wheel=handle_wheel
+            >
+                <div
+                    class="w-full h-full flex justify-center items-center"
+                    class:hidden=move || !is_loading.get()
+                >
+                    <Spinner />
+                </div>
+
+                <TimeSelectionOverlay
+                    process_id
+                    selection
+                    begin
+                    end
+                    canvas_width
+                    on_clear=move |()| set_raw_selection.set(None)
+                />
+
+                <Canvas
+                    canvas_node_ref
+                    actual_width=canvas_actual_width
+                    actual_height=canvas_actual_height
+                    height=timeline_height
+                    is_loading
+                ></Canvas>
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn Canvas(
+    canvas_node_ref: NodeRef<CanvasNode>,
+    actual_width: Signal<f64>,
+    actual_height: Signal<f64>,
+    height: Signal<f64>,
+    is_loading: Signal<bool>,
+) -> impl IntoView {
+    view! {
+        <canvas
+            class="border border-black w-full"
+            class:hidden=move || is_loading.get()
+            node_ref=canvas_node_ref
+            width=actual_width
+            height=actual_height
+            style:width="100%"
+            style:height=move || format!("{}px", height.get())
+        />
+    }
+}
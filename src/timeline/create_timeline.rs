@@ -0,0 +1,186 @@
+use std::{cell::RefCell, rc::Rc};
+
+use chrono::{DateTime, Duration, Utc};
+use leptos::{
+    create_effect, create_memo, create_signal, html::Canvas, Memo, NodeRef, Params, Signal,
+    SignalGet, SignalSet, SignalWith, WriteSignal,
+};
+use leptos_router::{use_params, use_query, Params};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::{
+    spans::{
+        create_process_spans::{create_process_spans, CreateProcessSpansReturn},
+        types::ThreadSpans,
+    },
+    types::ProcessInfo,
+    use_canvas::{use_canvas, UseCanvasReturn},
+};
+
+use super::canvas::{timeline_height, TimelineCanvas};
+
+pub struct CreateTimelineReturn {
+    pub canvas_node_ref: NodeRef<Canvas>,
+
+    pub set_dragging: WriteSignal<bool>,
+    pub set_mouse_x: WriteSignal<f64>,
+    pub set_mouse_y: WriteSignal<f64>,
+    pub set_begin: WriteSignal<DateTime<Utc>>,
+    pub set_end: WriteSignal<DateTime<Utc>>,
+    pub set_hovered: WriteSignal<Option<(usize, usize)>>,
+
+    pub process_id: Signal<String>,
+    pub process: Memo<Option<ProcessInfo>>,
+    pub is_loading: Signal<bool>,
+    pub is_dragging: Signal<bool>,
+    pub canvas_width: Signal<f64>,
+    pub mouse_x: Signal<f64>,
+    pub mouse_y: Signal<f64>,
+    pub begin: Signal<DateTime<Utc>>,
+    pub end: Signal<DateTime<Utc>>,
+    pub threads: Signal<Vec<ThreadSpans>>,
+    pub hovered: Signal<Option<(usize, usize)>>,
+    pub timeline_height: Signal<f64>,
+    pub canvas_actual_width: Signal<f64>,
+    pub canvas_actual_height: Signal<f64>,
+}
+
+pub fn create_timeline() -> CreateTimelineReturn {
+    let id = use_params_id();
+
+    let UseCanvasReturn {
+        node_ref: canvas_node_ref,
+        dpr,
+        width: canvas_width,
+        height: canvas_height,
+        actual_width: canvas_actual_width,
+        actual_height: canvas_actual_height,
+    } = use_canvas();
+
+    let (canvas, set_canvas) = create_signal::<Option<Rc<RefCell<TimelineCanvas>>>>(None);
+    let (is_dragging, set_dragging) = create_signal(false);
+    let (mouse_x, set_mouse_x) = create_signal(0.0);
+    let (mouse_y, set_mouse_y) = create_signal(0.0);
+    let (hovered, set_hovered) = create_signal::<Option<(usize, usize)>>(None);
+
+    let (begin, set_begin) = create_signal(Utc::now() - Duration::hours(24));
+    let (end, set_end) = create_signal(begin.get() + Duration::hours(25));
+
+    let CreateProcessSpansReturn {
+        process,
+        threads,
+        range,
+        is_loading,
+    } = create_process_spans(id);
+
+    let query = use_query::<TimelineQuery>();
+    let query_range = create_memo(move |_| {
+        query.with(|query| {
+            let query = query.as_ref().ok()?;
+            Some((
+                DateTime::from_timestamp_nanos(query.begin?),
+                DateTime::from_timestamp_nanos(query.end?),
+            ))
+        })
+    });
+
+    create_effect(move |_| {
+        // The range provided in the query takes precedence over the process lifetime
+        if let Some((query_begin, query_end)) = query_range.get() {
+            set_begin.set(query_begin);
+            set_end.set(query_end);
+            return;
+        }
+
+        let Some((range_begin, range_end)) = range.get() else {
+            return;
+        };
+
+        set_begin.set(range_begin);
+        set_end.set(range_end);
+    });
+
+    create_effect(move |_| {
+        let Some(node) = canvas_node_ref.get() else {
+            return;
+        };
+
+        match TimelineCanvas::try_new(&node) {
+            Ok(timeline_canvas) => set_canvas.set(Some(Rc::new(RefCell::new(timeline_canvas)))),
+            Err(err) => error!("timeline canvas failed to initialize: {err}"),
+        }
+    });
+
+    create_effect(move |_| {
+        canvas_width.track();
+        canvas_height.track();
+
+        let Some(canvas) = canvas.get() else {
+            return;
+        };
+
+        threads.with(|threads| {
+            canvas.borrow_mut().render(
+                threads,
+                begin.get(),
+                end.get(),
+                canvas_width.get(),
+                canvas_height.get(),
+                hovered.get(),
+                dpr.get(),
+            );
+        });
+    });
+
+    CreateTimelineReturn {
+        canvas_node_ref,
+
+        set_dragging,
+        set_mouse_x,
+        set_mouse_y,
+        set_begin,
+        set_end,
+        set_hovered,
+
+        process_id: Signal::derive(move || id.get().to_string()),
+        process,
+        is_loading,
+        is_dragging: is_dragging.into(),
+        canvas_width,
+        mouse_x: mouse_x.into(),
+        mouse_y: mouse_y.into(),
+        begin: begin.into(),
+        end: end.into(),
+        threads,
+        hovered: hovered.into(),
+        timeline_height: Signal::derive(move || threads.with(|threads| timeline_height(threads))),
+        canvas_actual_width,
+        canvas_actual_height,
+    }
+}
+
+#[derive(Params, PartialEq)]
+struct TimelineParams {
+    id: Option<Uuid>,
+}
+
+#[derive(Params, PartialEq)]
+struct TimelineQuery {
+    begin: Option<i64>,
+    end: Option<i64>,
+}
+
+fn use_params_id() -> Signal<Uuid> {
+    let params = use_params::<TimelineParams>();
+    let id = move || {
+        params.with(|params| {
+            params
+                .as_ref()
+                .ok()
+                .and_then(|params| params.id)
+                .unwrap_or_default()
+        })
+    };
+    id.into()
+}
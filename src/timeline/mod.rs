@@ -0,0 +1,5 @@
+mod canvas;
+mod components;
+mod create_timeline;
+
+pub use components::Timeline;
@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProcessInfo {
     pub process_id: String,
     pub exe: String,
@@ -16,3 +16,43 @@ pub struct ProcessInfo {
     pub parent_process_id: Option<String>,
     // pub properties: HashMap<String, String>,
 }
+
+#[cfg(test)]
+impl ProcessInfo {
+    /// A process started at `start_time`, with a 1 GHz clock starting at zero ticks.
+    pub fn for_tests(process_id: &str, start_time: DateTime<Utc>) -> Self {
+        Self {
+            process_id: process_id.to_string(),
+            exe: String::new(),
+            username: String::new(),
+            realname: String::new(),
+            computer: String::new(),
+            distro: String::new(),
+            cpu_brand: String::new(),
+            tsc_frequency: 1_000_000_000,
+            start_time,
+            start_ticks: 0,
+            parent_process_id: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stream {
+    pub process_id: String,
+    pub stream_id: String,
+}
+
+/// The end of a block, to find when a process sent its last one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockEnd {
+    pub end_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Block {
+    pub stream_id: String,
+    pub begin_ticks: i64,
+    pub end_ticks: i64,
+    pub nb_objects: i32,
+}
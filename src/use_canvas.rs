@@ -1,6 +1,9 @@
+use anyhow::{anyhow, bail, Result};
 use leptos::html::Canvas as CanvasNode;
-use leptos::{create_node_ref, NodeRef, Signal, SignalGet};
+use leptos::{create_node_ref, HtmlElement, NodeRef, Signal, SignalGet};
 use leptos_use::{use_device_pixel_ratio, use_element_size, UseElementSizeReturn};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, OffscreenCanvas};
 
 pub struct UseCanvasReturn {
     pub node_ref: NodeRef<CanvasNode>,
@@ -27,3 +30,83 @@ pub fn use_canvas() -> UseCanvasReturn {
         actual_height: actual_height.into(),
     }
 }
+
+fn context_options(alpha: bool) -> Result<JsValue> {
+    #[derive(serde::Serialize)]
+    struct ContextOptions {
+        alpha: bool,
+    }
+
+    serde_wasm_bindgen::to_value(&ContextOptions { alpha })
+        .map_err(|err| anyhow!("context options serialization error: {err}"))
+}
+
+/// Returns the opaque 2d context of the provided canvas.
+pub fn context_2d(node: &HtmlElement<CanvasNode>) -> Result<CanvasRenderingContext2d> {
+    (**node).context_2d(false)
+}
+
+/// A canvas drawn on by the page, or transferred to a worker.
+pub trait BitmapCanvas {
+    /// Returns the 2d context of the canvas, transparent if `alpha`.
+    fn context_2d(&self, alpha: bool) -> Result<CanvasRenderingContext2d>;
+
+    fn bitmap_size(&self) -> (u32, u32);
+
+    /// Resizes the bitmap, which clears the canvas, even to the same size.
+    fn set_bitmap_size(&self, width: u32, height: u32);
+
+    /// Resizes the bitmap only if its size changed.
+    fn fit_bitmap(&self, size: (u32, u32)) {
+        if self.bitmap_size() != size {
+            self.set_bitmap_size(size.0, size.1);
+        }
+    }
+}
+
+impl BitmapCanvas for HtmlCanvasElement {
+    fn context_2d(&self, alpha: bool) -> Result<CanvasRenderingContext2d> {
+        let ctx = self
+            .get_context_with_context_options("2d", &context_options(alpha)?)
+            .map_err(|err| anyhow!("{err:?}"))?;
+        let Some(ctx) = ctx else {
+            bail!("canvas' 2d context not found");
+        };
+
+        ctx.dyn_into()
+            .map_err(|err| anyhow!("context dyn conversion error: {err:?}"))
+    }
+
+    fn bitmap_size(&self) -> (u32, u32) {
+        (self.width(), self.height())
+    }
+
+    fn set_bitmap_size(&self, width: u32, height: u32) {
+        self.set_width(width);
+        self.set_height(height);
+    }
+}
+
+impl BitmapCanvas for OffscreenCanvas {
+    /// The context is an `OffscreenCanvasRenderingContext2D`, used through the bindings of
+    /// `CanvasRenderingContext2D` which has the same drawing methods, all bound by name.
+    fn context_2d(&self, alpha: bool) -> Result<CanvasRenderingContext2d> {
+        let ctx = self
+            .get_context_with_context_options("2d", &context_options(alpha)?)
+            .map_err(|err| anyhow!("{err:?}"))?;
+        let Some(ctx) = ctx else {
+            bail!("offscreen canvas' 2d context not found");
+        };
+
+        Ok(ctx.unchecked_into())
+    }
+
+    fn bitmap_size(&self) -> (u32, u32) {
+        (self.width(), self.height())
+    }
+
+    fn set_bitmap_size(&self, width: u32, height: u32) {
+        self.set_width(width);
+        self.set_height(height);
+    }
+}
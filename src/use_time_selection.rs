@@ -0,0 +1,57 @@
+use leptos::{
+    create_memo, create_signal, Callback, Signal, SignalGet, SignalSet, SignalUpdate, WriteSignal,
+};
+
+pub struct UseTimeSelectionReturn {
+    /// The normalized selection, in nanoseconds, `None` when nothing is selected
+    pub selection: Signal<Option<(i64, i64)>>,
+    pub is_selecting: Signal<bool>,
+    pub start_selection: Callback<i64>,
+    pub update_selection: Callback<i64>,
+    pub finish_selection: Callback<()>,
+    pub set_raw_selection: WriteSignal<Option<(i64, i64)>>,
+}
+
+/// Tracks a time range selected by dragging over a canvas.
+pub fn use_time_selection() -> UseTimeSelectionReturn {
+    // The raw selection keeps the anchor first, and the cursor last
+    let (raw_selection, set_raw_selection) = create_signal::<Option<(i64, i64)>>(None);
+    let (is_selecting, set_selecting) = create_signal(false);
+
+    let selection = create_memo(move |_| {
+        let (anchor, cursor) = raw_selection.get()?;
+        if anchor == cursor {
+            return None;
+        }
+        Some((anchor.min(cursor), anchor.max(cursor)))
+    });
+
+    let start_selection = move |time: i64| {
+        set_raw_selection.set(Some((time, time)));
+        set_selecting.set(true);
+    };
+
+    let update_selection = move |time: i64| {
+        if !is_selecting.get() {
+            return;
+        }
+        set_raw_selection.update(|raw_selection| {
+            if let Some((_anchor, cursor)) = raw_selection {
+                *cursor = time;
+            }
+        });
+    };
+
+    let finish_selection = move |()| {
+        set_selecting.set(false);
+    };
+
+    UseTimeSelectionReturn {
+        selection: selection.into(),
+        is_selecting: is_selecting.into(),
+        start_selection: start_selection.into(),
+        update_selection: update_selection.into(),
+        finish_selection: finish_selection.into(),
+        set_raw_selection,
+    }
+}
@@ -0,0 +1,206 @@
+use std::{cell::RefCell, rc::Rc};
+
+use anyhow::{anyhow, Result};
+use js_sys::{Array, Object, Reflect};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use wasm_bindgen::{closure::Closure, prelude::wasm_bindgen, JsCast, JsValue};
+use web_sys::{
+    Blob, BlobPropertyBag, DedicatedWorkerGlobalScope, MessageEvent, Url, Worker, WorkerOptions,
+    WorkerType,
+};
+
+/// Posted by a worker once it listens to the messages of the page.
+const READY: &str = "ready";
+
+#[wasm_bindgen]
+extern "C" {
+    type ImportMeta;
+
+    #[wasm_bindgen(method, getter)]
+    fn url(this: &ImportMeta) -> String;
+
+    /// The `import.meta` of the JS glue code, which locates the application module.
+    #[wasm_bindgen(thread_local, js_namespace = import, js_name = meta)]
+    static IMPORT_META: ImportMeta;
+}
+
+/// What the application does when loaded in a worker rather than in the page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerRole {
+    /// Fetches and shapes the data, see `pipeline`
+    Pipeline,
+    /// Renders the charts on transferred canvases, see `offscreen`
+    Renderer,
+}
+
+impl WorkerRole {
+    const ALL: [Self; 2] = [Self::Pipeline, Self::Renderer];
+
+    /// Also displayed by the browser's developer tools.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Pipeline => "sirius-pipeline",
+            Self::Renderer => "sirius-renderer",
+        }
+    }
+}
+
+/// The role of the worker the application is running in, `None` in the page.
+pub fn current_role() -> Option<WorkerRole> {
+    let scope = js_sys::global()
+        .dyn_into::<DedicatedWorkerGlobalScope>()
+        .ok()?;
+    let name = scope.name();
+
+    WorkerRole::ALL.into_iter().find(|role| role.name() == name)
+}
+
+type Queue = Rc<RefCell<Option<Vec<(JsValue, Array)>>>>;
+
+/// A worker running the application module in the given role.
+///
+/// The messages posted before the worker is ready are queued, since the module is still
+/// initializing and wouldn't receive them.
+pub struct ModuleWorker {
+    worker: Worker,
+    /// `None` once the worker is ready
+    queued: Queue,
+}
+
+impl ModuleWorker {
+    /// `on_error` is called if the worker fails, e.g. because module workers aren't supported.
+    pub fn spawn(
+        role: WorkerRole,
+        on_message: impl Fn(&JsValue) + 'static,
+        on_error: impl Fn() + 'static,
+    ) -> Result<Self> {
+        let module_url = serde_json::to_string(&IMPORT_META.with(ImportMeta::url))?;
+        let script = format!("import init from {module_url};\nawait init();\n");
+
+        let options = BlobPropertyBag::new();
+        options.set_type("text/javascript");
+        let blob = Blob::new_with_str_sequence_and_options(&Array::of1(&script.into()), &options)
+            .map_err(|err| anyhow!("worker script creation error: {err:?}"))?;
+        let script_url = Url::create_object_url_with_blob(&blob)
+            .map_err(|err| anyhow!("worker script url error: {err:?}"))?;
+
+        let options = WorkerOptions::new();
+        options.set_type(WorkerType::Module);
+        options.set_name(role.name());
+        let worker = Worker::new_with_options(&script_url, &options)
+            .map_err(|err| anyhow!("worker creation error: {err:?}"))?;
+
+        let queued: Queue = Rc::new(RefCell::new(Some(Vec::new())));
+
+        let on_message = Closure::<dyn Fn(MessageEvent)>::new({
+            let worker = worker.clone();
+            let queued = Rc::clone(&queued);
+            move |event: MessageEvent| {
+                let data = event.data();
+                if data.as_string().as_deref() != Some(READY) {
+                    on_message(&data);
+                    return;
+                }
+
+                for (message, transfer) in queued.take().unwrap_or_default() {
+                    if let Err(err) = worker.post_message_with_transfer(&message, &transfer) {
+                        error!("worker message error: {err:?}");
+                    }
+                }
+            }
+        });
+        worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        on_message.forget();
+
+        let on_error = Closure::<dyn Fn(web_sys::Event)>::new(move |_event| on_error());
+        worker.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        on_error.forget();
+
+        Ok(Self { worker, queued })
+    }
+
+    /// Posts a message wrapped by `to_js_message`.
+    pub fn post(&self, message: JsValue, transfer: Array) -> Result<()> {
+        if let Some(queued) = self.queued.borrow_mut().as_mut() {
+            queued.push((message, transfer));
+            return Ok(());
+        }
+
+        self.worker
+            .post_message_with_transfer(&message, &transfer)
+            .map_err(|err| anyhow!("worker message error: {err:?}"))
+    }
+}
+
+impl Drop for ModuleWorker {
+    fn drop(&mut self) {
+        self.worker.terminate();
+    }
+}
+
+fn worker_scope() -> DedicatedWorkerGlobalScope {
+    js_sys::global().unchecked_into()
+}
+
+/// Handles the messages of the page for the whole lifetime of the worker, then signals the page
+/// that the worker is ready.
+pub fn listen_to_page(on_message: impl Fn(&JsValue) + 'static) {
+    let scope = worker_scope();
+
+    let on_message =
+        Closure::<dyn Fn(MessageEvent)>::new(move |event: MessageEvent| on_message(&event.data()));
+    scope.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    on_message.forget();
+
+    if let Err(err) = scope.post_message(&READY.into()) {
+        error!("worker ready message error: {err:?}");
+    }
+}
+
+/// Posts a message wrapped by `to_js_message` from the worker to the page.
+pub fn post_to_page(message: &JsValue, transfer: &Array) {
+    if let Err(err) = worker_scope().post_message_with_transfer(message, transfer) {
+        error!("worker message error: {err:?}");
+    }
+}
+
+/// Wraps a message along with the objects transferred with it, e.g. buffers or canvases.
+///
+/// Messages are serialized as JSON since timestamps in nanoseconds don't fit in JS numbers.
+pub fn to_js_message<T: AsRef<JsValue>>(
+    message: &impl Serialize,
+    transfer: &[T],
+) -> Result<(JsValue, Array), String> {
+    let json = serde_json::to_string(message).map_err(|err| err.to_string())?;
+    let transfer = transfer.iter().map(AsRef::as_ref).collect::<Array>();
+
+    let js_message = Object::new();
+    Reflect::set(&js_message, &"json".into(), &json.into()).map_err(|err| format!("{err:?}"))?;
+    Reflect::set(&js_message, &"transfer".into(), &transfer).map_err(|err| format!("{err:?}"))?;
+
+    Ok((js_message.into(), transfer))
+}
+
+/// The message and transferred objects wrapped by `to_js_message`, the objects not of type `T`
+/// are ignored.
+pub fn from_js_message<M: for<'de> Deserialize<'de>, T: JsCast>(
+    js_message: &JsValue,
+) -> Result<(M, Vec<T>), String> {
+    let json = Reflect::get(js_message, &"json".into())
+        .ok()
+        .and_then(|json| json.as_string())
+        .ok_or("message without JSON")?;
+    let message = serde_json::from_str(&json).map_err(|err| err.to_string())?;
+
+    let transfer = Reflect::get(js_message, &"transfer".into())
+        .map(|transfer| {
+            Array::from(&transfer)
+                .iter()
+                .filter_map(|object| object.dyn_into::<T>().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok((message, transfer))
+}